@@ -0,0 +1,173 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::chain::ChainCost;
+use crate::groth16::Groth16Cost;
+use crate::hashes::KnownAnswer;
+use crate::merkle::MerkleCost;
+
+// How many iterations each sample runs
+#[derive(Clone, Copy, Debug)]
+pub enum Iterations {
+    Fixed(usize),
+    // Spread a total time budget over all samples, calibrated after warmup
+    TimeBudget(Duration),
+}
+
+// How a single benchmark is run
+#[derive(Clone, Copy, Debug)]
+pub struct BenchConfig {
+    pub warmup: Duration,
+    pub samples: usize,
+    pub iterations: Iterations,
+}
+
+// Distribution of per-iteration times across samples, in nanoseconds
+#[derive(Clone, Copy, Debug)]
+pub struct Stats {
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub ci95_low: f64,
+    pub ci95_high: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct BenchResult {
+    pub name: String,
+    pub samples: usize,
+    pub iterations_per_sample: usize,
+    pub stats: Stats,
+}
+
+// Everything measured for one hash during a run
+pub struct Measurement {
+    pub timing: Option<BenchResult>,
+    pub setup: Vec<BenchResult>,
+    pub groth16: Option<Groth16Cost>,
+    // One entry per tree depth; empty unless measured
+    pub merkle: Vec<MerkleCost>,
+    pub chain: Option<ChainCost>,
+    // Only from `verify`
+    pub known_answers: Vec<KnownAnswer>,
+}
+
+// Runs `f` for the warmup period, then times `samples` batches of
+// `iterations_per_sample` calls each. The closure's result goes through
+// `black_box` so the optimizer cannot drop the work being measured.
+pub fn run<T, F: FnMut() -> T>(name: &str, config: &BenchConfig, mut f: F) -> BenchResult {
+    let samples = config.samples.max(1);
+
+    // Always run at least once so a time budget has something to calibrate on
+    let warmup_start = Instant::now();
+    let mut warmup_runs = 0u32;
+    while warmup_runs == 0 || warmup_start.elapsed() < config.warmup {
+        black_box(f());
+        warmup_runs += 1;
+    }
+    let per_call = warmup_start.elapsed() / warmup_runs;
+
+    let iterations = match config.iterations {
+        Iterations::Fixed(n) => n.max(1),
+        Iterations::TimeBudget(budget) => {
+            let per_sample = budget.as_nanos() / samples as u128;
+            (per_sample / per_call.as_nanos().max(1)).max(1) as usize
+        }
+    };
+    let samples: Vec<f64> = (0..samples)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..iterations {
+                black_box(f());
+            }
+            start.elapsed().as_nanos() as f64 / iterations as f64
+        })
+        .collect();
+
+    BenchResult {
+        name: name.to_string(),
+        samples: samples.len(),
+        iterations_per_sample: iterations,
+        stats: Stats::from_samples(samples),
+    }
+}
+
+// A single timed run, reported in the same shape as a benchmark
+pub fn single_run(name: &str, elapsed: Duration) -> BenchResult {
+    BenchResult {
+        name: name.to_string(),
+        samples: 1,
+        iterations_per_sample: 1,
+        stats: Stats::from_samples(vec![elapsed.as_nanos() as f64]),
+    }
+}
+
+impl Stats {
+    pub fn from_samples(mut samples: Vec<f64>) -> Stats {
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let n = samples.len() as f64;
+
+        let mean = samples.iter().sum::<f64>() / n;
+        let variance = if samples.len() > 1 {
+            samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)
+        } else {
+            0.0
+        };
+        let stddev = variance.sqrt();
+
+        // 95% confidence interval of the mean (normal approximation)
+        let margin = 1.96 * stddev / n.sqrt();
+
+        Stats {
+            mean,
+            median: percentile(&samples, 50.0),
+            stddev,
+            min: samples[0],
+            max: samples[samples.len() - 1],
+            p95: percentile(&samples, 95.0),
+            p99: percentile(&samples, 99.0),
+            ci95_low: mean - margin,
+            ci95_high: mean + margin,
+        }
+    }
+
+    // Input bytes per second at the mean time, in MB/s (10^6 bytes); None
+    // for an empty input
+    pub fn throughput_mb_s(&self, input_bytes: usize) -> Option<f64> {
+        (input_bytes > 0 && self.mean > 0.0).then(|| input_bytes as f64 / self.mean * 1e3)
+    }
+}
+
+// Linear interpolation between closest ranks; `sorted` must be non-empty
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+// Auto-scales a nanosecond figure to ns/μs/ms/s
+pub fn format_duration(ns: f64) -> String {
+    if ns < 1e3 {
+        format!("{:.2} ns", ns)
+    } else if ns < 1e6 {
+        format!("{:.2} μs", ns / 1e3)
+    } else if ns < 1e9 {
+        format!("{:.2} ms", ns / 1e6)
+    } else {
+        format!("{:.2} s", ns / 1e9)
+    }
+}
+
+// Scales a MB/s figure down to KB/s below 1 MB/s
+pub fn format_throughput(mb_s: f64) -> String {
+    if mb_s < 1.0 {
+        format!("{:.1} KB/s", mb_s * 1e3)
+    } else {
+        format!("{:.1} MB/s", mb_s)
+    }
+}
@@ -0,0 +1,97 @@
+use std::hint::black_box;
+
+use crate::bench::{self, BenchConfig, BenchResult};
+use crate::circuits::{self, ChainCircuit, NodeGadget, R1csCost};
+use crate::fields::NamedField;
+use crate::groth16::Groth16Cost;
+use crate::hashes::HashError;
+use crate::nova::{self, NovaCost};
+
+// Length of the chain, and how much proving to do
+#[derive(Clone, Debug)]
+pub struct ChainSettings {
+    pub steps: usize,
+    pub hashes_per_step: usize,
+    // Native step timing
+    pub native: BenchConfig,
+    // Larger chains are synthesized but not proved with Groth16, and larger
+    // steps not folded with Nova; 0 proves none
+    pub max_proof_constraints: usize,
+    pub proof_samples: usize,
+}
+
+// Cost of a hash chain: one step as a circuit of its own, the whole chain
+// proved at once, and the chain as incrementally verified steps folded
+// with Nova
+#[derive(Clone, Debug)]
+pub struct ChainCost {
+    pub steps: usize,
+    pub hashes_per_step: usize,
+    // One step, both ends public
+    pub step: R1csCost,
+    // Every step in one circuit, both ends public
+    pub chain: R1csCost,
+    pub native_step: BenchResult,
+    // The whole chain in one Groth16 proof; None above
+    // `max_proof_constraints` or over a field without Groth16
+    pub groth16: Option<Groth16Cost>,
+    // None for fewer than two steps, a step above `max_proof_constraints`
+    // or a hash without a gadget over BN254
+    pub nova: Option<NovaCost>,
+}
+
+// The chain from `digest(input)`, `node` hashing one child as `gadget` does.
+// `nova_gadget` is the same gadget over Nova's BN254 scalar field, if any.
+pub fn measure<F, G, N>(
+    gadget: &G,
+    nova_gadget: Option<&N>,
+    digest: impl Fn(&[u8]) -> Result<Vec<u8>, HashError>,
+    node: impl Fn(&[Vec<u8>]) -> Result<Vec<u8>, HashError>,
+    input: &[u8],
+    settings: &ChainSettings,
+) -> Result<ChainCost, HashError>
+where
+    F: NamedField,
+    G: NodeGadget<F>,
+    N: NodeGadget<nova::Scalar> + Sync,
+{
+    let step = |state: &[u8]| (0..settings.hashes_per_step).try_fold(state.to_vec(), |state, _| node(&[state]));
+    let start = digest(input)?;
+    let end = (0..settings.steps).try_fold(start.clone(), |state, _| step(&state))?;
+
+    let circuit = |steps: usize, end: &[u8]| ChainCircuit {
+        gadget,
+        start: start.clone(),
+        steps,
+        hashes_per_step: settings.hashes_per_step,
+        end: end.to_vec(),
+    };
+    let synthesis = |e: bellpepper_core::SynthesisError| HashError::Synthesis(e.to_string());
+    let step_r1cs = circuits::measure::<F, _>(circuit(1, &step(&start)?)).map_err(synthesis)?;
+    let chain = circuits::measure::<F, _>(circuit(settings.steps, &end)).map_err(synthesis)?;
+    let native_step = bench::run("chain step", &settings.native, || step(black_box(&start)));
+
+    let groth16 = match chain.constraints <= settings.max_proof_constraints {
+        true => {
+            let inputs = [gadget.public_inputs(&start), gadget.public_inputs(&end)].concat();
+            F::groth16(|| circuit(settings.steps, &end), &inputs, settings.proof_samples)?
+        }
+        false => None,
+    };
+    let nova = match nova_gadget {
+        Some(nova_gadget) if settings.steps > 1 && step_r1cs.constraints <= settings.max_proof_constraints => {
+            let (start, end) = (nova_gadget.public_inputs(&start), nova_gadget.public_inputs(&end));
+            Some(nova::prove(nova_gadget, settings.hashes_per_step, settings.steps, &start, &end, settings.proof_samples)?)
+        }
+        _ => None,
+    };
+    Ok(ChainCost {
+        steps: settings.steps,
+        hashes_per_step: settings.hashes_per_step,
+        step: step_r1cs,
+        chain,
+        native_step,
+        groth16,
+        nova,
+    })
+}
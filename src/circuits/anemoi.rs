@@ -0,0 +1,106 @@
+use bellpepper_core::num::AllocatedNum;
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+use super::merkle::{self, FieldGadget};
+use super::wire::Wire;
+
+// Anemoi with l = 2 over already-padded field elements, with the same additive
+// sponge as the native `hashes::Anemoi`. Each Flystel is checked in closed
+// form: the output y = v is a witness, and the circuit checks
+// (y - v)^alpha = x - g y^2 and sets the output x = g v^2 + delta + (y - v)^alpha,
+// 5 constraints for alpha = 5. The digest element is allocated at one
+// constraint.
+pub struct AnemoiCircuit<'a, F: PrimeField> {
+    pub rate: usize,
+    pub alpha: u64,
+    // 1 / alpha modulo p - 1, for the witness only
+    pub alpha_inv: &'a [u64],
+    pub generator: F,
+    pub delta: F,
+    // l per round, for the x and y halves
+    pub c: &'a [Vec<F>],
+    pub d: &'a [Vec<F>],
+    pub elements: Vec<F>,
+}
+
+impl<F: PrimeField> AnemoiCircuit<'_, F> {
+    // Mirrors `hashes::Anemoi::linear_layer`
+    fn linear_layer(&self, state: &mut [Wire<F>]) {
+        let g = self.generator;
+        let (x, y) = state.split_at_mut(state.len() / 2);
+        x[0] = x[0].add(&x[1].scale(g));
+        x[1] = x[1].add(&x[0].scale(g));
+        y[1] = y[1].add(&y[0].scale(g));
+        y[0] = y[0].add(&y[1].scale(g));
+        y.iter_mut().zip(x.iter()).for_each(|(y, x)| *y = y.add(x));
+        x.iter_mut().zip(y.iter()).for_each(|(x, y)| *x = x.add(y));
+    }
+
+    fn flystel<CS: ConstraintSystem<F>>(&self, mut cs: CS, x: &Wire<F>, y: &Wire<F>) -> Result<(Wire<F>, Wire<F>), SynthesisError> {
+        let g = self.generator;
+        let w_value = x.value - g * y.value.square();
+        let v = Wire::alloc(cs.namespace(|| "v"), y.value - w_value.pow_vartime(self.alpha_inv))?;
+
+        let y2 = y.mul(cs.namespace(|| "y^2"), y)?;
+        let v2 = v.mul(cs.namespace(|| "v^2"), &v)?;
+        let w = x.add(&y2.scale(-g));
+        let t = y.add(&v.scale(-F::ONE));
+        let (a, b) = t.power_factors(cs.namespace(|| "(y - v)^alpha"), self.alpha)?;
+        w.enforce_product(cs.namespace(|| "(y - v)^alpha = x - g y^2"), &a, &b);
+        let u = w.add(&v2.scale(g)).add(&Wire::constant::<CS>(self.delta));
+        Ok((u, v))
+    }
+
+    fn permute<CS: ConstraintSystem<F>>(&self, cs: &mut CS, state: &mut [Wire<F>]) -> Result<(), SynthesisError> {
+        let columns = state.len() / 2;
+        for (round, (c, d)) in self.c.iter().zip(self.d).enumerate() {
+            let mut cs = cs.namespace(|| format!("round {}", round));
+            for i in 0..columns {
+                state[i] = state[i].add(&Wire::constant::<CS>(c[i]));
+                state[columns + i] = state[columns + i].add(&Wire::constant::<CS>(d[i]));
+            }
+            self.linear_layer(state);
+            for i in 0..columns {
+                let (u, v) = self.flystel(cs.namespace(|| format!("flystel {}", i)), &state[i], &state[columns + i])?;
+                (state[i], state[columns + i]) = (u, v);
+            }
+        }
+        self.linear_layer(state);
+        Ok(())
+    }
+
+    // The additive sponge over already-padded elements; returns the first
+    // state element
+    fn absorb<CS: ConstraintSystem<F>>(&self, cs: &mut CS, elements: &[Wire<F>]) -> Result<Wire<F>, SynthesisError> {
+        let mut state = vec![Wire::constant::<CS>(F::ZERO); 2 * self.c[0].len()];
+        for (block_index, block) in elements.chunks(self.rate).enumerate() {
+            let mut cs = cs.namespace(|| format!("block {}", block_index));
+            for (i, element) in block.iter().enumerate() {
+                state[i] = state[i].add(element);
+            }
+            self.permute(&mut cs, &mut state)?;
+        }
+        Ok(state.swap_remove(0))
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for AnemoiCircuit<'_, F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let elements = self
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(i, value)| Wire::alloc(cs.namespace(|| format!("element {}", i)), *value))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.absorb(cs, &elements)?.allocate(cs.namespace(|| "digest"))?;
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> FieldGadget<F> for AnemoiCircuit<'_, F> {
+    fn hash_elements<CS: ConstraintSystem<F>>(&self, mut cs: CS, elements: &[AllocatedNum<F>]) -> Result<AllocatedNum<F>, SynthesisError> {
+        let padded = merkle::pad::<F, CS>(elements, self.rate)?;
+        self.absorb(&mut cs, &padded)?.allocate(cs.namespace(|| "digest"))
+    }
+}
@@ -0,0 +1,159 @@
+use bellpepper::gadgets::multieq::MultiEq;
+use bellpepper::gadgets::uint32::UInt32;
+use bellpepper_core::boolean::{AllocatedBit, Boolean};
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+use super::blake3::{block_words, g, G_LANES, IV};
+
+const BLOCK_BYTES: usize = 64;
+
+// Message word order per round
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+// Parameter block word 0 of unkeyed BLAKE2s-256: digest length 32, no key,
+// fanout and depth 1
+const PARAMETERS: u32 = 0x0101_0020;
+
+// The compression function. The byte counter and the last-block flag
+// follow the public input length, so they are constants.
+fn compress<F, CS>(cs: CS, h: &[UInt32], block: &[UInt32], counter: u64, last: bool) -> Result<Vec<UInt32>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let mut cs = MultiEq::new(cs);
+    let mut v: Vec<UInt32> = h.to_vec();
+    v.extend(IV[..4].iter().map(|&word| UInt32::constant(word)));
+    v.extend(
+        [IV[4] ^ counter as u32, IV[5] ^ (counter >> 32) as u32, IV[6] ^ if last { u32::MAX } else { 0 }, IV[7]]
+            .map(UInt32::constant),
+    );
+
+    for (round, sigma) in SIGMA.iter().enumerate() {
+        let mut cs = cs.namespace(|| format!("round {}", round));
+        for (i, &lanes) in G_LANES.iter().enumerate() {
+            g(cs.namespace(|| format!("G {}", i)), &mut v, lanes, &block[sigma[2 * i]], &block[sigma[2 * i + 1]])?;
+        }
+    }
+
+    (0..8)
+        .map(|i| {
+            let mixed = v[i].xor(cs.namespace(|| format!("output {}", i)), &v[i + 8])?;
+            mixed.xor(cs.namespace(|| format!("chain {}", i)), &h[i])
+        })
+        .collect()
+}
+
+// Zero-pads `bits`, given least significant first within each byte, with
+// `zero` to whole blocks, the empty input taking one block
+fn pad<T: Clone>(bits: &[T], zero: T) -> Vec<T> {
+    let mut padded = bits.to_vec();
+    padded.resize((bits.len() / 8).div_ceil(BLOCK_BYTES).max(1) * BLOCK_BYTES * 8, zero);
+    padded
+}
+
+// Unkeyed BLAKE2s-256 of a `len`-byte input given as `pad`'s padded blocks
+fn hash_padded<F, CS>(mut cs: CS, padded: &[Boolean], len: usize) -> Result<Vec<Boolean>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let mut h: Vec<UInt32> = IV.iter().map(|&word| UInt32::constant(word)).collect();
+    h[0] = UInt32::constant(IV[0] ^ PARAMETERS);
+    let blocks = padded.len() / (BLOCK_BYTES * 8);
+    for (i, block) in padded.chunks(BLOCK_BYTES * 8).enumerate() {
+        let counter = (len.min((i + 1) * BLOCK_BYTES)) as u64;
+        h = compress(cs.namespace(|| format!("block {}", i)), &h, &block_words(block), counter, i == blocks - 1)?;
+    }
+    Ok(h.into_iter().flat_map(|word| word.into_bits()).collect())
+}
+
+// Unkeyed BLAKE2s-256 of `input`, given as bits, least significant bit of
+// each byte first. The input length is public, so padding bits are
+// constants. Returns the 256 digest bits in the same order.
+pub fn blake2s<F, CS>(cs: CS, input: &[Boolean]) -> Result<Vec<Boolean>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    assert!(input.len().is_multiple_of(8), "BLAKE2s input must be whole bytes");
+    hash_padded(cs, &pad(input, Boolean::constant(false)), input.len() / 8)
+}
+
+// BLAKE2s-256 of a private preimage. The message is zero-padded natively
+// and every bit of the padded blocks is allocated (one boolean constraint
+// each), so no block folds to constants, not even for an empty input; the
+// byte counters and last-block flag follow the public input length. G is
+// BLAKE3's, with additions packed by bellpepper's `MultiEq` as in its
+// BLAKE2s gadget, and the digest's witness values are checked against
+// `expected`.
+pub struct Blake2sCircuit {
+    pub preimage: Vec<u8>,
+    pub expected: Vec<u8>,
+}
+
+impl<F: PrimeField> Circuit<F> for Blake2sCircuit {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let bits: Vec<bool> = self
+            .preimage
+            .iter()
+            .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+            .collect();
+        let padded = pad(&bits, false)
+            .into_iter()
+            .enumerate()
+            .map(|(i, bit)| {
+                AllocatedBit::alloc(cs.namespace(|| format!("padded bit {}", i)), Some(bit))
+                    .map(Boolean::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let digest = hash_padded(cs.namespace(|| "blake2s"), &padded, self.preimage.len())?;
+        let digest = digest
+            .chunks(8)
+            .map(|byte| {
+                byte.iter().rev().try_fold(0u8, |acc, bit| bit.get_value().map(|b| acc << 1 | b as u8))
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(SynthesisError::AssignmentMissing)?;
+        if digest != self.expected {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bellpepper_core::test_cs::TestConstraintSystem;
+    use bellpepper_core::Circuit;
+    use blstrs::Scalar as Fr;
+
+    use super::Blake2sCircuit;
+    use crate::hashes::{Blake2s, HashFunction};
+
+    // Lengths around the 64-byte block, where the last block is full or
+    // spills, and across several blocks
+    #[test]
+    fn digest_matches_native() {
+        for len in [0, 23, 64, 65, 200] {
+            let preimage: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let expected = Blake2s.hash(&preimage).unwrap();
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            Blake2sCircuit { preimage, expected }.synthesize(&mut cs).unwrap();
+            assert!(cs.is_satisfied(), "length {}: {:?}", len, cs.which_is_unsatisfied());
+        }
+    }
+}
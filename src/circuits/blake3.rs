@@ -0,0 +1,234 @@
+use bellpepper::gadgets::multieq::MultiEq;
+use bellpepper::gadgets::uint32::UInt32;
+use bellpepper_core::boolean::{AllocatedBit, Boolean};
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+const CHUNK_BYTES: usize = 1024;
+const BLOCK_BYTES: usize = 64;
+const ROUNDS: usize = 7;
+
+// SHA-256's initial hash value, BLAKE3's key when unkeyed, and BLAKE2s's IV
+pub(super) const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+// Applied to the message words between rounds
+const MESSAGE_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+const CHUNK_START: u32 = 1;
+const CHUNK_END: u32 = 2;
+const PARENT: u32 = 4;
+const ROOT: u32 = 8;
+
+// The columns, then the diagonals, of the 4x4 state each G mixes
+pub(super) const G_LANES: [(usize, usize, usize, usize); 8] =
+    [(0, 4, 8, 12), (1, 5, 9, 13), (2, 6, 10, 14), (3, 7, 11, 15), (0, 5, 10, 15), (1, 6, 11, 12), (2, 7, 8, 13), (3, 4, 9, 14)];
+
+// BLAKE2s's G with the same rotations
+pub(super) fn g<F, CS, M>(mut cs: M, v: &mut [UInt32], (a, b, c, d): (usize, usize, usize, usize), x: &UInt32, y: &UInt32) -> Result<(), SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+    M: ConstraintSystem<F, Root = MultiEq<F, CS>>,
+{
+    v[a] = UInt32::addmany(cs.namespace(|| "a + b + x"), &[v[a].clone(), v[b].clone(), x.clone()])?;
+    v[d] = v[d].xor(cs.namespace(|| "d ^ a"), &v[a])?.rotr(16);
+    v[c] = UInt32::addmany(cs.namespace(|| "c + d"), &[v[c].clone(), v[d].clone()])?;
+    v[b] = v[b].xor(cs.namespace(|| "b ^ c"), &v[c])?.rotr(12);
+    v[a] = UInt32::addmany(cs.namespace(|| "a + b + y"), &[v[a].clone(), v[b].clone(), y.clone()])?;
+    v[d] = v[d].xor(cs.namespace(|| "d ^ a again"), &v[a])?.rotr(8);
+    v[c] = UInt32::addmany(cs.namespace(|| "c + d again"), &[v[c].clone(), v[d].clone()])?;
+    v[b] = v[b].xor(cs.namespace(|| "b ^ c again"), &v[c])?.rotr(7);
+    Ok(())
+}
+
+// The compression function, truncated to the 8-word chaining value
+fn compress<F, CS>(cs: CS, cv: &[UInt32], block: &[UInt32], counter: u64, block_len: u32, flags: u32) -> Result<Vec<UInt32>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let mut cs = MultiEq::new(cs);
+    let mut v: Vec<UInt32> = cv.to_vec();
+    v.extend(IV[..4].iter().map(|&word| UInt32::constant(word)));
+    v.extend([counter as u32, (counter >> 32) as u32, block_len, flags].map(UInt32::constant));
+
+    let mut m = block.to_vec();
+    for round in 0..ROUNDS {
+        let mut cs = cs.namespace(|| format!("round {}", round));
+        for (i, &lanes) in G_LANES.iter().enumerate() {
+            g(cs.namespace(|| format!("G {}", i)), &mut v, lanes, &m[2 * i], &m[2 * i + 1])?;
+        }
+        m = MESSAGE_PERMUTATION.iter().map(|&j| m[j].clone()).collect();
+    }
+
+    (0..8).map(|i| v[i].xor(cs.namespace(|| format!("output {}", i)), &v[i + 8])).collect()
+}
+
+// Words of a whole 64-byte block given as bits, least significant first
+pub(super) fn block_words(bits: &[Boolean]) -> Vec<UInt32> {
+    bits.chunks(32).map(UInt32::from_bits).collect()
+}
+
+// Byte lengths of the chunks of a `len`-byte input, the empty input being
+// one empty chunk
+fn chunk_lens(len: usize) -> Vec<usize> {
+    match len {
+        0 => vec![0],
+        _ => (0..len.div_ceil(CHUNK_BYTES)).map(|i| (len - i * CHUNK_BYTES).min(CHUNK_BYTES)).collect(),
+    }
+}
+
+// A chunk's bytes once its last block is zero-padded, an empty chunk taking
+// one whole block
+fn padded_chunk_bytes(len: usize) -> usize {
+    len.div_ceil(BLOCK_BYTES).max(1) * BLOCK_BYTES
+}
+
+// Zero-pads the last block of every chunk of `bits`, given least
+// significant first within each byte, with `zero`
+fn pad<T: Clone>(bits: &[T], zero: T) -> Vec<T> {
+    let mut padded = Vec::new();
+    for (i, len) in chunk_lens(bits.len() / 8).into_iter().enumerate() {
+        let start = i * CHUNK_BYTES * 8;
+        padded.extend_from_slice(&bits[start..start + len * 8]);
+        padded.resize(padded.len() + (padded_chunk_bytes(len) - len) * 8, zero.clone());
+    }
+    padded
+}
+
+// A chunk of `len` input bytes, given as its padded blocks
+fn chunk_cv<F, CS>(mut cs: CS, padded: &[Boolean], len: usize, counter: u64, root: bool) -> Result<Vec<UInt32>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let blocks: Vec<&[Boolean]> = padded.chunks(BLOCK_BYTES * 8).collect();
+    let mut cv: Vec<UInt32> = IV.iter().map(|&word| UInt32::constant(word)).collect();
+    for (i, block) in blocks.iter().enumerate() {
+        let mut flags = if i == 0 { CHUNK_START } else { 0 };
+        if i == blocks.len() - 1 {
+            flags |= CHUNK_END | if root { ROOT } else { 0 };
+        }
+        let block_len = len.saturating_sub(i * BLOCK_BYTES).min(BLOCK_BYTES) as u32;
+        cv = compress(cs.namespace(|| format!("block {}", i)), &cv, &block_words(block), counter, block_len, flags)?;
+    }
+    Ok(cv)
+}
+
+// The left subtree holds the largest power of two chunks that leaves at
+// least one chunk on the right. Each chunk is its padded blocks and its
+// input length.
+fn subtree_cv<F, CS>(mut cs: CS, chunks: &[(&[Boolean], usize)], first_counter: u64, root: bool) -> Result<Vec<UInt32>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    if chunks.len() == 1 {
+        return chunk_cv(cs, chunks[0].0, chunks[0].1, first_counter, root);
+    }
+    let left_len = 1 << (usize::BITS - 1 - (chunks.len() - 1).leading_zeros());
+    let left = subtree_cv(cs.namespace(|| "left"), &chunks[..left_len], first_counter, false)?;
+    let right = subtree_cv(cs.namespace(|| "right"), &chunks[left_len..], first_counter + left_len as u64, false)?;
+    let key: Vec<UInt32> = IV.iter().map(|&word| UInt32::constant(word)).collect();
+    let block: Vec<UInt32> = left.into_iter().chain(right).collect();
+    let flags = PARENT | if root { ROOT } else { 0 };
+    compress(cs.namespace(|| "parent"), &key, &block, 0, BLOCK_BYTES as u32, flags)
+}
+
+// Unkeyed BLAKE3 with a 32-byte output of a `len`-byte input given as
+// `pad`'s padded blocks. The chunk tree and block lengths follow `len`,
+// which is public.
+fn hash_padded<F, CS>(mut cs: CS, padded: &[Boolean], len: usize) -> Result<Vec<Boolean>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let mut chunks = Vec::new();
+    let mut rest = padded;
+    for len in chunk_lens(len) {
+        let (chunk, tail) = rest.split_at(padded_chunk_bytes(len) * 8);
+        chunks.push((chunk, len));
+        rest = tail;
+    }
+    let digest = subtree_cv(cs.namespace(|| "tree"), &chunks, 0, true)?;
+    Ok(digest.into_iter().flat_map(|word| word.into_bits()).collect())
+}
+
+// Unkeyed BLAKE3 with a 32-byte output of `input`, given as bits, least
+// significant bit of each byte first. The input length is public, so padding
+// bits are constants. Returns the 256 digest bits in the same order.
+pub fn blake3<F, CS>(cs: CS, input: &[Boolean]) -> Result<Vec<Boolean>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    assert!(input.len().is_multiple_of(8), "BLAKE3 input must be whole bytes");
+    hash_padded(cs, &pad(input, Boolean::constant(false)), input.len() / 8)
+}
+
+// BLAKE3 over a private preimage. The last block of every chunk is
+// zero-padded natively and every bit of the padded blocks is allocated (one
+// boolean constraint each), so no block folds to constants, not even for an
+// empty input; the block lengths and flags follow the public input length.
+// Additions are packed by bellpepper's `MultiEq` as in its BLAKE2s gadget,
+// and the digest's witness values are checked against `expected`.
+pub struct Blake3Circuit {
+    pub preimage: Vec<u8>,
+    pub expected: Vec<u8>,
+}
+
+impl<F: PrimeField> Circuit<F> for Blake3Circuit {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let bits: Vec<bool> = self
+            .preimage
+            .iter()
+            .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+            .collect();
+        let padded = pad(&bits, false)
+            .into_iter()
+            .enumerate()
+            .map(|(i, bit)| {
+                AllocatedBit::alloc(cs.namespace(|| format!("padded bit {}", i)), Some(bit))
+                    .map(Boolean::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let digest = hash_padded(cs.namespace(|| "blake3"), &padded, self.preimage.len())?;
+        let digest = digest
+            .chunks(8)
+            .map(|byte| {
+                byte.iter().rev().try_fold(0u8, |acc, bit| bit.get_value().map(|b| acc << 1 | b as u8))
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(SynthesisError::AssignmentMissing)?;
+        if digest != self.expected {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bellpepper_core::test_cs::TestConstraintSystem;
+    use bellpepper_core::Circuit;
+    use blstrs::Scalar as Fr;
+
+    use super::Blake3Circuit;
+    use crate::hashes::{Blake3, HashFunction};
+
+    // Lengths around a 64-byte block and a 1024-byte chunk, the last giving
+    // a parent node
+    #[test]
+    fn digest_matches_native() {
+        for len in [0, 23, 64, 65, 1025] {
+            let preimage: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let expected = Blake3.hash(&preimage).unwrap();
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            Blake3Circuit { preimage, expected }.synthesize(&mut cs).unwrap();
+            assert!(cs.is_satisfied(), "length {}: {:?}", len, cs.which_is_unsatisfied());
+        }
+    }
+}
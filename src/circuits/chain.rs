@@ -0,0 +1,34 @@
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+use super::merkle::NodeGadget;
+
+// A hash chain: each of `steps` steps replaces the state with
+// `hashes_per_step` iterated hashes of itself, each hash being a node with
+// one child. Both ends are public. The end's witness is checked against
+// `end`, as for `MerkleCircuit`'s root.
+pub struct ChainCircuit<'a, G> {
+    pub gadget: &'a G,
+    pub start: Vec<u8>,
+    pub steps: usize,
+    pub hashes_per_step: usize,
+    pub end: Vec<u8>,
+}
+
+impl<F: PrimeField, G: NodeGadget<F>> Circuit<F> for ChainCircuit<'_, G> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let mut state = self.gadget.alloc_digest(cs.namespace(|| "start"), &self.start)?;
+        self.gadget.inputize(cs.namespace(|| "public start"), &state)?;
+        for step in 0..self.steps {
+            let mut cs = cs.namespace(|| format!("step {}", step));
+            for i in 0..self.hashes_per_step {
+                state = self.gadget.hash(cs.namespace(|| format!("hash {}", i)), std::slice::from_ref(&state))?;
+            }
+        }
+
+        if self.gadget.value(&state).ok_or(SynthesisError::AssignmentMissing)? != self.end {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        self.gadget.inputize(cs.namespace(|| "public end"), &state)
+    }
+}
@@ -0,0 +1,69 @@
+use bellpepper_core::num::AllocatedNum;
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+use super::merkle::{self, FieldGadget};
+use super::wire::Wire;
+
+// GMiMC-erf over already-padded field elements, with the same additive
+// sponge as the native `hashes::Gmimc`. Each round is one S-box on the first
+// element (3 constraints for x^5), added to the others as linear
+// combinations; the digest element is allocated at one constraint.
+pub struct GmimcCircuit<'a, F: PrimeField> {
+    pub rate: usize,
+    pub width: usize,
+    pub alpha: u64,
+    // One per round
+    pub round_constants: &'a [F],
+    pub elements: Vec<F>,
+}
+
+impl<F: PrimeField> GmimcCircuit<'_, F> {
+    fn permute<CS: ConstraintSystem<F>>(&self, cs: &mut CS, state: &mut [Wire<F>]) -> Result<(), SynthesisError> {
+        let last = self.round_constants.len() - 1;
+        for (round, &c) in self.round_constants.iter().enumerate() {
+            let power = state[0]
+                .add(&Wire::constant::<CS>(c))
+                .pow(cs.namespace(|| format!("round {}", round)), self.alpha)?;
+            state[1..].iter_mut().for_each(|x| *x = x.add(&power));
+            if round < last {
+                state.rotate_right(1);
+            }
+        }
+        Ok(())
+    }
+
+    // The additive sponge over already-padded elements; returns the first
+    // state element
+    fn absorb<CS: ConstraintSystem<F>>(&self, cs: &mut CS, elements: &[Wire<F>]) -> Result<Wire<F>, SynthesisError> {
+        let mut state = vec![Wire::constant::<CS>(F::ZERO); self.width];
+        for (block_index, block) in elements.chunks(self.rate).enumerate() {
+            let mut cs = cs.namespace(|| format!("block {}", block_index));
+            for (i, element) in block.iter().enumerate() {
+                state[i] = state[i].add(element);
+            }
+            self.permute(&mut cs, &mut state)?;
+        }
+        Ok(state.swap_remove(0))
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for GmimcCircuit<'_, F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let elements = self
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(i, value)| Wire::alloc(cs.namespace(|| format!("element {}", i)), *value))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.absorb(cs, &elements)?.allocate(cs.namespace(|| "digest"))?;
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> FieldGadget<F> for GmimcCircuit<'_, F> {
+    fn hash_elements<CS: ConstraintSystem<F>>(&self, mut cs: CS, elements: &[AllocatedNum<F>]) -> Result<AllocatedNum<F>, SynthesisError> {
+        let padded = merkle::pad::<F, CS>(elements, self.rate)?;
+        self.absorb(&mut cs, &padded)?.allocate(cs.namespace(|| "digest"))
+    }
+}
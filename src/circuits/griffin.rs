@@ -0,0 +1,99 @@
+use bellpepper_core::num::AllocatedNum;
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+use super::merkle::{self, FieldGadget};
+use super::wire::Wire;
+
+// Griffin at t = 3 over already-padded field elements, with the same additive
+// sponge as the native `hashes::Griffin`. x^(1/d) allocates y = x^(1/d) and
+// checks y^d = x; x^d is a square-and-multiply chain; each further element
+// costs L^2 and one product. The digest element is allocated at one
+// constraint.
+pub struct GriffinCircuit<'a, F: PrimeField> {
+    pub rate: usize,
+    pub d: u64,
+    // 1 / d modulo p - 1, for the witness only
+    pub d_inv: &'a [u64],
+    // t per round, except the last
+    pub round_constants: &'a [Vec<F>],
+    // (alpha_i, beta_i) for i = 2 .. t - 1
+    pub alpha_beta: &'a [(F, F)],
+    pub elements: Vec<F>,
+}
+
+impl<F: PrimeField> GriffinCircuit<'_, F> {
+    // Mirrors `hashes::Griffin::linear_layer`
+    fn linear_layer(state: &mut [Wire<F>]) {
+        let sum = state[1..].iter().fold(state[0].clone(), |acc, x| acc.add(x));
+        state.iter_mut().for_each(|x| *x = x.add(&sum));
+    }
+
+    fn nonlinear_layer<CS: ConstraintSystem<F>>(&self, mut cs: CS, state: &mut [Wire<F>]) -> Result<(), SynthesisError> {
+        let x = state.to_vec();
+        let y0 = Wire::alloc(cs.namespace(|| "y0"), x[0].value.pow_vartime(self.d_inv))?;
+        let (a, b) = y0.power_factors(cs.namespace(|| "y0^d"), self.d)?;
+        x[0].enforce_product(cs.namespace(|| "y0^d = x0"), &a, &b);
+        state[0] = y0;
+        state[1] = x[1].pow(cs.namespace(|| "x1^d"), self.d)?;
+
+        for (i, &(alpha, beta)) in (2..state.len()).zip(self.alpha_beta) {
+            let mut l = state[0].scale(F::from(i as u64 - 1)).add(&state[1]);
+            if i > 2 {
+                l = l.add(&x[i - 1]);
+            }
+            let l2 = l.mul(cs.namespace(|| format!("L{}^2", i)), &l)?;
+            let quadratic = l2.add(&l.scale(alpha)).add(&Wire::constant::<CS>(beta));
+            state[i] = x[i].mul(cs.namespace(|| format!("y{}", i)), &quadratic)?;
+        }
+        Ok(())
+    }
+
+    fn permute<CS: ConstraintSystem<F>>(&self, cs: &mut CS, state: &mut [Wire<F>]) -> Result<(), SynthesisError> {
+        Self::linear_layer(state);
+        for round in 0..=self.round_constants.len() {
+            self.nonlinear_layer(cs.namespace(|| format!("round {}", round)), state)?;
+            Self::linear_layer(state);
+            if let Some(constants) = self.round_constants.get(round) {
+                for (x, &c) in state.iter_mut().zip(constants) {
+                    *x = x.add(&Wire::constant::<CS>(c));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The additive sponge over already-padded elements; returns the first
+    // state element
+    fn absorb<CS: ConstraintSystem<F>>(&self, cs: &mut CS, elements: &[Wire<F>]) -> Result<Wire<F>, SynthesisError> {
+        let mut state = vec![Wire::constant::<CS>(F::ZERO); self.round_constants[0].len()];
+        for (block_index, block) in elements.chunks(self.rate).enumerate() {
+            let mut cs = cs.namespace(|| format!("block {}", block_index));
+            for (i, element) in block.iter().enumerate() {
+                state[i] = state[i].add(element);
+            }
+            self.permute(&mut cs, &mut state)?;
+        }
+        Ok(state.swap_remove(0))
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for GriffinCircuit<'_, F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let elements = self
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(i, value)| Wire::alloc(cs.namespace(|| format!("element {}", i)), *value))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.absorb(cs, &elements)?.allocate(cs.namespace(|| "digest"))?;
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> FieldGadget<F> for GriffinCircuit<'_, F> {
+    fn hash_elements<CS: ConstraintSystem<F>>(&self, mut cs: CS, elements: &[AllocatedNum<F>]) -> Result<AllocatedNum<F>, SynthesisError> {
+        let padded = merkle::pad::<F, CS>(elements, self.rate)?;
+        self.absorb(&mut cs, &padded)?.allocate(cs.namespace(|| "digest"))
+    }
+}
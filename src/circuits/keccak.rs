@@ -0,0 +1,267 @@
+use bellpepper::gadgets::multipack;
+use bellpepper_core::boolean::{AllocatedBit, Boolean};
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+// Keccak-256 rate in bytes (1600-bit state, 512-bit capacity)
+const RATE_BYTES: usize = 136;
+const LANE_BITS: usize = 64;
+const ROUNDS: usize = 24;
+
+const ROUND_CONSTANTS: [u64; ROUNDS] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+// Rotation offsets, indexed by lane x + 5 * y
+const ROTATIONS: [u32; 25] = [
+    0, 1, 62, 28, 27,
+    36, 44, 6, 55, 20,
+    3, 10, 43, 25, 39,
+    41, 45, 15, 21, 8,
+    18, 2, 61, 56, 14,
+];
+
+// A 64-bit lane, least significant bit first
+type Lane = Vec<Boolean>;
+
+fn constant_lane(value: u64) -> Lane {
+    (0..LANE_BITS).map(|i| Boolean::constant((value >> i) & 1 == 1)).collect()
+}
+
+// Rotating left by n moves bit i to position i + n
+fn rotate_left(lane: &Lane, n: u32) -> Lane {
+    let n = n as usize % LANE_BITS;
+    (0..LANE_BITS).map(|i| lane[(i + LANE_BITS - n) % LANE_BITS].clone()).collect()
+}
+
+fn xor_lanes<F, CS>(mut cs: CS, a: &Lane, b: &Lane) -> Result<Lane, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    a.iter()
+        .zip(b)
+        .enumerate()
+        .map(|(i, (a, b))| Boolean::xor(cs.namespace(|| format!("bit {}", i)), a, b))
+        .collect()
+}
+
+// Keccak-f[1600] on 25 lanes indexed x + 5 * y. XOR and AND-NOT cost one
+// constraint per bit unless an operand is constant; rotations and the
+// round-constant XOR are free.
+pub fn keccak_f1600<F, CS>(mut cs: CS, state: Vec<Lane>) -> Result<Vec<Lane>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    let mut a = state;
+    for (round, rc) in ROUND_CONSTANTS.iter().enumerate() {
+        let mut cs = cs.namespace(|| format!("round {}", round));
+
+        // θ: XOR each lane with the parities of two neighbouring columns
+        let mut c = Vec::with_capacity(5);
+        for x in 0..5 {
+            let mut cs = cs.namespace(|| format!("theta column {}", x));
+            let mut parity = a[x].clone();
+            for y in 1..5 {
+                parity = xor_lanes(cs.namespace(|| format!("row {}", y)), &parity, &a[x + 5 * y])?;
+            }
+            c.push(parity);
+        }
+        let mut d = Vec::with_capacity(5);
+        for x in 0..5 {
+            let rotated = rotate_left(&c[(x + 1) % 5], 1);
+            d.push(xor_lanes(cs.namespace(|| format!("theta d {}", x)), &c[(x + 4) % 5], &rotated)?);
+        }
+        for (i, lane) in a.iter_mut().enumerate() {
+            *lane = xor_lanes(cs.namespace(|| format!("theta lane {}", i)), lane, &d[i % 5])?;
+        }
+
+        // ρ and π: rotate each lane and move (x, y) to (y, 2x + 3y)
+        let mut b = vec![Vec::new(); 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = rotate_left(&a[x + 5 * y], ROTATIONS[x + 5 * y]);
+            }
+        }
+
+        // χ: a = b ^ (!b[x + 1] & b[x + 2]) within each row
+        for x in 0..5 {
+            for y in 0..5 {
+                let mut cs = cs.namespace(|| format!("chi lane {}", x + 5 * y));
+                let (b0, b1, b2) = (&b[x + 5 * y], &b[(x + 1) % 5 + 5 * y], &b[(x + 2) % 5 + 5 * y]);
+                a[x + 5 * y] = (0..LANE_BITS)
+                    .map(|i| {
+                        let masked = Boolean::and(cs.namespace(|| format!("and {}", i)), &b1[i].not(), &b2[i])?;
+                        Boolean::xor(cs.namespace(|| format!("xor {}", i)), &b0[i], &masked)
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+        }
+
+        // ι: XOR a constant into lane 0, which only flips bits
+        a[0] = a[0]
+            .iter()
+            .zip(constant_lane(*rc))
+            .map(|(bit, k)| if k.get_value() == Some(true) { bit.not() } else { bit.clone() })
+            .collect();
+    }
+    Ok(a)
+}
+
+// Padding for a `len`-byte message: `delimiter` .. 0x80, both in one byte
+// if only one is free. `delimiter` is 0x01 for the original Keccak-256 used
+// by Ethereum, 0x06 for FIPS 202 SHA3-256, whose extra bits are its domain
+// separation suffix.
+fn padding(len: usize, delimiter: u8) -> Vec<u8> {
+    let mut padding = vec![0u8; RATE_BYTES - len % RATE_BYTES];
+    padding[0] |= delimiter;
+    *padding.last_mut().expect("at least one padding byte") |= 0x80;
+    padding
+}
+
+// Absorbs whole 136-byte blocks, given as bits, least significant bit of
+// each byte first, and squeezes 256 digest bits in the same order
+fn sponge<F, CS>(mut cs: CS, padded: &[Boolean]) -> Result<Vec<Boolean>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    assert!(padded.len().is_multiple_of(RATE_BYTES * 8), "Keccak-256 input must be padded");
+
+    let mut state = vec![constant_lane(0); 25];
+    for (i, block) in padded.chunks(RATE_BYTES * 8).enumerate() {
+        let mut cs = cs.namespace(|| format!("block {}", i));
+        for (j, lane_bits) in block.chunks(LANE_BITS).enumerate() {
+            state[j] = xor_lanes(cs.namespace(|| format!("absorb lane {}", j)), &state[j], &lane_bits.to_vec())?;
+        }
+        state = keccak_f1600(cs.namespace(|| "keccak-f"), state)?;
+    }
+
+    Ok(state.into_iter().take(4).flatten().collect())
+}
+
+// Keccak at rate 136 with a 256-bit output over bytes given as bits, least
+// significant bit of each byte first, with `delimiter` as in `padding`. The
+// input length is public, so padding bits are constants. Returns the 256
+// digest bits in the same order.
+pub fn keccak256<F, CS>(cs: CS, input: &[Boolean], delimiter: u8) -> Result<Vec<Boolean>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+{
+    assert!(input.len().is_multiple_of(8), "Keccak-256 input must be whole bytes");
+
+    let mut bits = input.to_vec();
+    bits.extend(
+        padding(input.len() / 8, delimiter)
+            .iter()
+            .flat_map(|byte| (0..8).map(move |i| Boolean::constant((byte >> i) & 1 == 1))),
+    );
+    sponge(cs, &bits)
+}
+
+// Keccak-256, or SHA3-256 with delimiter 0x06, of a private preimage. The
+// message is padded natively and every bit of the padded blocks is allocated
+// (one boolean constraint each), so the padding is part of the witness and
+// no block folds to constants, not even for an empty input. The digest's
+// witness values are checked against `expected`, so a wrong gadget fails
+// instead of being counted. `public_digest` packs the digest bits into
+// public inputs.
+pub struct Keccak256Circuit {
+    pub preimage: Vec<u8>,
+    pub delimiter: u8,
+    pub expected: Vec<u8>,
+    pub public_digest: bool,
+}
+
+impl<F: PrimeField> Circuit<F> for Keccak256Circuit {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let padding = padding(self.preimage.len(), self.delimiter);
+        let bits = self
+            .preimage
+            .iter()
+            .chain(&padding)
+            .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+            .enumerate()
+            .map(|(i, bit)| {
+                AllocatedBit::alloc(cs.namespace(|| format!("padded bit {}", i)), Some(bit))
+                    .map(Boolean::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let digest = sponge(cs.namespace(|| "keccak256"), &bits)?;
+        if self.public_digest {
+            multipack::pack_into_inputs(cs.namespace(|| "public digest"), &digest)?;
+        }
+        let digest = digest
+            .chunks(8)
+            .map(|byte| {
+                byte.iter().rev().try_fold(0u8, |acc, bit| bit.get_value().map(|b| acc << 1 | b as u8))
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(SynthesisError::AssignmentMissing)?;
+        if digest != self.expected {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bellpepper::gadgets::multipack;
+    use bellpepper_core::test_cs::TestConstraintSystem;
+    use bellpepper_core::Circuit;
+    use blstrs::Scalar as Fr;
+
+    use super::Keccak256Circuit;
+    use crate::hashes::{HashFunction, Keccak256, Sha3_256};
+
+    // Lengths either side of the 136-byte rate, so the padding fills a
+    // block, spills into a new one, or shares one byte with the delimiter
+    #[test]
+    fn keccak256_matches_native() {
+        for len in [0, 23, 135, 136, 137, 300] {
+            let preimage: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let digest = Keccak256.hash(&preimage).unwrap();
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            let circuit = Keccak256Circuit {
+                preimage,
+                delimiter: 0x01,
+                expected: digest.clone(),
+                public_digest: true,
+            };
+            circuit.synthesize(&mut cs).unwrap();
+            assert!(cs.is_satisfied(), "length {}: {:?}", len, cs.which_is_unsatisfied());
+            let inputs = multipack::compute_multipacking::<Fr>(&multipack::bytes_to_bits_le(&digest));
+            assert!(cs.verify(&inputs), "length {}: digest differs from the native hash", len);
+        }
+    }
+
+    // SHA3-256's delimiter, which must give a digest Keccak-256 does not
+    #[test]
+    fn sha3_256_matches_native() {
+        for len in [0, 135, 137] {
+            let preimage: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let digest = Sha3_256.hash(&preimage).unwrap();
+            assert_ne!(digest, Keccak256.hash(&preimage).unwrap(), "length {}", len);
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            let circuit = Keccak256Circuit {
+                preimage,
+                delimiter: 0x06,
+                expected: digest.clone(),
+                public_digest: true,
+            };
+            circuit.synthesize(&mut cs).unwrap();
+            assert!(cs.is_satisfied(), "length {}: {:?}", len, cs.which_is_unsatisfied());
+            let inputs = multipack::compute_multipacking::<Fr>(&multipack::bytes_to_bits_le(&digest));
+            assert!(cs.verify(&inputs), "length {}: digest differs from the native hash", len);
+        }
+    }
+}
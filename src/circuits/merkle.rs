@@ -0,0 +1,448 @@
+use bellpepper::gadgets::multipack;
+use bellpepper::gadgets::sha256::sha256;
+use bellpepper_core::boolean::{AllocatedBit, Boolean};
+use bellpepper_core::num::{AllocatedNum, Num};
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+use super::blake2s::blake2s;
+use super::blake3::blake3;
+use super::keccak::keccak256;
+use super::wire::Wire;
+use crate::fields;
+
+// A hash gadget as one node of a Merkle tree, over digests in whatever form
+// the gadget works on
+pub trait NodeGadget<F: PrimeField> {
+    type Digest;
+
+    // A digest given as the native hash's output, as a private witness
+    fn alloc_digest<CS: ConstraintSystem<F>>(&self, cs: CS, digest: &[u8]) -> Result<Self::Digest, SynthesisError>;
+
+    // `a` if `condition` holds, otherwise `b`
+    fn select<CS: ConstraintSystem<F>>(
+        &self,
+        cs: CS,
+        condition: &Boolean,
+        a: &Self::Digest,
+        b: &Self::Digest,
+    ) -> Result<Self::Digest, SynthesisError>;
+
+    // The parent of `children`, in slot order
+    fn hash<CS: ConstraintSystem<F>>(&self, cs: CS, children: &[Self::Digest]) -> Result<Self::Digest, SynthesisError>;
+
+    fn inputize<CS: ConstraintSystem<F>>(&self, cs: CS, digest: &Self::Digest) -> Result<(), SynthesisError>;
+
+    // The public inputs `inputize` allocates for a native digest
+    fn public_inputs(&self, digest: &[u8]) -> Vec<F>;
+
+    // A digest's witness value as native output bytes
+    fn value(&self, digest: &Self::Digest) -> Option<Vec<u8>>;
+
+    // A digest as the field elements a folding scheme carries from one step
+    // to the next, valued as `public_inputs`
+    fn pack_state<CS: ConstraintSystem<F>>(&self, cs: CS, digest: &Self::Digest) -> Result<Vec<AllocatedNum<F>>, SynthesisError>;
+
+    // The digest `pack_state` turned into `state`
+    fn unpack_state<CS: ConstraintSystem<F>>(&self, cs: CS, state: &[AllocatedNum<F>]) -> Result<Self::Digest, SynthesisError>;
+}
+
+// Every byte hash with a gadget has a 32-byte digest
+const DIGEST_BITS: usize = 256;
+
+// `bits` as one number, least significant first, as `multipack` packs them
+fn pack_bits<F: PrimeField, CS: ConstraintSystem<F>>(bits: &[Boolean]) -> Num<F> {
+    let mut coeff = F::ONE;
+    bits.iter().fold(Num::zero(), |num, bit| {
+        let num = num.add_bool_with_coeff(CS::one(), bit, coeff);
+        coeff = coeff.double();
+        num
+    })
+}
+
+// The byte hashes' gadgets, over digests as bits. A node hashes its
+// children's digests concatenated, as the native hash would.
+#[derive(Clone, Copy, Debug)]
+pub enum BitGadget {
+    Sha256,
+    // Keccak-256, or SHA3-256 with delimiter 0x06
+    Keccak256 { delimiter: u8 },
+    Blake2s,
+    Blake3,
+}
+
+impl BitGadget {
+    // Position of a byte's i-th bit: SHA-256 reads each byte most
+    // significant bit first, the others least significant bit first
+    fn shift(&self, i: usize) -> usize {
+        match self {
+            BitGadget::Sha256 => 7 - i,
+            _ => i,
+        }
+    }
+
+    fn bits(&self, bytes: &[u8]) -> Vec<bool> {
+        bytes.iter().flat_map(|byte| (0..8).map(move |i| byte >> self.shift(i) & 1 == 1)).collect()
+    }
+}
+
+impl<F: PrimeField> NodeGadget<F> for BitGadget {
+    type Digest = Vec<Boolean>;
+
+    // One boolean constraint per bit
+    fn alloc_digest<CS: ConstraintSystem<F>>(&self, mut cs: CS, digest: &[u8]) -> Result<Vec<Boolean>, SynthesisError> {
+        self.bits(digest)
+            .into_iter()
+            .enumerate()
+            .map(|(i, bit)| AllocatedBit::alloc(cs.namespace(|| format!("bit {}", i)), Some(bit)).map(Boolean::from))
+            .collect()
+    }
+
+    // SHA-256's Ch, one constraint per bit
+    fn select<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        condition: &Boolean,
+        a: &Vec<Boolean>,
+        b: &Vec<Boolean>,
+    ) -> Result<Vec<Boolean>, SynthesisError> {
+        a.iter()
+            .zip(b)
+            .enumerate()
+            .map(|(i, (a, b))| Boolean::sha256_ch(cs.namespace(|| format!("bit {}", i)), condition, a, b))
+            .collect()
+    }
+
+    fn hash<CS: ConstraintSystem<F>>(&self, cs: CS, children: &[Vec<Boolean>]) -> Result<Vec<Boolean>, SynthesisError> {
+        let input = children.concat();
+        match self {
+            BitGadget::Sha256 => sha256(cs, &input),
+            BitGadget::Keccak256 { delimiter } => keccak256(cs, &input, *delimiter),
+            BitGadget::Blake2s => blake2s(cs, &input),
+            BitGadget::Blake3 => blake3(cs, &input),
+        }
+    }
+
+    // Packed into two public inputs over a 255-bit field
+    fn inputize<CS: ConstraintSystem<F>>(&self, cs: CS, digest: &Vec<Boolean>) -> Result<(), SynthesisError> {
+        multipack::pack_into_inputs(cs, digest)
+    }
+
+    fn public_inputs(&self, digest: &[u8]) -> Vec<F> {
+        multipack::compute_multipacking(&self.bits(digest))
+    }
+
+    fn value(&self, digest: &Vec<Boolean>) -> Option<Vec<u8>> {
+        digest
+            .chunks(8)
+            .map(|byte| {
+                byte.iter()
+                    .enumerate()
+                    .try_fold(0u8, |acc, (i, bit)| bit.get_value().map(|b| acc | (b as u8) << self.shift(i)))
+            })
+            .collect()
+    }
+
+    // `inputize`'s packing into witnesses, one constraint per element
+    fn pack_state<CS: ConstraintSystem<F>>(&self, mut cs: CS, digest: &Vec<Boolean>) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        digest
+            .chunks(F::CAPACITY as usize)
+            .enumerate()
+            .map(|(i, bits)| {
+                let packed = pack_bits::<F, CS>(bits);
+                let element = AllocatedNum::alloc(cs.namespace(|| format!("element {}", i)), || {
+                    packed.get_value().ok_or(SynthesisError::AssignmentMissing)
+                })?;
+                cs.enforce(
+                    || format!("packing {}", i),
+                    |_| packed.lc(F::ONE),
+                    |lc| lc + CS::one(),
+                    |lc| lc + element.get_variable(),
+                );
+                Ok(element)
+            })
+            .collect()
+    }
+
+    // Each element's low bits, one boolean constraint each, and one
+    // constraint per element; a chunk is under `CAPACITY` bits, so its
+    // decomposition is unique
+    fn unpack_state<CS: ConstraintSystem<F>>(&self, mut cs: CS, state: &[AllocatedNum<F>]) -> Result<Vec<Boolean>, SynthesisError> {
+        let capacity = F::CAPACITY as usize;
+        if state.len() != DIGEST_BITS.div_ceil(capacity) {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let mut digest = Vec::with_capacity(DIGEST_BITS);
+        for (i, element) in state.iter().enumerate() {
+            let mut cs = cs.namespace(|| format!("element {}", i));
+            let bytes = element.get_value().map(|value| fields::to_le_bytes(&value));
+            let bits = (0..capacity.min(DIGEST_BITS - i * capacity))
+                .map(|j| {
+                    let bit = bytes.as_ref().map(|bytes| bytes[j / 8] >> (j % 8) & 1 == 1);
+                    AllocatedBit::alloc(cs.namespace(|| format!("bit {}", j)), bit).map(Boolean::from)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let packed = pack_bits::<F, CS>(&bits);
+            cs.enforce(
+                || "packing",
+                |_| packed.lc(F::ONE),
+                |lc| lc + CS::one(),
+                |lc| lc + element.get_variable(),
+            );
+            digest.extend(bits);
+        }
+        Ok(digest)
+    }
+}
+
+// A field hash's gadget over allocated elements, padded in-circuit as the
+// native hash pads its packed elements. Returns the digest element.
+pub trait FieldGadget<F: PrimeField> {
+    fn hash_elements<CS: ConstraintSystem<F>>(&self, cs: CS, elements: &[AllocatedNum<F>]) -> Result<AllocatedNum<F>, SynthesisError>;
+}
+
+// The additive sponges' 10* padding to a multiple of `rate`, with constant
+// padding elements
+pub fn pad<F: PrimeField, CS: ConstraintSystem<F>>(elements: &[AllocatedNum<F>], rate: usize) -> Result<Vec<Wire<F>>, SynthesisError> {
+    let mut padded = elements.iter().map(Wire::from_num).collect::<Result<Vec<_>, _>>()?;
+    padded.push(Wire::constant::<CS>(F::ONE));
+    padded.resize(padded.len().div_ceil(rate) * rate, Wire::constant::<CS>(F::ZERO));
+    Ok(padded)
+}
+
+// A field hash's gadget as a node, taking each child's digest as one element
+pub struct FieldNode<G>(pub G);
+
+impl<F: PrimeField, G: FieldGadget<F>> NodeGadget<F> for FieldNode<G> {
+    type Digest = AllocatedNum<F>;
+
+    fn alloc_digest<CS: ConstraintSystem<F>>(&self, mut cs: CS, digest: &[u8]) -> Result<AllocatedNum<F>, SynthesisError> {
+        let value = fields::from_le_bytes(digest).ok_or(SynthesisError::Unsatisfiable)?;
+        AllocatedNum::alloc(cs.namespace(|| "element"), || Ok(value))
+    }
+
+    // One constraint: condition (a - b) = selected - b
+    fn select<CS: ConstraintSystem<F>>(
+        &self,
+        mut cs: CS,
+        condition: &Boolean,
+        a: &AllocatedNum<F>,
+        b: &AllocatedNum<F>,
+    ) -> Result<AllocatedNum<F>, SynthesisError> {
+        let selected = AllocatedNum::alloc(cs.namespace(|| "selected"), || {
+            let chosen = if condition.get_value().ok_or(SynthesisError::AssignmentMissing)? { a } else { b };
+            chosen.get_value().ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        cs.enforce(
+            || "condition (a - b) = selected - b",
+            |_| condition.lc(CS::one(), F::ONE),
+            |lc| lc + a.get_variable() - b.get_variable(),
+            |lc| lc + selected.get_variable() - b.get_variable(),
+        );
+        Ok(selected)
+    }
+
+    fn hash<CS: ConstraintSystem<F>>(&self, cs: CS, children: &[AllocatedNum<F>]) -> Result<AllocatedNum<F>, SynthesisError> {
+        self.0.hash_elements(cs, children)
+    }
+
+    // One constraint
+    fn inputize<CS: ConstraintSystem<F>>(&self, cs: CS, digest: &AllocatedNum<F>) -> Result<(), SynthesisError> {
+        digest.inputize(cs)
+    }
+
+    fn public_inputs(&self, digest: &[u8]) -> Vec<F> {
+        fields::from_le_bytes(digest).into_iter().collect()
+    }
+
+    fn value(&self, digest: &AllocatedNum<F>) -> Option<Vec<u8>> {
+        digest.get_value().map(|value| fields::to_le_bytes(&value))
+    }
+
+    // The element itself
+    fn pack_state<CS: ConstraintSystem<F>>(&self, _cs: CS, digest: &AllocatedNum<F>) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        Ok(vec![digest.clone()])
+    }
+
+    fn unpack_state<CS: ConstraintSystem<F>>(&self, _cs: CS, state: &[AllocatedNum<F>]) -> Result<AllocatedNum<F>, SynthesisError> {
+        match state {
+            [element] => Ok(element.clone()),
+            _ => Err(SynthesisError::Unsatisfiable),
+        }
+    }
+}
+
+// One level of a Merkle path, in native digests
+#[derive(Clone, Debug)]
+pub struct MerkleLevel {
+    // Slot of the path's node among its siblings
+    pub position: usize,
+    // Every child of the parent, by slot; the path's own slot is ignored
+    pub children: Vec<Vec<u8>>,
+}
+
+// Places `node` at slot `position` among `siblings`, which keep their order
+// in the other slots. Slot j holds the node, or the sibling at j - 1 or j,
+// by whether the position is j or below j. Each slot's equality with the
+// position is an AND of the position bits or their negations (one
+// constraint per bit after the first), and "below j" an OR of the earlier
+// equalities (one constraint). Selecting costs one constraint per field
+// element or bit for the end slots and two for the others. For an arity
+// that is not a power of two, one more constraint keeps the position below
+// it.
+fn insert<F, CS, G>(mut cs: CS, gadget: &G, position: &[Boolean], node: &G::Digest, siblings: &[G::Digest]) -> Result<Vec<G::Digest>, SynthesisError>
+where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+    G: NodeGadget<F>,
+{
+    let arity = siblings.len() + 1;
+    let is_slot = (0..arity)
+        .map(|slot| {
+            let mut cs = cs.namespace(|| format!("position is {}", slot));
+            let literal = |i: usize| if slot >> i & 1 == 1 { position[i].clone() } else { position[i].not() };
+            (1..position.len()).try_fold(literal(0), |acc, i| Boolean::and(cs.namespace(|| format!("bit {}", i)), &acc, &literal(i)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if !arity.is_power_of_two() {
+        cs.enforce(
+            || "position below arity",
+            |lc| is_slot.iter().fold(lc, |lc, is| lc + &is.lc(CS::one(), F::ONE)),
+            |lc| lc + CS::one(),
+            |lc| lc + CS::one(),
+        );
+    }
+
+    let mut below = Boolean::constant(false);
+    let mut children = Vec::with_capacity(arity);
+    for slot in 0..arity {
+        let mut cs = cs.namespace(|| format!("slot {}", slot));
+        let shifted;
+        let sibling = match slot {
+            0 => &siblings[0],
+            _ if slot == arity - 1 => &siblings[slot - 1],
+            _ => {
+                shifted = gadget.select(cs.namespace(|| "sibling"), &below, &siblings[slot - 1], &siblings[slot])?;
+                &shifted
+            }
+        };
+        children.push(gadget.select(cs.namespace(|| "node"), &is_slot[slot], node, sibling)?);
+        // Only the middle slots read it
+        if slot + 2 < arity {
+            below = Boolean::or(cs.namespace(|| "below"), &below, &is_slot[slot])?;
+        }
+    }
+    Ok(children)
+}
+
+// Membership of a private leaf digest in a tree with a public root. Per
+// level, the position is ⌈log2 arity⌉ booleans, little-endian (one
+// constraint each); only the arity - 1 siblings are allocated as witnesses,
+// and `insert` places the path's node among them before hashing. The
+// recomputed root's witness is checked against `root`, so a gadget that
+// disagrees with the native hash fails instead of being counted, and is then
+// exposed as public inputs.
+pub struct MerkleCircuit<'a, G> {
+    pub gadget: &'a G,
+    pub leaf: Vec<u8>,
+    // Leaf level first
+    pub levels: Vec<MerkleLevel>,
+    pub root: Vec<u8>,
+}
+
+impl<F: PrimeField, G: NodeGadget<F>> Circuit<F> for MerkleCircuit<'_, G> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let mut node = self.gadget.alloc_digest(cs.namespace(|| "leaf"), &self.leaf)?;
+        for (l, level) in self.levels.iter().enumerate() {
+            let mut cs = cs.namespace(|| format!("level {}", l));
+            let arity = level.children.len();
+            let position_bits = (usize::BITS - (arity - 1).leading_zeros()) as usize;
+            let position = (0..position_bits)
+                .map(|i| {
+                    AllocatedBit::alloc(cs.namespace(|| format!("position bit {}", i)), Some(level.position >> i & 1 == 1))
+                        .map(Boolean::from)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let siblings = level
+                .children
+                .iter()
+                .enumerate()
+                .filter(|&(slot, _)| slot != level.position)
+                .map(|(slot, child)| self.gadget.alloc_digest(cs.namespace(|| format!("sibling {}", slot)), child))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let children = insert(cs.namespace(|| "insert"), self.gadget, &position, &node, &siblings)?;
+            node = self.gadget.hash(cs.namespace(|| "parent"), &children)?;
+        }
+
+        if self.gadget.value(&node).ok_or(SynthesisError::AssignmentMissing)? != self.root {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        self.gadget.inputize(cs.namespace(|| "root"), &node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bellpepper_core::boolean::{AllocatedBit, Boolean};
+    use bellpepper_core::test_cs::TestConstraintSystem;
+    use bellpepper_core::ConstraintSystem;
+    use blstrs::Scalar as Fr;
+
+    use super::{insert, BitGadget, NodeGadget};
+
+    // Every position of arities 2 to 5; past a non-power-of-two arity, the
+    // position bits must not satisfy the circuit
+    #[test]
+    fn insert_places_node_in_its_slot() {
+        let gadget = BitGadget::Blake2s;
+        let digest = |label: u8| vec![label; 32];
+        for arity in 2..=5usize {
+            let bits = (usize::BITS - (arity - 1).leading_zeros()) as usize;
+            for position in 0..1 << bits {
+                let mut cs = TestConstraintSystem::<Fr>::new();
+                let position_bits = (0..bits)
+                    .map(|i| {
+                        AllocatedBit::alloc(cs.namespace(|| format!("position bit {}", i)), Some(position >> i & 1 == 1))
+                            .map(Boolean::from)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap();
+                let node = NodeGadget::<Fr>::alloc_digest(&gadget, cs.namespace(|| "node"), &digest(0xff)).unwrap();
+                let siblings = (0..arity - 1)
+                    .map(|i| NodeGadget::<Fr>::alloc_digest(&gadget, cs.namespace(|| format!("sibling {}", i)), &digest(i as u8)))
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap();
+                let children = insert(cs.namespace(|| "insert"), &gadget, &position_bits, &node, &siblings).unwrap();
+                if position >= arity {
+                    assert!(!cs.is_satisfied(), "arity {}: position {} accepted", arity, position);
+                    continue;
+                }
+
+                assert!(cs.is_satisfied(), "arity {}, position {}: {:?}", arity, position, cs.which_is_unsatisfied());
+                let mut expected: Vec<Vec<u8>> = (0..arity - 1).map(|i| digest(i as u8)).collect();
+                expected.insert(position, digest(0xff));
+                let children: Vec<Vec<u8>> = children.iter().map(|child| NodeGadget::<Fr>::value(&gadget, child).unwrap()).collect();
+                assert_eq!(children, expected, "arity {}, position {}", arity, position);
+            }
+        }
+    }
+
+    // A digest through `pack_state` and back, as between two folded steps: the
+    // state is the public inputs' packing, and unpacking restores every bit
+    #[test]
+    fn state_round_trips() {
+        let digest: Vec<u8> = (0..32).map(|i| (i * 7 + 3) as u8).collect();
+        for gadget in [BitGadget::Sha256, BitGadget::Blake3] {
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            let bits = NodeGadget::<Fr>::alloc_digest(&gadget, cs.namespace(|| "digest"), &digest).unwrap();
+            let state = NodeGadget::<Fr>::pack_state(&gadget, cs.namespace(|| "to state"), &bits).unwrap();
+            let values: Vec<Fr> = state.iter().map(|element| element.get_value().unwrap()).collect();
+            assert_eq!(values, NodeGadget::<Fr>::public_inputs(&gadget, &digest), "{:?}", gadget);
+
+            let unpacked = NodeGadget::<Fr>::unpack_state(&gadget, cs.namespace(|| "from state"), &state).unwrap();
+            assert!(cs.is_satisfied(), "{:?}: {:?}", gadget, cs.which_is_unsatisfied());
+            assert_eq!(NodeGadget::<Fr>::value(&gadget, &unpacked), Some(digest.clone()), "{:?}", gadget);
+        }
+    }
+}
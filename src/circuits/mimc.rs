@@ -0,0 +1,64 @@
+use bellpepper_core::num::AllocatedNum;
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+use super::merkle::FieldGadget;
+use super::wire::Wire;
+
+// circomlib's MiMCSponge over already-packed field elements, key 0 and one
+// output. Each Feistel round is t^5 (3 constraints) added to the other half,
+// as in circomlib's MiMCFeistel template; the digest is allocated at one
+// constraint. Packed elements are private inputs.
+pub struct MimcSpongeCircuit<'a, F: PrimeField> {
+    pub constants: &'a [F],
+    pub elements: Vec<F>,
+}
+
+impl<F: PrimeField> MimcSpongeCircuit<'_, F> {
+    fn feistel<CS: ConstraintSystem<F>>(&self, cs: &mut CS, left: Wire<F>, right: Wire<F>) -> Result<(Wire<F>, Wire<F>), SynthesisError> {
+        let (mut left, mut right) = (left, right);
+        let last = self.constants.len() - 1;
+        for (i, &c) in self.constants.iter().enumerate() {
+            let t = left.add(&Wire::constant::<CS>(c));
+            let t5 = t.pow(cs.namespace(|| format!("round {}", i)), 5)?;
+            if i < last {
+                (left, right) = (right.add(&t5), left);
+            } else {
+                right = right.add(&t5);
+            }
+        }
+        Ok((left, right))
+    }
+
+    // Adds each element to R and permutes; returns R
+    fn absorb<CS: ConstraintSystem<F>>(&self, cs: &mut CS, elements: &[Wire<F>]) -> Result<Wire<F>, SynthesisError> {
+        let mut r = Wire::constant::<CS>(F::ZERO);
+        let mut c = Wire::constant::<CS>(F::ZERO);
+        for (i, element) in elements.iter().enumerate() {
+            let mut cs = cs.namespace(|| format!("element {}", i));
+            (r, c) = self.feistel(&mut cs, r.add(element), c)?;
+        }
+        Ok(r)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for MimcSpongeCircuit<'_, F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let elements = self
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(i, value)| Wire::alloc(cs.namespace(|| format!("element {}", i)), *value))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.absorb(cs, &elements)?.allocate(cs.namespace(|| "digest"))?;
+        Ok(())
+    }
+}
+
+// multiHash takes its elements unpadded
+impl<F: PrimeField> FieldGadget<F> for MimcSpongeCircuit<'_, F> {
+    fn hash_elements<CS: ConstraintSystem<F>>(&self, mut cs: CS, elements: &[AllocatedNum<F>]) -> Result<AllocatedNum<F>, SynthesisError> {
+        let elements = elements.iter().map(Wire::from_num).collect::<Result<Vec<_>, _>>()?;
+        self.absorb(&mut cs, &elements)?.allocate(cs.namespace(|| "digest"))
+    }
+}
@@ -0,0 +1,156 @@
+mod anemoi;
+mod blake2s;
+mod blake3;
+mod chain;
+mod gmimc;
+mod griffin;
+mod keccak;
+mod merkle;
+mod mimc;
+mod pedersen;
+mod poseidon;
+mod poseidon2;
+mod rescue;
+mod sha256;
+mod wire;
+
+pub use anemoi::AnemoiCircuit;
+pub use blake2s::Blake2sCircuit;
+pub use blake3::Blake3Circuit;
+pub use chain::ChainCircuit;
+pub use gmimc::GmimcCircuit;
+pub use griffin::GriffinCircuit;
+pub use keccak::Keccak256Circuit;
+pub use merkle::{BitGadget, FieldNode, MerkleCircuit, MerkleLevel, NodeGadget};
+pub use mimc::MimcSpongeCircuit;
+pub use pedersen::PedersenCircuit;
+pub use poseidon::PoseidonCircuit;
+pub use poseidon2::Poseidon2Circuit;
+pub use rescue::RescueCircuit;
+pub use sha256::Sha256Circuit;
+
+use std::marker::PhantomData;
+
+use bellpepper_core::{Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use ff::PrimeField;
+use serde::Serialize;
+
+// Size of a synthesized R1CS instance
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct R1csCost {
+    pub constraints: usize,
+    // Public inputs, excluding the constant `one`
+    pub public_inputs: usize,
+    pub private_variables: usize,
+    // Non-zero entries of the A, B and C matrices
+    pub nonzero_a: usize,
+    pub nonzero_b: usize,
+    pub nonzero_c: usize,
+}
+
+impl R1csCost {
+    pub fn variables(&self) -> usize {
+        self.public_inputs + self.private_variables
+    }
+
+    pub fn nonzero_entries(&self) -> usize {
+        self.nonzero_a + self.nonzero_b + self.nonzero_c
+    }
+}
+
+// A constraint system that only records the shape of the circuit. Witness
+// closures are still evaluated so gadgets that track values keep working.
+pub struct CountingCs<F: PrimeField> {
+    cost: R1csCost,
+    _f: PhantomData<F>,
+}
+
+fn nonzero_terms<F: PrimeField>(lc: &LinearCombination<F>) -> usize {
+    lc.iter().filter(|(_, coeff)| !bool::from(coeff.is_zero())).count()
+}
+
+impl<F: PrimeField> ConstraintSystem<F> for CountingCs<F> {
+    type Root = Self;
+
+    fn new() -> Self {
+        CountingCs {
+            cost: R1csCost::default(),
+            _f: PhantomData,
+        }
+    }
+
+    fn alloc<Fn, A, AR>(&mut self, _annotation: A, f: Fn) -> Result<Variable, SynthesisError>
+    where
+        Fn: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let _ = f();
+        self.cost.private_variables += 1;
+        Ok(Variable::new_unchecked(Index::Aux(self.cost.private_variables - 1)))
+    }
+
+    fn alloc_input<Fn, A, AR>(&mut self, _annotation: A, f: Fn) -> Result<Variable, SynthesisError>
+    where
+        Fn: FnOnce() -> Result<F, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let _ = f();
+        self.cost.public_inputs += 1;
+        // Input 0 is the constant `one`
+        Ok(Variable::new_unchecked(Index::Input(self.cost.public_inputs)))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LB: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+        LC: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    {
+        self.cost.constraints += 1;
+        self.cost.nonzero_a += nonzero_terms(&a(LinearCombination::zero()));
+        self.cost.nonzero_b += nonzero_terms(&b(LinearCombination::zero()));
+        self.cost.nonzero_c += nonzero_terms(&c(LinearCombination::zero()));
+    }
+
+    fn push_namespace<NR, N>(&mut self, _name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+// Synthesizes `circuit` and returns its size
+pub fn measure<F: PrimeField, C: Circuit<F>>(circuit: C) -> Result<R1csCost, SynthesisError> {
+    let mut cs = CountingCs::<F>::new();
+    circuit.synthesize(&mut cs)?;
+    Ok(cs.cost)
+}
+
+// Hashes `children` as one node with `gadget`, and checks the circuit is
+// satisfied and its digest is `expected`, the native hash's
+#[cfg(test)]
+pub fn assert_node_digest<F: PrimeField, G: NodeGadget<F>>(gadget: &G, children: &[Vec<u8>], expected: &[u8], context: &str) {
+    use bellpepper_core::test_cs::TestConstraintSystem;
+
+    let mut cs = TestConstraintSystem::<F>::new();
+    let children = children
+        .iter()
+        .enumerate()
+        .map(|(i, child)| gadget.alloc_digest(cs.namespace(|| format!("child {}", i)), child))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let digest = gadget.hash(cs.namespace(|| "hash"), &children).unwrap();
+    assert!(cs.is_satisfied(), "{}: {:?}", context, cs.which_is_unsatisfied());
+    assert_eq!(gadget.value(&digest).as_deref(), Some(expected), "{}: digest differs from the native hash", context);
+}
@@ -0,0 +1,155 @@
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+use super::wire::Wire;
+
+// Pedersen hash on a twisted Edwards curve a x^2 + y^2 = 1 + d x^2 y^2 over
+// the circuit's field, with the windows of the native `hashes::Pedersen`.
+// Every input bit is allocated with one boolean constraint. Within a segment
+// the chunks are summed in Montgomery form, as in Sapling's gadget:
+//
+// - Lookup: the window's 2^m multiples are a multilinear polynomial in the m
+//   magnitude bits, so u and v cost one constraint per product of two or more
+//   bits; the sign negates v at one more constraint.
+// - Montgomery addition: 3 constraints, incomplete but safe for the distinct
+//   multiples a segment adds up.
+//
+// Each segment sum is mapped back to Edwards form (2 constraints) and added to
+// the running total with a complete Edwards addition (6 constraints). The
+// digest x is allocated at one constraint.
+pub struct PedersenCircuit<'a, F: PrimeField> {
+    pub magnitude_bits: usize,
+    pub chunks_per_segment: usize,
+    pub a: F,
+    pub d: F,
+    // Montgomery B v^2 = u^3 + A u^2 + u, for the same curve
+    pub montgomery_a: F,
+    pub montgomery_b: F,
+    // Montgomery (u, v) of the 2^m multiples of each window, per segment
+    // then chunk
+    pub tables: &'a [Vec<Vec<(F, F)>>],
+    pub bits: Vec<bool>,
+}
+
+fn invert<F: PrimeField>(x: F) -> Result<F, SynthesisError> {
+    Option::from(x.invert()).ok_or(SynthesisError::DivisionByZero)
+}
+
+impl<F: PrimeField> PedersenCircuit<'_, F> {
+    fn lookup<CS: ConstraintSystem<F>>(&self, mut cs: CS, bits: &[Wire<F>], table: &[(F, F)]) -> Result<(Wire<F>, Wire<F>), SynthesisError> {
+        let (magnitude, sign) = bits.split_at(self.magnitude_bits);
+        // One monomial per subset of the magnitude bits, the empty one being 1
+        let mut monomials = vec![Wire::constant::<CS>(F::ONE)];
+        for (i, bit) in magnitude.iter().enumerate() {
+            for mask in 0..1 << i {
+                let monomial = match mask {
+                    0 => bit.clone(),
+                    _ => monomials[mask].mul(cs.namespace(|| format!("monomial {}", mask | 1 << i)), bit)?,
+                };
+                monomials.push(monomial);
+            }
+        }
+
+        // Coefficients by the Moebius transform of the table over subsets
+        let mut coefficients = table.to_vec();
+        for i in 0..self.magnitude_bits {
+            for mask in (0..coefficients.len()).filter(|mask| mask & 1 << i != 0) {
+                let (u, v) = coefficients[mask ^ 1 << i];
+                coefficients[mask].0 -= u;
+                coefficients[mask].1 -= v;
+            }
+        }
+        let (u, v) = monomials.iter().zip(&coefficients).fold(
+            (Wire::constant::<CS>(F::ZERO), Wire::constant::<CS>(F::ZERO)),
+            |(u, v), (monomial, &(cu, cv))| (u.add(&monomial.scale(cu)), v.add(&monomial.scale(cv))),
+        );
+
+        let negate = Wire::constant::<CS>(F::ONE).add(&sign[0].scale(-F::from(2)));
+        let v = v.mul(cs.namespace(|| "conditional negation"), &negate)?;
+        Ok((u, v))
+    }
+
+    fn montgomery_add<CS: ConstraintSystem<F>>(&self, mut cs: CS, p: &(Wire<F>, Wire<F>), q: &(Wire<F>, Wire<F>)) -> Result<(Wire<F>, Wire<F>), SynthesisError> {
+        let ((u1, v1), (u2, v2)) = (p, q);
+        let du = u2.add(&u1.scale(-F::ONE));
+        let dv = v2.add(&v1.scale(-F::ONE));
+        let lambda = Wire::alloc(cs.namespace(|| "lambda"), dv.value * invert(du.value)?)?;
+        dv.enforce_product(cs.namespace(|| "lambda (u2 - u1) = v2 - v1"), &lambda, &du);
+
+        let u3_value = self.montgomery_b * lambda.value.square() - self.montgomery_a - u1.value - u2.value;
+        let u3 = Wire::alloc(cs.namespace(|| "u3"), u3_value)?;
+        let sum = u3.add(&Wire::constant::<CS>(self.montgomery_a)).add(u1).add(u2);
+        sum.enforce_product(cs.namespace(|| "B lambda^2 = u3 + A + u1 + u2"), &lambda.scale(self.montgomery_b), &lambda);
+
+        let v3 = Wire::alloc(cs.namespace(|| "v3"), lambda.value * (u1.value - u3.value) - v1.value)?;
+        v3.add(v1).enforce_product(cs.namespace(|| "lambda (u1 - u3) = v3 + v1"), &lambda, &u1.add(&u3.scale(-F::ONE)));
+        Ok((u3, v3))
+    }
+
+    // x = u / v and y = (u - 1) / (u + 1)
+    fn to_edwards<CS: ConstraintSystem<F>>(mut cs: CS, (u, v): &(Wire<F>, Wire<F>)) -> Result<(Wire<F>, Wire<F>), SynthesisError> {
+        let one = Wire::constant::<CS>(F::ONE);
+        let x = Wire::alloc(cs.namespace(|| "x"), u.value * invert(v.value)?)?;
+        u.enforce_product(cs.namespace(|| "x v = u"), &x, v);
+        let y = Wire::alloc(cs.namespace(|| "y"), (u.value - F::ONE) * invert(u.value + F::ONE)?)?;
+        u.add(&one.scale(-F::ONE)).enforce_product(cs.namespace(|| "y (u + 1) = u - 1"), &y, &u.add(&one));
+        Ok((x, y))
+    }
+
+    fn edwards_add<CS: ConstraintSystem<F>>(&self, mut cs: CS, p: &(Wire<F>, Wire<F>), q: &(Wire<F>, Wire<F>)) -> Result<(Wire<F>, Wire<F>), SynthesisError> {
+        let ((x1, y1), (x2, y2)) = (p, q);
+        let one = Wire::constant::<CS>(F::ONE);
+        let beta = x1.mul(cs.namespace(|| "x1 y2"), y2)?;
+        let gamma = y1.mul(cs.namespace(|| "y1 x2"), x2)?;
+        let delta = y1.add(&x1.scale(-self.a)).mul(cs.namespace(|| "(y1 - a x1)(x2 + y2)"), &x2.add(y2))?;
+        let tau = beta.mul(cs.namespace(|| "beta gamma"), &gamma)?;
+
+        let dt = self.d * tau.value;
+        let x3 = Wire::alloc(cs.namespace(|| "x3"), (beta.value + gamma.value) * invert(F::ONE + dt)?)?;
+        beta.add(&gamma).enforce_product(cs.namespace(|| "x3 (1 + d tau) = beta + gamma"), &x3, &one.add(&tau.scale(self.d)));
+        let y3_value = (delta.value + self.a * beta.value - gamma.value) * invert(F::ONE - dt)?;
+        let y3 = Wire::alloc(cs.namespace(|| "y3"), y3_value)?;
+        let numerator = delta.add(&beta.scale(self.a)).add(&gamma.scale(-F::ONE));
+        numerator.enforce_product(cs.namespace(|| "y3 (1 - d tau) = delta + a beta - gamma"), &y3, &one.add(&tau.scale(-self.d)));
+        Ok((x3, y3))
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for PedersenCircuit<'_, F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let chunk_bits = self.magnitude_bits + 1;
+        let mut total = None;
+        for (s, segment) in self.bits.chunks(chunk_bits * self.chunks_per_segment).enumerate() {
+            let mut cs = cs.namespace(|| format!("segment {}", s));
+            let mut sum = None;
+            for (j, chunk) in segment.chunks(chunk_bits).enumerate() {
+                let mut cs = cs.namespace(|| format!("chunk {}", j));
+                let bits = chunk
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &bit)| {
+                        let wire = Wire::alloc(cs.namespace(|| format!("bit {}", i)), F::from(bit as u64))?;
+                        wire.enforce_product(cs.namespace(|| format!("bit {} is boolean", i)), &wire, &wire);
+                        Ok(wire)
+                    })
+                    .collect::<Result<Vec<_>, SynthesisError>>()?;
+                let point = self.lookup(cs.namespace(|| "lookup"), &bits, &self.tables[s][j])?;
+                sum = Some(match sum {
+                    None => point,
+                    Some(sum) => self.montgomery_add(cs.namespace(|| "add"), &sum, &point)?,
+                });
+            }
+            let Some(sum) = sum else { continue };
+            let point = Self::to_edwards(cs.namespace(|| "to Edwards"), &sum)?;
+            total = Some(match total {
+                None => point,
+                Some(total) => self.edwards_add(cs.namespace(|| "add"), &total, &point)?,
+            });
+        }
+
+        let x = total.map(|(x, _)| x).unwrap_or_else(|| Wire::constant::<CS>(F::ZERO));
+        let digest = Wire::alloc(cs.namespace(|| "digest"), x.value)?;
+        digest.enforce_product(cs.namespace(|| "digest = x"), &x, &Wire::constant::<CS>(F::ONE));
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use bellpepper_core::num::AllocatedNum;
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use neptune::circuit2::Elt;
+use neptune::poseidon::{Arity, PoseidonConstants};
+use neptune::sponge::api::{IOPattern, SpongeAPI, SpongeOp};
+use neptune::sponge::circuit::SpongeCircuit;
+use neptune::sponge::vanilla::{Mode, SpongeTrait};
+
+use super::merkle::FieldGadget;
+
+// Poseidon over already-packed field elements, using the same SAFE sponge
+// and IO pattern as the native `hashes::Poseidon`. Packed elements are
+// allocated as private inputs at no constraint cost.
+pub struct PoseidonCircuit<'a, F: PrimeField, A: Arity<F>> {
+    pub constants: &'a PoseidonConstants<F, A>,
+    pub elements: Vec<F>,
+    // Exposes the digest as a public input (one more constraint), as a proof
+    // of knowledge of a preimage needs
+    pub public_digest: bool,
+}
+
+impl<F: PrimeField, A: Arity<F>> Circuit<F> for PoseidonCircuit<'_, F, A> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let elements = self
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(i, value)| AllocatedNum::alloc(cs.namespace(|| format!("element {}", i)), || Ok(*value)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let digest = self.hash_elements(&mut *cs, &elements)?;
+        if self.public_digest {
+            digest.inputize(cs.namespace(|| "public digest"))?;
+        }
+        Ok(())
+    }
+}
+
+// The sponge's IO pattern takes the element count, so elements need no padding
+impl<F: PrimeField, A: Arity<F>> FieldGadget<F> for PoseidonCircuit<'_, F, A> {
+    fn hash_elements<CS: ConstraintSystem<F>>(&self, mut cs: CS, elements: &[AllocatedNum<F>]) -> Result<AllocatedNum<F>, SynthesisError> {
+        let elements: Vec<Elt<F>> = elements.iter().cloned().map(Elt::Allocated).collect();
+        let length = elements.len() as u32;
+
+        let mut sponge = SpongeCircuit::new_with_constants(self.constants, Mode::Simplex);
+        let mut ns = cs.namespace(|| "sponge");
+        let acc = &mut ns;
+
+        sponge.start(IOPattern(vec![SpongeOp::Absorb(length), SpongeOp::Squeeze(1)]), None, acc);
+        SpongeAPI::absorb(&mut sponge, length, &elements, acc);
+        let digest = SpongeAPI::squeeze(&mut sponge, 1, acc);
+        let digest = digest[0].ensure_allocated(acc, true)?;
+        sponge.finish(acc).map_err(|_| SynthesisError::Unsatisfiable)?;
+        Ok(digest)
+    }
+}
@@ -0,0 +1,121 @@
+use bellpepper_core::num::AllocatedNum;
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+use super::merkle::FieldGadget;
+use super::wire::Wire;
+
+// Poseidon2 over already-packed field elements, with the same overwrite-mode
+// sponge as the native `hashes::Poseidon2`. Each S-box is a square-and-
+// multiply chain (3 constraints for x^5, 4 for x^7); the digest elements are
+// allocated at one constraint each. Packed elements are private inputs.
+pub struct Poseidon2Circuit<'a, F: PrimeField> {
+    pub rate: usize,
+    pub alpha: u64,
+    pub full_rounds: usize,
+    pub output: usize,
+    // t per full round, one per partial round
+    pub round_constants: &'a [Vec<F>],
+    // Internal matrix 1 + diag(d), as d
+    pub internal_diagonal: &'a [F],
+    pub elements: Vec<F>,
+}
+
+impl<F: PrimeField> Poseidon2Circuit<'_, F> {
+    fn sum(state: &[Wire<F>]) -> Wire<F> {
+        state[1..].iter().fold(state[0].clone(), |acc, x| acc.add(x))
+    }
+
+    // Mirrors `hashes::Poseidon2::external_matmul`
+    fn external_matmul(state: &mut [Wire<F>]) {
+        if state.len() <= 3 {
+            let sum = Self::sum(state);
+            state.iter_mut().for_each(|x| *x = x.add(&sum));
+            return;
+        }
+        let [two, four] = [F::from(2), F::from(4)];
+        for chunk in state.chunks_mut(4) {
+            let t0 = chunk[0].add(&chunk[1]);
+            let t1 = chunk[2].add(&chunk[3]);
+            let t2 = chunk[1].scale(two).add(&t1);
+            let t3 = chunk[3].scale(two).add(&t0);
+            let t4 = t1.scale(four).add(&t3);
+            let t5 = t0.scale(four).add(&t2);
+            chunk.clone_from_slice(&[t3.add(&t5), t5, t2.add(&t4), t4]);
+        }
+        if state.len() > 4 {
+            let mut columns = state[..4].to_vec();
+            for chunk in state[4..].chunks(4) {
+                columns.iter_mut().zip(chunk).for_each(|(c, x)| *c = c.add(x));
+            }
+            for (i, x) in state.iter_mut().enumerate() {
+                *x = x.add(&columns[i % 4]);
+            }
+        }
+    }
+
+    fn internal_matmul(&self, state: &mut [Wire<F>]) {
+        let sum = Self::sum(state);
+        for (x, &d) in state.iter_mut().zip(self.internal_diagonal) {
+            *x = x.scale(d).add(&sum);
+        }
+    }
+
+    fn permute<CS: ConstraintSystem<F>>(&self, cs: &mut CS, state: &mut [Wire<F>]) -> Result<(), SynthesisError> {
+        let half_full = self.full_rounds / 2;
+        let partial_rounds = self.round_constants.len() - self.full_rounds;
+        Self::external_matmul(state);
+        for (round, constants) in self.round_constants.iter().enumerate() {
+            let mut cs = cs.namespace(|| format!("round {}", round));
+            if round < half_full || round >= half_full + partial_rounds {
+                for (i, (x, &c)) in state.iter_mut().zip(constants).enumerate() {
+                    *x = x.add(&Wire::constant::<CS>(c)).pow(cs.namespace(|| format!("sbox {}", i)), self.alpha)?;
+                }
+                Self::external_matmul(state);
+            } else {
+                state[0] = state[0].add(&Wire::constant::<CS>(constants[0])).pow(cs.namespace(|| "sbox 0"), self.alpha)?;
+                self.internal_matmul(state);
+            }
+        }
+        Ok(())
+    }
+
+    // The overwrite sponge over already-padded elements; returns the state
+    fn absorb<CS: ConstraintSystem<F>>(&self, cs: &mut CS, elements: &[Wire<F>]) -> Result<Vec<Wire<F>>, SynthesisError> {
+        let width = self.round_constants[0].len();
+        let mut state = vec![Wire::constant::<CS>(F::ZERO); width];
+        for (block_index, block) in elements.chunks(self.rate).enumerate() {
+            let mut cs = cs.namespace(|| format!("block {}", block_index));
+            state[..block.len()].clone_from_slice(block);
+            self.permute(&mut cs, &mut state)?;
+        }
+        Ok(state)
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for Poseidon2Circuit<'_, F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let elements = self
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(i, value)| Wire::alloc(cs.namespace(|| format!("element {}", i)), *value))
+            .collect::<Result<Vec<_>, _>>()?;
+        let state = self.absorb(cs, &elements)?;
+        for (i, x) in state[..self.output].iter().enumerate() {
+            x.allocate(cs.namespace(|| format!("digest {}", i)))?;
+        }
+        Ok(())
+    }
+}
+
+// Zero padding to a multiple of the rate, as the native hash pads its packed
+// elements; the digest is the first state element, so `output` must be 1
+impl<F: PrimeField> FieldGadget<F> for Poseidon2Circuit<'_, F> {
+    fn hash_elements<CS: ConstraintSystem<F>>(&self, mut cs: CS, elements: &[AllocatedNum<F>]) -> Result<AllocatedNum<F>, SynthesisError> {
+        let mut padded = elements.iter().map(Wire::from_num).collect::<Result<Vec<_>, _>>()?;
+        padded.resize(padded.len().div_ceil(self.rate) * self.rate, Wire::constant::<CS>(F::ZERO));
+        let state = self.absorb(&mut cs, &padded)?;
+        state[0].allocate(cs.namespace(|| "digest"))
+    }
+}
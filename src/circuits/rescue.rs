@@ -0,0 +1,104 @@
+use bellpepper_core::num::AllocatedNum;
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+use super::merkle::{self, FieldGadget};
+use super::wire::Wire;
+
+// Rescue-Prime over already-padded field elements, with the same additive
+// sponge as the native `hashes::RescuePrime`. x^alpha is a square-and-
+// multiply chain; x^(1/alpha) allocates y = x^(1/alpha) as a witness and
+// checks y^alpha = x, at the same cost. The digest element is allocated at
+// one constraint.
+pub struct RescueCircuit<'a, F: PrimeField> {
+    pub rate: usize,
+    pub alpha: u64,
+    // 1 / alpha modulo p - 1, for the witness only
+    pub alpha_inv: &'a [u64],
+    pub mds: &'a [Vec<F>],
+    // 2m per round
+    pub round_constants: &'a [F],
+    pub elements: Vec<F>,
+}
+
+impl<F: PrimeField> RescueCircuit<'_, F> {
+    fn mds_mul(&self, state: &[Wire<F>]) -> Vec<Wire<F>> {
+        self.mds
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(state)
+                    .map(|(&m, x)| x.scale(m))
+                    .reduce(|acc, term| acc.add(&term))
+                    .expect("state is not empty")
+            })
+            .collect()
+    }
+
+    fn add_constants<CS: ConstraintSystem<F>>(state: &[Wire<F>], constants: &[F]) -> Vec<Wire<F>> {
+        state.iter().zip(constants).map(|(x, &c)| x.add(&Wire::constant::<CS>(c))).collect()
+    }
+
+    fn inverse_sbox<CS: ConstraintSystem<F>>(&self, mut cs: CS, x: &Wire<F>) -> Result<Wire<F>, SynthesisError> {
+        let y = Wire::alloc(cs.namespace(|| "y"), x.value.pow_vartime(self.alpha_inv))?;
+        let (a, b) = y.power_factors(cs.namespace(|| "y^alpha"), self.alpha)?;
+        x.enforce_product(cs.namespace(|| "y^alpha = x"), &a, &b);
+        Ok(y)
+    }
+
+    fn permute<CS: ConstraintSystem<F>>(&self, cs: &mut CS, state: Vec<Wire<F>>) -> Result<Vec<Wire<F>>, SynthesisError> {
+        let width = state.len();
+        let mut state = state;
+        for (round, constants) in self.round_constants.chunks(2 * width).enumerate() {
+            let mut cs = cs.namespace(|| format!("round {}", round));
+            let powered = state
+                .iter()
+                .enumerate()
+                .map(|(i, x)| x.pow(cs.namespace(|| format!("sbox {}", i)), self.alpha))
+                .collect::<Result<Vec<_>, _>>()?;
+            state = Self::add_constants::<CS>(&self.mds_mul(&powered), &constants[..width]);
+
+            let rooted = state
+                .iter()
+                .enumerate()
+                .map(|(i, x)| self.inverse_sbox(cs.namespace(|| format!("inverse sbox {}", i)), x))
+                .collect::<Result<Vec<_>, _>>()?;
+            state = Self::add_constants::<CS>(&self.mds_mul(&rooted), &constants[width..]);
+        }
+        Ok(state)
+    }
+
+    // The additive sponge over already-padded elements; returns the first
+    // state element
+    fn absorb<CS: ConstraintSystem<F>>(&self, cs: &mut CS, elements: &[Wire<F>]) -> Result<Wire<F>, SynthesisError> {
+        let mut state = vec![Wire::constant::<CS>(F::ZERO); self.mds.len()];
+        for (block_index, block) in elements.chunks(self.rate).enumerate() {
+            let mut cs = cs.namespace(|| format!("block {}", block_index));
+            for (i, element) in block.iter().enumerate() {
+                state[i] = state[i].add(element);
+            }
+            state = self.permute(&mut cs, state)?;
+        }
+        Ok(state.swap_remove(0))
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for RescueCircuit<'_, F> {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let elements = self
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(i, value)| Wire::alloc(cs.namespace(|| format!("element {}", i)), *value))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.absorb(cs, &elements)?.allocate(cs.namespace(|| "digest"))?;
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> FieldGadget<F> for RescueCircuit<'_, F> {
+    fn hash_elements<CS: ConstraintSystem<F>>(&self, mut cs: CS, elements: &[AllocatedNum<F>]) -> Result<AllocatedNum<F>, SynthesisError> {
+        let padded = merkle::pad::<F, CS>(elements, self.rate)?;
+        self.absorb(&mut cs, &padded)?.allocate(cs.namespace(|| "digest"))
+    }
+}
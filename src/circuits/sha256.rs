@@ -0,0 +1,85 @@
+use bellpepper::gadgets::multipack;
+use bellpepper::gadgets::sha256::sha256_compression_function;
+use bellpepper::gadgets::uint32::UInt32;
+use bellpepper_core::boolean::{AllocatedBit, Boolean};
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+const BLOCK_BYTES: usize = 64;
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+// SHA-256's padding: 0x80, zero bytes, then the message length in bits as a
+// big-endian u64, up to a whole number of blocks
+fn pad(preimage: &[u8]) -> Vec<u8> {
+    let mut padded = preimage.to_vec();
+    padded.push(0x80);
+    padded.resize((preimage.len() + 9).div_ceil(BLOCK_BYTES) * BLOCK_BYTES - 8, 0);
+    padded.extend((preimage.len() as u64 * 8).to_be_bytes());
+    padded
+}
+
+// SHA-256 of a private preimage. The message is padded natively and every
+// bit of the padded blocks is allocated (one boolean constraint each), so
+// the padding is part of the witness as Poseidon's packed elements are, and
+// no block folds to constants, not even for an empty input. The blocks go
+// through bellpepper's SHA-256 compression gadget. The digest bits are left
+// unexposed unless `public_digest` is set, which packs them into public
+// inputs (two constraints over a 255-bit field).
+pub struct Sha256Circuit {
+    pub preimage: Vec<u8>,
+    pub public_digest: bool,
+}
+
+impl<F: PrimeField> Circuit<F> for Sha256Circuit {
+    fn synthesize<CS: ConstraintSystem<F>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let bits = pad(&self.preimage)
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+            .enumerate()
+            .map(|(i, bit)| {
+                AllocatedBit::alloc(cs.namespace(|| format!("padded bit {}", i)), Some(bit))
+                    .map(Boolean::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut state: Vec<UInt32> = IV.iter().map(|&word| UInt32::constant(word)).collect();
+        for (i, block) in bits.chunks(BLOCK_BYTES * 8).enumerate() {
+            state = sha256_compression_function(cs.namespace(|| format!("block {}", i)), block, &state)?;
+        }
+        let digest: Vec<Boolean> = state.into_iter().flat_map(|word| word.into_bits_be()).collect();
+        if self.public_digest {
+            multipack::pack_into_inputs(cs.namespace(|| "public digest"), &digest)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bellpepper::gadgets::multipack;
+    use bellpepper_core::test_cs::TestConstraintSystem;
+    use bellpepper_core::Circuit;
+    use blstrs::Scalar as Fr;
+
+    use super::Sha256Circuit;
+    use crate::hashes::{HashFunction, Sha256};
+
+    // Lengths either side of 55 bytes, the most one block's padding leaves
+    // room for, and across several blocks
+    #[test]
+    fn digest_matches_native() {
+        for len in [0, 23, 55, 56, 64, 119, 200] {
+            let preimage: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let digest = Sha256.hash(&preimage).unwrap();
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            let circuit = Sha256Circuit { preimage, public_digest: true };
+            circuit.synthesize(&mut cs).unwrap();
+            assert!(cs.is_satisfied(), "length {}: {:?}", len, cs.which_is_unsatisfied());
+            let inputs = multipack::compute_multipacking::<Fr>(&multipack::bytes_to_bits(&digest));
+            assert!(cs.verify(&inputs), "length {}: digest differs from the native hash", len);
+        }
+    }
+}
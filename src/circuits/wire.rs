@@ -0,0 +1,78 @@
+use bellpepper_core::num::AllocatedNum;
+use bellpepper_core::{ConstraintSystem, LinearCombination, SynthesisError};
+use ff::PrimeField;
+
+// A linear combination of allocated variables with its witness value.
+// Linear layers and constant additions only rewrite it, so they cost nothing;
+// only `mul` and the power helpers add constraints.
+#[derive(Clone)]
+pub struct Wire<F: PrimeField> {
+    pub lc: LinearCombination<F>,
+    pub value: F,
+}
+
+impl<F: PrimeField> Wire<F> {
+    // A fresh private variable, at no constraint cost
+    pub fn alloc<CS: ConstraintSystem<F>>(mut cs: CS, value: F) -> Result<Self, SynthesisError> {
+        let num = AllocatedNum::alloc(cs.namespace(|| "value"), || Ok(value))?;
+        Ok(Wire { lc: LinearCombination::from_variable(num.get_variable()), value })
+    }
+
+    // An already-allocated variable, at no constraint cost
+    pub fn from_num(num: &AllocatedNum<F>) -> Result<Self, SynthesisError> {
+        let value = num.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+        Ok(Wire { lc: LinearCombination::from_variable(num.get_variable()), value })
+    }
+
+    pub fn constant<CS: ConstraintSystem<F>>(value: F) -> Self {
+        Wire { lc: LinearCombination::zero() + (value, CS::one()), value }
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        Wire { lc: self.lc.clone() + &other.lc, value: self.value + other.value }
+    }
+
+    pub fn scale(&self, factor: F) -> Self {
+        Wire { lc: LinearCombination::zero() + (factor, &self.lc), value: self.value * factor }
+    }
+
+    // One constraint: a fresh variable equal to `self * other`
+    pub fn mul<CS: ConstraintSystem<F>>(&self, mut cs: CS, other: &Self) -> Result<Self, SynthesisError> {
+        let product = Wire::alloc(cs.namespace(|| "product"), self.value * other.value)?;
+        product.enforce_product(cs.namespace(|| "product = a * b"), self, other);
+        Ok(product)
+    }
+
+    // One constraint: a fresh variable equal to `self`, e.g. for a digest
+    pub fn allocate<CS: ConstraintSystem<F>>(&self, mut cs: CS) -> Result<AllocatedNum<F>, SynthesisError> {
+        let num = AllocatedNum::alloc(cs.namespace(|| "value"), || Ok(self.value))?;
+        Wire::from_num(&num)?.enforce_product(cs.namespace(|| "value = x"), self, &Wire::constant::<CS>(F::ONE));
+        Ok(num)
+    }
+
+    // One constraint: `a * b = self`
+    pub fn enforce_product<CS: ConstraintSystem<F>>(&self, mut cs: CS, a: &Self, b: &Self) {
+        cs.enforce(|| "a * b = c", |_| a.lc.clone(), |_| b.lc.clone(), |_| self.lc.clone());
+    }
+
+    // Two wires whose product is self^alpha, by square-and-multiply: 1
+    // constraint for x^3, 2 for x^5, 3 for x^7
+    pub fn power_factors<CS: ConstraintSystem<F>>(&self, mut cs: CS, alpha: u64) -> Result<(Self, Self), SynthesisError> {
+        let x2 = self.mul(cs.namespace(|| "x^2"), self)?;
+        match alpha {
+            3 => Ok((x2, self.clone())),
+            5 => Ok((x2.mul(cs.namespace(|| "x^4"), &x2)?, self.clone())),
+            7 => {
+                let x4 = x2.mul(cs.namespace(|| "x^4"), &x2)?;
+                Ok((x4.mul(cs.namespace(|| "x^6"), &x2)?, self.clone()))
+            }
+            _ => Err(SynthesisError::Unsatisfiable),
+        }
+    }
+
+    // self^alpha as a fresh variable: 2 constraints for x^3, 3 for x^5, 4 for x^7
+    pub fn pow<CS: ConstraintSystem<F>>(&self, mut cs: CS, alpha: u64) -> Result<Self, SynthesisError> {
+        let (a, b) = self.power_factors(cs.namespace(|| "factors"), alpha)?;
+        a.mul(cs.namespace(|| "x^alpha"), &b)
+    }
+}
@@ -0,0 +1,284 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::bench::{BenchConfig, Iterations};
+use crate::chain::ChainSettings;
+use crate::hashes::{PoseidonField, RegistryConfig};
+use crate::merkle::MerkleSettings;
+
+const DEFAULT_INPUT: &[u8] = b"This is a test message.";
+
+// Native path verification runs once per depth, and a chain step once, so
+// both warm up briefly
+const MERKLE_WARMUP_MS: u64 = 50;
+
+#[derive(Parser, Debug)]
+#[command(name = "ethereum-hash-comparison", version)]
+#[command(about = "Compare traditional and SNARK-friendly hash functions")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the full comparison report (default)
+    Report(BenchArgs),
+    /// Benchmark native hashing speed
+    Bench(BenchArgs),
+    /// Print the digest of the input under each hash
+    Hash,
+    /// Print SNARK constraint estimates
+    Constraints,
+    /// Check each hash against its reference vectors
+    Verify,
+    /// Benchmark Poseidon at every supported arity
+    Sweep(BenchArgs),
+    /// Time Groth16 setup, proving and verification over BLS12-381
+    Prove(ProveArgs),
+    /// Measure Merkle membership circuits as the tree grows deeper
+    Merkle(MerkleArgs),
+    /// Measure Poseidon and SHA-256 hash chains, proved whole and folded with Nova
+    Chain(ChainArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct CommonArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+
+    /// Only run these hashes (comma-separated, e.g. sha256,poseidon)
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub hashes: Vec<String>,
+
+    /// Poseidon arity
+    #[arg(long, global = true, default_value_t = 2)]
+    pub poseidon_arity: usize,
+
+    /// Poseidon scalar fields (comma-separated), each run side by side
+    #[arg(long, global = true, value_enum, value_delimiter = ',', default_values_t = [PoseidonField::Bn254, PoseidonField::Bls12_381])]
+    pub poseidon_field: Vec<PoseidonField>,
+
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+#[group(multiple = false)]
+pub struct InputArgs {
+    /// Input as a literal string
+    #[arg(long, global = true)]
+    pub input: Option<String>,
+
+    /// Input as hex bytes
+    #[arg(long, global = true)]
+    pub hex: Option<String>,
+
+    /// Read input from a file
+    #[arg(long, global = true)]
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct BenchArgs {
+    /// Number of timed samples per hash
+    #[arg(long, default_value_t = 30)]
+    pub samples: usize,
+
+    /// Iterations per sample
+    #[arg(long, default_value_t = 1000, conflicts_with = "time")]
+    pub iterations: usize,
+
+    /// Time budget per hash in seconds, instead of a fixed iteration count
+    #[arg(long)]
+    pub time: Option<f64>,
+
+    /// Warmup time per hash in milliseconds
+    #[arg(long, default_value_t = 200)]
+    pub warmup_ms: u64,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ProveArgs {
+    /// Timed proofs and verifications per hash, after one warmup run each
+    #[arg(long, default_value_t = 3)]
+    pub samples: usize,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct MerkleArgs {
+    /// Tree depths to measure (comma-separated)
+    #[arg(long, value_delimiter = ',', default_values_t = [4, 8, 16, 32])]
+    pub depths: Vec<usize>,
+
+    /// Children per node
+    #[arg(long, default_value_t = 2)]
+    pub arity: usize,
+
+    /// Timed samples of native path verification
+    #[arg(long, default_value_t = 10)]
+    pub samples: usize,
+
+    /// Time budget in seconds for native path verification, per depth
+    #[arg(long, default_value_t = 0.2)]
+    pub time: f64,
+
+    /// Prove circuits up to this many constraints with Groth16 (0 proves none)
+    #[arg(long, default_value_t = 100_000)]
+    pub max_proof_constraints: usize,
+
+    /// Timed proofs and verifications per proved depth
+    #[arg(long, default_value_t = 3)]
+    pub proof_samples: usize,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ChainArgs {
+    /// Steps in the chain
+    #[arg(long, default_value_t = 16)]
+    pub steps: usize,
+
+    /// Hashes of the previous state per step
+    #[arg(long, default_value_t = 1)]
+    pub hashes_per_step: usize,
+
+    /// Timed samples of one native step
+    #[arg(long, default_value_t = 10)]
+    pub samples: usize,
+
+    /// Time budget in seconds for native step timing
+    #[arg(long, default_value_t = 0.2)]
+    pub time: f64,
+
+    /// Prove the whole chain with Groth16, and fold steps with Nova, up to this many constraints (0 proves none)
+    #[arg(long, default_value_t = 100_000)]
+    pub max_proof_constraints: usize,
+
+    /// Timed proofs and verifications of the whole chain, and of Nova's compressed proof
+    #[arg(long, default_value_t = 3)]
+    pub proof_samples: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl Default for BenchArgs {
+    fn default() -> Self {
+        BenchArgs {
+            samples: 30,
+            iterations: 1000,
+            time: None,
+            warmup_ms: 200,
+        }
+    }
+}
+
+impl BenchArgs {
+    pub fn config(&self) -> Result<BenchConfig, String> {
+        if self.samples == 0 {
+            return Err("--samples must be at least 1".to_string());
+        }
+        let iterations = match self.time {
+            Some(secs) if secs.is_finite() && secs > 0.0 => {
+                Iterations::TimeBudget(Duration::from_secs_f64(secs))
+            }
+            Some(secs) => return Err(format!("invalid --time {}", secs)),
+            None if self.iterations == 0 => {
+                return Err("--iterations must be at least 1".to_string())
+            }
+            None => Iterations::Fixed(self.iterations),
+        };
+        Ok(BenchConfig {
+            warmup: Duration::from_millis(self.warmup_ms),
+            samples: self.samples,
+            iterations,
+        })
+    }
+}
+
+impl MerkleArgs {
+    pub fn settings(&self) -> Result<MerkleSettings, String> {
+        if self.depths.is_empty() || self.depths.contains(&0) {
+            return Err("--depths needs at least one depth, each at least 1".to_string());
+        }
+        if self.arity < 2 {
+            return Err("--arity must be at least 2".to_string());
+        }
+        if self.samples == 0 || self.proof_samples == 0 {
+            return Err("--samples and --proof-samples must be at least 1".to_string());
+        }
+        if !self.time.is_finite() || self.time <= 0.0 {
+            return Err(format!("invalid --time {}", self.time));
+        }
+        Ok(MerkleSettings {
+            depths: self.depths.clone(),
+            arity: self.arity,
+            native: BenchConfig {
+                warmup: Duration::from_millis(MERKLE_WARMUP_MS),
+                samples: self.samples,
+                iterations: Iterations::TimeBudget(Duration::from_secs_f64(self.time)),
+            },
+            max_proof_constraints: self.max_proof_constraints,
+            proof_samples: self.proof_samples,
+        })
+    }
+}
+
+impl ChainArgs {
+    pub fn settings(&self) -> Result<ChainSettings, String> {
+        if self.steps == 0 || self.hashes_per_step == 0 {
+            return Err("--steps and --hashes-per-step must be at least 1".to_string());
+        }
+        if self.samples == 0 || self.proof_samples == 0 {
+            return Err("--samples and --proof-samples must be at least 1".to_string());
+        }
+        if !self.time.is_finite() || self.time <= 0.0 {
+            return Err(format!("invalid --time {}", self.time));
+        }
+        Ok(ChainSettings {
+            steps: self.steps,
+            hashes_per_step: self.hashes_per_step,
+            native: BenchConfig {
+                warmup: Duration::from_millis(MERKLE_WARMUP_MS),
+                samples: self.samples,
+                iterations: Iterations::TimeBudget(Duration::from_secs_f64(self.time)),
+            },
+            max_proof_constraints: self.max_proof_constraints,
+            proof_samples: self.proof_samples,
+        })
+    }
+}
+
+impl InputArgs {
+    pub fn read(&self) -> Result<Vec<u8>, String> {
+        if let Some(text) = &self.input {
+            Ok(text.as_bytes().to_vec())
+        } else if let Some(hex) = &self.hex {
+            let hex = hex.strip_prefix("0x").unwrap_or(hex);
+            hex::decode(hex).map_err(|e| format!("invalid --hex input: {}", e))
+        } else if let Some(path) = &self.file {
+            std::fs::read(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))
+        } else {
+            Ok(DEFAULT_INPUT.to_vec())
+        }
+    }
+}
+
+impl CommonArgs {
+    pub fn registry_config(&self) -> RegistryConfig {
+        RegistryConfig {
+            poseidon_arity: self.poseidon_arity,
+            poseidon_fields: self.poseidon_field.clone(),
+        }
+    }
+}
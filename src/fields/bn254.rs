@@ -0,0 +1,9 @@
+use ff::PrimeField;
+
+// BN254 (alt_bn128) scalar field, the field of Ethereum's ecAdd/ecMul/ecPairing
+// precompiles and of circom/snarkjs circuits. 5 generates the multiplicative group.
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "21888242871839275222246405745257275088548364400416034343698204186575808495617"]
+#[PrimeFieldGenerator = "5"]
+#[PrimeFieldReprEndianness = "little"]
+pub struct Fr([u64; 4]);
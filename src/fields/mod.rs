@@ -0,0 +1,118 @@
+mod bn254;
+mod small;
+
+pub use bn254::Fr as Bn254Fr;
+pub use small::{BabyBear, Goldilocks, Mersenne31, SmallPrime};
+
+use bellpepper_core::Circuit;
+use ff::PrimeField;
+use num_bigint::BigUint;
+
+use crate::groth16::{self, Groth16Cost};
+use crate::hashes::HashError;
+
+// Human-facing names of a scalar field, used in hash names and domains
+pub trait NamedField: PrimeField {
+    // Curve or field family, e.g. "BN254"
+    const CURVE: &'static str;
+    // The field itself, e.g. "BN254 Fr"
+    const NAME: &'static str;
+
+    // Groth16 over the field's pairing-friendly curve (see `groth16.rs`);
+    // None for fields without a pairing implementation here
+    fn groth16<C: Circuit<Self>>(
+        _circuit: impl Fn() -> C,
+        _public_inputs: &[Self],
+        _samples: usize,
+    ) -> Result<Option<Groth16Cost>, HashError> {
+        Ok(None)
+    }
+}
+
+// Whole bytes that always fit below the modulus: (NUM_BITS - 1) / 8, since any
+// value under 2^(NUM_BITS - 1) is canonical
+pub fn bytes_per_element<F: PrimeField>() -> usize {
+    (F::NUM_BITS as usize - 1) / 8
+}
+
+// Length of the field's canonical encoding
+pub fn repr_len<F: PrimeField>() -> usize {
+    F::Repr::default().as_ref().len()
+}
+
+// `ff` leaves the byte order of `Repr` to each field, so probe it with one
+fn repr_is_little_endian<F: PrimeField>() -> bool {
+    F::ONE.to_repr().as_ref()[0] == 1
+}
+
+// Decodes little-endian bytes (at most `repr_len` of them) into a field
+// element; None if the value is not below the modulus
+pub fn from_le_bytes<F: PrimeField>(bytes: &[u8]) -> Option<F> {
+    let mut repr = F::Repr::default();
+    let buf = repr.as_mut();
+    if repr_is_little_endian::<F>() {
+        buf[..bytes.len()].copy_from_slice(bytes);
+    } else {
+        let offset = buf.len() - bytes.len();
+        buf[offset..].iter_mut().zip(bytes.iter().rev()).for_each(|(b, &v)| *b = v);
+    }
+    F::from_repr(repr).into()
+}
+
+// Canonical encoding of `element`, little-endian whatever the field's `Repr` order
+pub fn to_le_bytes<F: PrimeField>(element: &F) -> Vec<u8> {
+    let mut bytes = element.to_repr().as_ref().to_vec();
+    if !repr_is_little_endian::<F>() {
+        bytes.reverse();
+    }
+    bytes
+}
+
+// The modulus as an integer, for parameter derivation that needs p itself
+// (e.g. exponents modulo p - 1)
+pub fn modulus<F: PrimeField>() -> BigUint {
+    let hex = F::MODULUS.trim_start_matches("0x");
+    BigUint::parse_bytes(hex.as_bytes(), 16).expect("PrimeField::MODULUS is hex")
+}
+
+// `value` reduced modulo the field's characteristic
+pub fn from_biguint<F: PrimeField>(value: &BigUint) -> F {
+    let bytes = (value % modulus::<F>()).to_bytes_le();
+    from_le_bytes(&bytes).expect("reduced value is canonical")
+}
+
+// The smallest alpha >= 3 with gcd(alpha, p - 1) = 1, so that x^alpha is a
+// permutation, and 1 / alpha modulo p - 1 as little-endian limbs for
+// `pow_vartime`
+pub fn invertible_power<F: PrimeField>() -> (u64, Vec<u64>) {
+    fn gcd(a: u64, b: u64) -> u64 {
+        if b == 0 { a } else { gcd(b, a % b) }
+    }
+    let p_minus_1 = modulus::<F>() - 1u32;
+    let remainder = |divisor: u64| (&p_minus_1 % divisor).to_u64_digits().first().copied().unwrap_or(0);
+    let alpha = (3..).find(|&a| gcd(a, remainder(a)) == 1).expect("some alpha is coprime to p - 1");
+    let alpha_inv = (1..alpha)
+        .map(|k| &p_minus_1 * k + 1u32)
+        .find(|e| (e % alpha).to_u64_digits().is_empty())
+        .map(|e| (e / alpha).to_u64_digits())
+        .expect("alpha is invertible modulo p - 1");
+    (alpha, alpha_inv)
+}
+
+impl NamedField for blstrs::Scalar {
+    const CURVE: &'static str = "BLS12-381";
+    const NAME: &'static str = "BLS12-381 Fr";
+
+    fn groth16<C: Circuit<Self>>(
+        circuit: impl Fn() -> C,
+        public_inputs: &[Self],
+        samples: usize,
+    ) -> Result<Option<Groth16Cost>, HashError> {
+        groth16::prove(circuit, public_inputs, samples).map(Some)
+    }
+}
+
+impl NamedField for Bn254Fr {
+    const CURVE: &'static str = "BN254";
+    const NAME: &'static str = "BN254 Fr";
+}
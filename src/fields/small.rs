@@ -0,0 +1,92 @@
+// Word-sized prime fields used by STARK provers. Elements are canonical u64
+// values below the modulus. Arithmetic is plain reduction (u64 for 31-bit
+// moduli, the 2^64 = 2^32 - 1 identity for Goldilocks), not the vectorised
+// Montgomery code of Plonky2/3.
+pub trait SmallPrime: 'static {
+    const NAME: &'static str;
+    const MODULUS: u64;
+
+    fn bits() -> usize {
+        64 - Self::MODULUS.leading_zeros() as usize
+    }
+
+    fn add(a: u64, b: u64) -> u64 {
+        let sum = a as u128 + b as u128;
+        if sum >= Self::MODULUS as u128 {
+            (sum - Self::MODULUS as u128) as u64
+        } else {
+            sum as u64
+        }
+    }
+
+    fn mul(a: u64, b: u64) -> u64 {
+        if Self::MODULUS <= u32::MAX as u64 {
+            a * b % Self::MODULUS
+        } else {
+            ((a as u128 * b as u128) % Self::MODULUS as u128) as u64
+        }
+    }
+
+    fn pow(base: u64, exp: u64) -> u64 {
+        let (mut base, mut exp, mut acc) = (base, exp, 1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = Self::mul(acc, base);
+            }
+            base = Self::mul(base, base);
+            exp >>= 1;
+        }
+        acc
+    }
+
+    // Inverse by Fermat's little theorem; `a` must be non-zero
+    fn inv(a: u64) -> u64 {
+        Self::pow(a, Self::MODULUS - 2)
+    }
+}
+
+// 2^64 - 2^32 + 1 (Plonky2)
+pub struct Goldilocks;
+
+// 2^31 - 2^27 + 1 (RISC Zero, SP1, Plonky3)
+pub struct BabyBear;
+
+// 2^31 - 1 (Plonky3, Stwo)
+pub struct Mersenne31;
+
+impl SmallPrime for Goldilocks {
+    const NAME: &'static str = "Goldilocks";
+    const MODULUS: u64 = 0xffff_ffff_0000_0001;
+
+    // Reduces hi * 2^64 + lo using 2^64 = 2^32 - 1 and 2^96 = -1
+    fn mul(a: u64, b: u64) -> u64 {
+        const EPSILON: u64 = 0xffff_ffff;
+        let product = a as u128 * b as u128;
+        let (lo, hi) = (product as u64, (product >> 64) as u64);
+        let (hi_hi, hi_lo) = (hi >> 32, hi & EPSILON);
+
+        let (mut t0, borrow) = lo.overflowing_sub(hi_hi);
+        if borrow {
+            t0 = t0.wrapping_sub(EPSILON);
+        }
+        let (mut t1, carry) = t0.overflowing_add(hi_lo * EPSILON);
+        if carry {
+            t1 = t1.wrapping_add(EPSILON);
+        }
+        if t1 >= Self::MODULUS {
+            t1 - Self::MODULUS
+        } else {
+            t1
+        }
+    }
+}
+
+impl SmallPrime for BabyBear {
+    const NAME: &'static str = "BabyBear";
+    const MODULUS: u64 = 0x7800_0001;
+}
+
+impl SmallPrime for Mersenne31 {
+    const NAME: &'static str = "Mersenne31";
+    const MODULUS: u64 = 0x7fff_ffff;
+}
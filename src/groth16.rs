@@ -0,0 +1,174 @@
+use std::time::{Duration, Instant};
+
+use bellman::groth16;
+use bellpepper_core::{Circuit, ConstraintSystem, Index, LinearCombination, SynthesisError, Variable};
+use blstrs::{Bls12, Scalar as Fr};
+use rand_core::OsRng;
+
+use crate::bench::{self, BenchConfig, BenchResult, Iterations};
+use crate::circuits::{self, R1csCost};
+use crate::hashes::HashError;
+
+// End-to-end Groth16 cost of proving knowledge of a preimage
+#[derive(Clone, Debug)]
+pub struct Groth16Cost {
+    // The proved circuit, digest inputs included
+    pub r1cs: R1csCost,
+    // A single run: the setup is a one-off per circuit
+    pub setup: BenchResult,
+    pub prove: BenchResult,
+    pub verify: BenchResult,
+    pub proof_bytes: usize,
+    // bellman's parameter file, which embeds the verifying key
+    pub proving_key_bytes: usize,
+    pub verifying_key_bytes: usize,
+}
+
+// A bellpepper circuit synthesized once, with its witness, and replayed
+// into bellman, which keeps its own copy of the constraint-system traits.
+// Both number inputs and auxiliary variables in allocation order, so the
+// recorded indices carry over unchanged.
+#[derive(Default)]
+struct Recorded {
+    // Excluding the constant `one`
+    inputs: Vec<Option<Fr>>,
+    aux: Vec<Option<Fr>>,
+    constraints: Vec<[LinearCombination<Fr>; 3]>,
+}
+
+impl Recorded {
+    fn synthesize<C: Circuit<Fr>>(circuit: C) -> Result<Recorded, HashError> {
+        let mut cs = Recorded::default();
+        circuit.synthesize(&mut cs).map_err(|e| HashError::Synthesis(e.to_string()))?;
+        Ok(cs)
+    }
+}
+
+impl ConstraintSystem<Fr> for Recorded {
+    type Root = Self;
+
+    fn alloc<F, A, AR>(&mut self, _annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.aux.push(f().ok());
+        Ok(Variable::new_unchecked(Index::Aux(self.aux.len() - 1)))
+    }
+
+    fn alloc_input<F, A, AR>(&mut self, _annotation: A, f: F) -> Result<Variable, SynthesisError>
+    where
+        F: FnOnce() -> Result<Fr, SynthesisError>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.inputs.push(f().ok());
+        Ok(Variable::new_unchecked(Index::Input(self.inputs.len())))
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _annotation: A, a: LA, b: LB, c: LC)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+        LA: FnOnce(LinearCombination<Fr>) -> LinearCombination<Fr>,
+        LB: FnOnce(LinearCombination<Fr>) -> LinearCombination<Fr>,
+        LC: FnOnce(LinearCombination<Fr>) -> LinearCombination<Fr>,
+    {
+        self.constraints.push([
+            a(LinearCombination::zero()),
+            b(LinearCombination::zero()),
+            c(LinearCombination::zero()),
+        ]);
+    }
+
+    fn push_namespace<NR, N>(&mut self, _name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+}
+
+fn to_bellman(lc: &LinearCombination<Fr>) -> bellman::LinearCombination<Fr> {
+    lc.iter().fold(bellman::LinearCombination::zero(), |acc, (variable, &coeff)| {
+        let index = match variable.get_unchecked() {
+            Index::Input(i) => bellman::Index::Input(i),
+            Index::Aux(i) => bellman::Index::Aux(i),
+        };
+        acc + (coeff, bellman::Variable::new_unchecked(index))
+    })
+}
+
+impl bellman::Circuit<Fr> for Recorded {
+    fn synthesize<CS: bellman::ConstraintSystem<Fr>>(self, cs: &mut CS) -> Result<(), bellman::SynthesisError> {
+        let missing = || bellman::SynthesisError::AssignmentMissing;
+        for (i, value) in self.aux.into_iter().enumerate() {
+            cs.alloc(|| format!("aux {}", i), || value.ok_or_else(missing))?;
+        }
+        for (i, value) in self.inputs.into_iter().enumerate() {
+            cs.alloc_input(|| format!("input {}", i), || value.ok_or_else(missing))?;
+        }
+        for (i, [a, b, c]) in self.constraints.iter().enumerate() {
+            cs.enforce(|| format!("constraint {}", i), |_| to_bellman(a), |_| to_bellman(b), |_| to_bellman(c));
+        }
+        Ok(())
+    }
+}
+
+// Groth16 over BLS12-381 for the circuit `circuit` builds, the digest being
+// its public input: one setup with locally sampled randomness (a stand-in
+// for a ceremony, so the keys are for benchmarking only), then `samples`
+// proofs and as many verifications after a warmup run of each. A proof that
+// does not verify is an error.
+pub fn prove<C: Circuit<Fr>>(circuit: impl Fn() -> C, public_inputs: &[Fr], samples: usize) -> Result<Groth16Cost, HashError> {
+    let synthesis = |e: bellman::SynthesisError| HashError::Synthesis(e.to_string());
+    let r1cs = circuits::measure(circuit()).map_err(|e| HashError::Synthesis(e.to_string()))?;
+
+    let start = Instant::now();
+    let params = groth16::generate_random_parameters::<Bls12, _, _>(Recorded::synthesize(circuit())?, &mut OsRng)
+        .map_err(synthesis)?;
+    let setup = bench::single_run("groth16 setup", start.elapsed());
+    let pvk = groth16::prepare_verifying_key(&params.vk);
+
+    let config = BenchConfig {
+        warmup: Duration::ZERO,
+        samples,
+        iterations: Iterations::Fixed(1),
+    };
+    // Synthesis and witness generation are part of proving
+    let mut proof = None;
+    let prove = bench::run("groth16 prove", &config, || {
+        proof = Some(Recorded::synthesize(circuit()).and_then(|circuit| {
+            groth16::create_random_proof(circuit, &params, &mut OsRng).map_err(synthesis)
+        }));
+    });
+    let proof = proof.expect("bench::run calls the closure")?;
+
+    let mut verified = Ok(());
+    let verify = bench::run("groth16 verify", &config, || {
+        verified = groth16::verify_proof(&pvk, &proof, public_inputs);
+    });
+    verified.map_err(|e| HashError::Proof(e.to_string()))?;
+
+    let size = |write: &dyn Fn(&mut Vec<u8>) -> std::io::Result<()>| -> Result<usize, HashError> {
+        let mut bytes = Vec::new();
+        write(&mut bytes).map_err(|e| HashError::Proof(e.to_string()))?;
+        Ok(bytes.len())
+    };
+    Ok(Groth16Cost {
+        r1cs,
+        setup,
+        prove,
+        verify,
+        proof_bytes: size(&|out| proof.write(out))?,
+        proving_key_bytes: size(&|out| params.write(out))?,
+        verifying_key_bytes: size(&|out| params.vk.write(out))?,
+    })
+}
@@ -0,0 +1,65 @@
+// Trace sizes for an AIR at constraint degree 3, as Plonky3's Poseidon2 and
+// Keccak AIRs lay them out: a trace of `width` committed columns over
+// `rows` rows, with constraints of degree at most 3 over one row and the
+// next. A permutation either fills one wide row with every intermediate it
+// commits, or takes one row per round of a narrower trace. STARK provers
+// pad the rows to a power of two; the counts here are not padded.
+
+use serde::Serialize;
+
+// Maximum constraint degree of every layout here
+pub const DEGREE: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct AirCost {
+    pub width: usize,
+    pub rows: usize,
+    pub degree: usize,
+    // Rate bytes of every permutation or compression run, so the padded
+    // input at full packing
+    pub absorbed_bytes: usize,
+}
+
+impl AirCost {
+    pub fn cells(&self) -> usize {
+        self.width * self.rows
+    }
+
+    pub fn cells_per_byte(&self) -> f64 {
+        self.cells() as f64 / self.absorbed_bytes as f64
+    }
+}
+
+// Committed cells of x^alpha at degree 3: its output, plus x^3 above
+// alpha = 3 (enough up to alpha = 9)
+pub fn sbox_cells(alpha: u64) -> usize {
+    if alpha <= 3 { 1 } else { 2 }
+}
+
+// One row per permutation of a substitution-permutation network: the input
+// state, then the S-boxes of every full and partial round. The linear
+// layers between them are folded into the constraints and add no cells.
+pub fn spn_width(width: usize, full_rounds: usize, partial_rounds: usize, alpha: u64) -> usize {
+    width + (full_rounds * width + partial_rounds) * sbox_cells(alpha)
+}
+
+// Keccak-f[1600] in Plonky3's keccak-air, one row per round: 24 round
+// flags and an export flag, the preimage and the round's input as 16-bit
+// limbs, theta's column parities C and C' and its output A' as bits, the
+// output of rho, pi and chi as limbs, and lane (0, 0) as bits before iota
+// and as limbs after it
+const KECCAK_WIDTH: usize = 24 + 1 + 100 + 100 + 320 + 320 + 1600 + 100 + 64 + 4;
+const KECCAK_ROUNDS: usize = 24;
+// Keccak-256's and SHA3-256's (512-bit capacity)
+const KECCAK_RATE_BYTES: usize = 136;
+
+// Keccak-256 or SHA3-256 over `data`, pad10*1 adding at least one byte
+pub fn keccak256(data: &[u8]) -> AirCost {
+    let permutations = (data.len() + 1).div_ceil(KECCAK_RATE_BYTES);
+    AirCost {
+        width: KECCAK_WIDTH,
+        rows: permutations * KECCAK_ROUNDS,
+        degree: DEGREE,
+        absorbed_bytes: permutations * KECCAK_RATE_BYTES,
+    }
+}
@@ -0,0 +1,314 @@
+use std::hint::black_box;
+
+use num_bigint::BigUint;
+
+use super::plonk::{self, PlonkishCost};
+use super::poseidon::pack_bytes;
+use super::{Domain, HashError, HashFunction, SetupPhase, UseCases, NON_STANDARD};
+use crate::circuits::{self, AnemoiCircuit, FieldNode, R1csCost};
+use crate::fields::{self, NamedField};
+use crate::merkle::{self, MerkleCost, MerkleSettings};
+
+// l = 2 columns: the state is (x_0, x_1, y_0, y_1), of which capacity 1
+const COLUMNS: usize = 2;
+const WIDTH: usize = 2 * COLUMNS;
+const CAPACITY: usize = 1;
+const SECURITY_LEVEL: usize = 128;
+
+// Digits 1-76 and 77-152 of pi's decimal expansion, the specification's
+// source of round constants
+const PI_0: &str = "1415926535897932384626433832795028841971693993751058209749445923078164062862";
+const PI_1: &str = "0899862803482534211706798214808651328230664709384460955058223172535940812848";
+
+// Everything the specification derives from (p, l, security level)
+struct Parameters<F> {
+    alpha: u64,
+    // 1 / alpha modulo p - 1, as little-endian limbs for `pow_vartime`
+    alpha_inv: Vec<u64>,
+    // g, the field's smallest generator: the Flystel's beta and the matrix entry
+    generator: F,
+    // The Flystel's delta = 1 / g (gamma is 0)
+    delta: F,
+    rounds: usize,
+    // One row of l per round, added to the x and y halves
+    c: Vec<Vec<F>>,
+    d: Vec<Vec<F>>,
+}
+
+impl<F: NamedField> Parameters<F> {
+    fn generate() -> Parameters<F> {
+        let (alpha, alpha_inv) = fields::invertible_power::<F>();
+        let generator = F::MULTIPLICATIVE_GENERATOR;
+        let delta = generator.invert().unwrap();
+        let rounds = Self::rounds(alpha);
+
+        // C_r,i = g (pi_0^r)^2 + (pi_0^r + pi_1^i)^alpha and
+        // D_r,i = g (pi_1^i)^2 + (pi_0^r + pi_1^i)^alpha + 1 / g
+        let parse = |digits: &str| fields::from_biguint::<F>(&BigUint::parse_bytes(digits.as_bytes(), 10).unwrap());
+        let (pi_0, pi_1) = (parse(PI_0), parse(PI_1));
+        let (mut c, mut d) = (Vec::with_capacity(rounds), Vec::with_capacity(rounds));
+        for r in 0..rounds {
+            let pi_0_r = pi_0.pow_vartime([r as u64]);
+            let (c_row, d_row): (Vec<F>, Vec<F>) = (0..COLUMNS)
+                .map(|i| {
+                    let pi_1_i = pi_1.pow_vartime([i as u64]);
+                    let power = (pi_0_r + pi_1_i).pow_vartime([alpha]);
+                    (generator * pi_0_r.square() + power, generator * pi_1_i.square() + power + delta)
+                })
+                .unzip();
+            c.push(c_row);
+            d.push(d_row);
+        }
+
+        Parameters { alpha, alpha_inv, generator, delta, rounds, c, d }
+    }
+
+    // The smallest r with C(4lr + kappa, 2lr)^2 >= 2^s against Groebner-basis
+    // attacks, plus 2 rounds for the second attack model and min(5, l + 1)
+    // margin, at least 8
+    fn rounds(alpha: u64) -> usize {
+        let kappa = match alpha {
+            3 => 1,
+            5 => 2,
+            7 => 4,
+            9 => 7,
+            11 => 9,
+            _ => panic!("no Anemoi round bound for alpha = {}", alpha),
+        };
+        // log2 of (n choose k)
+        let log2_binomial = |n: usize, k: usize| (1..=k).map(|i| ((n - k + i) as f64 / i as f64).log2()).sum::<f64>();
+        let r = (1..)
+            .find(|&r| 2.0 * log2_binomial(4 * COLUMNS * r + kappa, 2 * COLUMNS * r) >= SECURITY_LEVEL as f64)
+            .expect("the bound grows with r");
+        (r + 2 + (COLUMNS + 1).min(5)).max(8)
+    }
+}
+
+// Anemoi (Bouvier, Briaud, Chaidos, Perrin, Salen, Velichkov, Willems 2022)
+// over a SNARK scalar field, with l = 2. Each round adds C to the x half and
+// D to the y half, applies the linear layer, then one open Flystel per
+// column:
+//
+//   x -= g y^2;  y -= x^(1/alpha);  x += g y^2 + 1/g
+//
+// The permutation ends with one more linear layer. The linear layer is
+// M_x = [[1, g], [g, g^2 + 1]] on x, the same on y with its words swapped,
+// then the pseudo-Hadamard transform y += x, x += y. In a circuit the
+// Flystel is checked in closed form, (y - v)^alpha = x - g y^2 for the
+// output y = v, which is why it costs about as much as a single x^alpha.
+//
+// Bytes are packed as for Poseidon and absorbed with the same additive
+// sponge and 10* padding as Rescue-Prime. The digest is x_0. The constants
+// follow the specification, but with no reference vectors to check them the
+// instance is marked non-standard.
+pub struct Anemoi<F: NamedField> {
+    name: &'static str,
+    parameters: Parameters<F>,
+}
+
+impl<F: NamedField> Anemoi<F> {
+    pub fn new() -> Self {
+        Anemoi {
+            name: Box::leak(format!("Anemoi-{}{}", F::CURVE, NON_STANDARD).into_boxed_str()),
+            parameters: Parameters::generate(),
+        }
+    }
+
+    fn rate() -> usize {
+        WIDTH - CAPACITY
+    }
+
+    fn linear_layer(&self, state: &mut [F]) {
+        let g = self.parameters.generator;
+        let (x, y) = state.split_at_mut(COLUMNS);
+        x[0] += g * x[1];
+        x[1] += g * x[0];
+        y[1] += g * y[0];
+        y[0] += g * y[1];
+        y.iter_mut().zip(x.iter()).for_each(|(y, x)| *y += x);
+        x.iter_mut().zip(y.iter()).for_each(|(x, y)| *x += y);
+    }
+
+    fn permute(&self, state: &mut [F]) {
+        let parameters = &self.parameters;
+        for (c, d) in parameters.c.iter().zip(&parameters.d) {
+            state[..COLUMNS].iter_mut().zip(c).for_each(|(x, c)| *x += c);
+            state[COLUMNS..].iter_mut().zip(d).for_each(|(y, d)| *y += d);
+            self.linear_layer(state);
+            for i in 0..COLUMNS {
+                let (mut x, mut y) = (state[i], state[COLUMNS + i]);
+                x -= parameters.generator * y.square();
+                y -= x.pow_vartime(&parameters.alpha_inv);
+                x += parameters.generator * y.square() + parameters.delta;
+                (state[i], state[COLUMNS + i]) = (x, y);
+            }
+        }
+        self.linear_layer(state);
+    }
+
+    // The sponge's own 10* padding to a multiple of the rate
+    fn pad(mut elements: Vec<F>) -> Vec<F> {
+        elements.push(F::ONE);
+        elements.resize(elements.len().div_ceil(Self::rate()) * Self::rate(), F::ZERO);
+        elements
+    }
+
+    // Packed elements, padded
+    fn absorbed_elements(data: &[u8]) -> Result<Vec<F>, HashError> {
+        Ok(Self::pad(pack_bytes::<F>(data)?))
+    }
+
+    // Absorbs padded elements, returning the first state element
+    fn sponge(&self, elements: &[F]) -> F {
+        let mut state = vec![F::ZERO; WIDTH];
+        for block in elements.chunks(Self::rate()) {
+            state.iter_mut().zip(block).for_each(|(x, m)| *x += m);
+            self.permute(&mut state);
+        }
+        state[0]
+    }
+
+    // The gadget over `elements`, already padded
+    fn circuit(&self, elements: Vec<F>) -> AnemoiCircuit<'_, F> {
+        let parameters = &self.parameters;
+        AnemoiCircuit {
+            rate: Self::rate(),
+            alpha: parameters.alpha,
+            alpha_inv: &parameters.alpha_inv,
+            generator: parameters.generator,
+            delta: parameters.delta,
+            c: &parameters.c,
+            d: &parameters.d,
+            elements,
+        }
+    }
+}
+
+impl<F: NamedField> HashFunction for Anemoi<F> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn output_size(&self) -> usize {
+        fields::repr_len::<F>()
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Field(F::NAME)
+    }
+
+    fn security_bits(&self) -> u32 {
+        SECURITY_LEVEL as u32
+    }
+
+    fn parameters(&self) -> Option<String> {
+        Some(format!(
+            "l={}, rate {}, Flystel x^{}, {} rounds, unchecked constants",
+            COLUMNS,
+            Self::rate(),
+            self.parameters.alpha,
+            self.parameters.rounds
+        ))
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        Ok(fields::to_le_bytes(&self.sponge(&Self::absorbed_elements(data)?)))
+    }
+
+    fn field_elements(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        Ok(Some(pack_bytes::<F>(data)?.len()))
+    }
+
+    fn setup_phases(&self) -> Vec<SetupPhase> {
+        vec![SetupPhase {
+            name: "round constants from pi",
+            run: Box::new(|| {
+                black_box(Parameters::<F>::generate());
+            }),
+        }]
+    }
+
+    // Closed Flystel: y^2, v^2, and (y - v)^5 at 3 constraints, l per round
+    fn snark_constraints(&self) -> Option<usize> {
+        Some(5 * COLUMNS * self.parameters.rounds)
+    }
+
+    fn snark_constraints_computed(&self) -> bool {
+        true
+    }
+
+    fn r1cs_cost(&self, data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        circuits::measure(self.circuit(Self::absorbed_elements(data)?))
+            .map(Some)
+            .map_err(|e| HashError::Synthesis(e.to_string()))
+    }
+
+    // A node absorbs one element per child, padded as any input
+    fn merkle(&self, data: &[u8], settings: &MerkleSettings) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        let node = |children: &[Vec<u8>]| -> Result<Vec<u8>, HashError> {
+            Ok(fields::to_le_bytes(&self.sponge(&Self::pad(merkle::elements::<F>(children)?))))
+        };
+        let gadget = FieldNode(self.circuit(Vec::new()));
+        merkle::measure::<F, _>(&gadget, |data| self.hash(data), node, data, settings).map(Some)
+    }
+
+    // Closed Flystel: y^2, v^2, y - v, (y - v)^alpha, x - g y^2 to compare
+    // against it, and u. The linear layer is two in-place updates for each
+    // of M_x and M_y plus 2l for the transform.
+    fn plonk_gates(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        let flystel = 2 + 1 + plonk::power_gates(self.parameters.alpha) + 1 + 1;
+        let linear_layer = 2 * 2 + 2 * COLUMNS;
+        let per_permutation = self.parameters.rounds * (linear_layer + COLUMNS * flystel) + linear_layer;
+        let elements = Self::absorbed_elements(data)?.len();
+        let permutations = elements / Self::rate();
+        Ok(Some(permutations * per_permutation + plonk::absorb_gates(elements, Self::rate())))
+    }
+
+    // One row per round plus the final linear layer's, with a column per
+    // Flystel for the witness v that closes it at degree alpha
+    fn plonkish_cost(&self, data: &[u8]) -> Result<Option<PlonkishCost>, HashError> {
+        let permutations = Self::absorbed_elements(data)?.len() / Self::rate();
+        Ok(Some(PlonkishCost {
+            advice_columns: WIDTH + COLUMNS,
+            rows: plonk::sponge_rows(permutations, self.parameters.rounds + 1, true),
+            lookup_tables: 0,
+            table_rows: 0,
+            degree: plonk::gate_degree(self.parameters.alpha),
+        }))
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "Plonkish and R1CS circuits: fewest constraints per round",
+                "Merkle trees via the Jive compression mode",
+            ],
+            bad_for: &["Slow natively: the Flystel needs x^(1/alpha)"],
+            ethereum_use: "zk research",
+            best_for: "Low-constraint ZK",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Anemoi;
+    use crate::circuits::{self, FieldNode};
+    use crate::fields;
+    use crate::hashes::poseidon::pack_bytes;
+    use crate::hashes::HashFunction;
+
+    // The packed elements, hashed as one node, give the byte hash's digest:
+    // both pad in the sponge. Lengths cover one, two and several permutations.
+    #[test]
+    fn gadget_matches_native() {
+        let hash = Anemoi::<blstrs::Scalar>::new();
+        let gadget = FieldNode(hash.circuit(Vec::new()));
+        for len in [0, 31, 62, 200] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let elements: Vec<Vec<u8>> = pack_bytes::<blstrs::Scalar>(&data).unwrap().iter().map(fields::to_le_bytes).collect();
+            let context = format!("{}, {} bytes", hash.name(), len);
+            circuits::assert_node_digest(&gadget, &elements, &hash.hash(&data).unwrap(), &context);
+        }
+    }
+}
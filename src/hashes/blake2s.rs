@@ -0,0 +1,71 @@
+use blstrs::Scalar as Fr;
+
+use super::{Domain, HashError, HashFunction, KnownAnswer, UseCases};
+use crate::circuits::{self, BitGadget, Blake2sCircuit, R1csCost};
+use crate::merkle::{self, MerkleCost, MerkleSettings};
+
+pub struct Blake2s;
+
+impl HashFunction for Blake2s {
+    fn name(&self) -> &'static str {
+        "BLAKE2s-256"
+    }
+
+    fn output_size(&self) -> usize {
+        32
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Bytes
+    }
+
+    fn security_bits(&self) -> u32 {
+        128
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        Ok(blake2s_simd::blake2s(data).as_bytes().to_vec())
+    }
+
+    // One compression in the Zcash protocol specification's gadget
+    fn snark_constraints(&self) -> Option<usize> {
+        Some(21_006)
+    }
+
+    fn r1cs_cost(&self, data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        let circuit = Blake2sCircuit {
+            preimage: data.to_vec(),
+            expected: self.hash(data)?,
+        };
+        circuits::measure::<Fr, _>(circuit)
+            .map(Some)
+            .map_err(|e| HashError::Synthesis(e.to_string()))
+    }
+
+    // A node hashes its children's digests concatenated
+    fn merkle(&self, data: &[u8], settings: &MerkleSettings) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        let node = |children: &[Vec<u8>]| self.hash(&children.concat());
+        merkle::measure::<Fr, _>(&BitGadget::Blake2s, |data| self.hash(data), node, data, settings).map(Some)
+    }
+
+    // RFC 7693 appendix B
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        vec![KnownAnswer {
+            name: "\"abc\"",
+            expected: "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+            actual: self.hash(b"abc").map(hex::encode).unwrap_or_default(),
+        }]
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "Fast software hashing on 32-bit platforms",
+                "Zcash Sapling key derivation and PRFs",
+            ],
+            bad_for: &["Expensive in zkSNARKs, though cheaper than SHA-256"],
+            ethereum_use: "None (BLAKE2b only)",
+            best_for: "Fast general purpose",
+        }
+    }
+}
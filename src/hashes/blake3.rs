@@ -0,0 +1,76 @@
+use blstrs::Scalar as Fr;
+
+use super::{Domain, HashError, HashFunction, KnownAnswer, UseCases};
+use crate::circuits::{self, BitGadget, Blake3Circuit, R1csCost};
+use crate::merkle::{self, MerkleCost, MerkleSettings};
+
+pub struct Blake3;
+
+impl HashFunction for Blake3 {
+    fn name(&self) -> &'static str {
+        "BLAKE3"
+    }
+
+    fn output_size(&self) -> usize {
+        32
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Bytes
+    }
+
+    fn security_bits(&self) -> u32 {
+        128
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        Ok(blake3::hash(data).as_bytes().to_vec())
+    }
+
+    // No published gadget count: BLAKE2s's 21,006 per compression scaled
+    // from 10 rounds to BLAKE3's 7
+    fn snark_constraints(&self) -> Option<usize> {
+        Some(15_000)
+    }
+
+    fn snark_constraints_computed(&self) -> bool {
+        true
+    }
+
+    fn r1cs_cost(&self, data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        let circuit = Blake3Circuit {
+            preimage: data.to_vec(),
+            expected: self.hash(data)?,
+        };
+        circuits::measure::<Fr, _>(circuit)
+            .map(Some)
+            .map_err(|e| HashError::Synthesis(e.to_string()))
+    }
+
+    // A node hashes its children's digests concatenated
+    fn merkle(&self, data: &[u8], settings: &MerkleSettings) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        let node = |children: &[Vec<u8>]| self.hash(&children.concat());
+        merkle::measure::<Fr, _>(&BitGadget::Blake3, |data| self.hash(data), node, data, settings).map(Some)
+    }
+
+    // The reference implementation's test vectors
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        vec![KnownAnswer {
+            name: "empty input",
+            expected: "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+            actual: self.hash(b"").map(hex::encode).unwrap_or_default(),
+        }]
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "Very fast hashing of large inputs (SIMD, tree parallelism)",
+                "File integrity and content addressing",
+            ],
+            bad_for: &["Expensive in zkSNARKs: 32-bit additions and XORs"],
+            ethereum_use: "None",
+            best_for: "Bulk data",
+        }
+    }
+}
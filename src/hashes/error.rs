@@ -0,0 +1,32 @@
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HashError {
+    // Bytes that do not decode to a field element below the modulus
+    NonCanonicalEncoding { field: &'static str, bytes: Vec<u8> },
+    // More inputs than a fixed-size permutation or IO pattern can take
+    TooManyInputs { max: usize, given: usize },
+    InvalidParameters(String),
+    // Circuit synthesis failed while measuring R1CS cost or proving
+    Synthesis(String),
+    // A Groth16 proof did not verify, or its keys could not be serialized
+    Proof(String),
+}
+
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashError::NonCanonicalEncoding { field, bytes } => {
+                write!(f, "non-canonical {} encoding: 0x{}", field, hex::encode(bytes))
+            }
+            HashError::TooManyInputs { max, given } => {
+                write!(f, "too many inputs: got {}, at most {} allowed", given, max)
+            }
+            HashError::InvalidParameters(reason) => write!(f, "invalid parameters: {}", reason),
+            HashError::Synthesis(reason) => write!(f, "circuit synthesis failed: {}", reason),
+            HashError::Proof(reason) => write!(f, "Groth16 proof failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for HashError {}
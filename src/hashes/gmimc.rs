@@ -0,0 +1,223 @@
+use std::hint::black_box;
+
+use super::plonk::{self, PlonkishCost};
+use super::poseidon::pack_bytes;
+use super::shake::ShakeSampler;
+use super::{Domain, HashError, HashFunction, SetupPhase, UseCases, NON_STANDARD};
+use crate::circuits::{self, FieldNode, GmimcCircuit, R1csCost};
+use crate::fields::{self, NamedField};
+use crate::merkle::{self, MerkleCost, MerkleSettings};
+
+// GMiMC-erf at width 3, x^5 with the round count the zkhash reference
+// implementation uses for both BN254 and BLS12-381
+const WIDTH: usize = 3;
+const CAPACITY: usize = 1;
+const ALPHA: u64 = 5;
+const ROUNDS: usize = 226;
+
+// `ShakeSampler` seeded with "GMiMC". The reference implementation currently
+// draws random constants instead, so this is its documented (commented-out)
+// derivation and there are no published vectors.
+fn round_constants<F: NamedField>() -> Vec<F> {
+    let mut sampler = ShakeSampler::<F>::new("GMiMC");
+    (0..ROUNDS).map(|_| sampler.next()).collect()
+}
+
+// GMiMC-erf (Albrecht et al. 2019), an unbalanced Feistel network over a
+// SNARK scalar field. Each round computes one S-box (x_0 + c_i)^5, adds it to
+// every other element and rotates the state right by one; the last round does
+// not rotate. One S-box per round keeps the R1CS cost at 3 constraints a
+// round, but the round count grows with the field size, like MiMC's.
+//
+// Bytes are packed exactly as for Poseidon and absorbed with the same additive
+// sponge and 10* padding as Rescue-Prime. The digest is the first state
+// element (32 bytes). Without reference constants the instances are marked
+// non-standard.
+pub struct Gmimc<F: NamedField> {
+    name: &'static str,
+    round_constants: Vec<F>,
+}
+
+impl<F: NamedField> Gmimc<F> {
+    pub fn new() -> Self {
+        Gmimc {
+            name: Box::leak(format!("GMiMC-{}{}", F::CURVE, NON_STANDARD).into_boxed_str()),
+            round_constants: round_constants(),
+        }
+    }
+
+    fn rate() -> usize {
+        WIDTH - CAPACITY
+    }
+
+    fn permute(&self, state: &mut [F]) {
+        for (round, c) in self.round_constants.iter().enumerate() {
+            let t = state[0] + c;
+            let power = t.square().square() * t;
+            state[1..].iter_mut().for_each(|x| *x += power);
+            if round < ROUNDS - 1 {
+                state.rotate_right(1);
+            }
+        }
+    }
+
+    // The sponge's own 10* padding to a multiple of the rate
+    fn pad(mut elements: Vec<F>) -> Vec<F> {
+        elements.push(F::ONE);
+        elements.resize(elements.len().div_ceil(Self::rate()) * Self::rate(), F::ZERO);
+        elements
+    }
+
+    // Packed elements, padded
+    fn absorbed_elements(data: &[u8]) -> Result<Vec<F>, HashError> {
+        Ok(Self::pad(pack_bytes::<F>(data)?))
+    }
+
+    // Absorbs padded elements, returning the first state element
+    fn sponge(&self, elements: &[F]) -> F {
+        let mut state = vec![F::ZERO; WIDTH];
+        for block in elements.chunks(Self::rate()) {
+            state.iter_mut().zip(block).for_each(|(x, m)| *x += m);
+            self.permute(&mut state);
+        }
+        state[0]
+    }
+
+    // The gadget over `elements`, already padded
+    fn circuit(&self, elements: Vec<F>) -> GmimcCircuit<'_, F> {
+        GmimcCircuit {
+            rate: Self::rate(),
+            width: WIDTH,
+            alpha: ALPHA,
+            round_constants: &self.round_constants,
+            elements,
+        }
+    }
+}
+
+impl<F: NamedField> HashFunction for Gmimc<F> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn output_size(&self) -> usize {
+        fields::repr_len::<F>()
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Field(F::NAME)
+    }
+
+    fn security_bits(&self) -> u32 {
+        128
+    }
+
+    fn parameters(&self) -> Option<String> {
+        Some(format!("t={}, rate {}, x^{}, {} rounds, non-standard constants", WIDTH, Self::rate(), ALPHA, ROUNDS))
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        Ok(fields::to_le_bytes(&self.sponge(&Self::absorbed_elements(data)?)))
+    }
+
+    fn field_elements(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        Ok(Some(pack_bytes::<F>(data)?.len()))
+    }
+
+    fn setup_phases(&self) -> Vec<SetupPhase> {
+        vec![SetupPhase {
+            name: "SHAKE128 round constants",
+            run: Box::new(|| {
+                black_box(round_constants::<F>());
+            }),
+        }]
+    }
+
+    // One x^5 S-box per round at 3 constraints
+    fn snark_constraints(&self) -> Option<usize> {
+        Some(3 * ROUNDS)
+    }
+
+    fn snark_constraints_computed(&self) -> bool {
+        true
+    }
+
+    fn r1cs_cost(&self, data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        circuits::measure(self.circuit(Self::absorbed_elements(data)?))
+            .map(Some)
+            .map_err(|e| HashError::Synthesis(e.to_string()))
+    }
+
+    // A node absorbs one element per child, padded as any input
+    fn merkle(&self, data: &[u8], settings: &MerkleSettings) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        let node = |children: &[Vec<u8>]| -> Result<Vec<u8>, HashError> {
+            Ok(fields::to_le_bytes(&self.sponge(&Self::pad(merkle::elements::<F>(children)?))))
+        };
+        let gadget = FieldNode(self.circuit(Vec::new()));
+        merkle::measure::<F, _>(&gadget, |data| self.hash(data), node, data, settings).map(Some)
+    }
+
+    // One S-box and t - 1 additions per round
+    fn plonk_gates(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        let per_round = plonk::power_gates(ALPHA) + (WIDTH - 1);
+        let elements = Self::absorbed_elements(data)?.len();
+        let permutations = elements / Self::rate();
+        Ok(Some(permutations * ROUNDS * per_round + plonk::absorb_gates(elements, Self::rate())))
+    }
+
+    // One row per round over the state alone
+    fn plonkish_cost(&self, data: &[u8]) -> Result<Option<PlonkishCost>, HashError> {
+        let permutations = Self::absorbed_elements(data)?.len() / Self::rate();
+        Ok(Some(PlonkishCost {
+            advice_columns: WIDTH,
+            rows: plonk::sponge_rows(permutations, ROUNDS, true),
+            lookup_tables: 0,
+            table_rows: 0,
+            degree: plonk::gate_degree(ALPHA),
+        }))
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "Wide states: the S-box count does not grow with t",
+                "Migration target where MiMC's Feistel shape is familiar",
+            ],
+            bad_for: &["Algebraic attacks have repeatedly cut its margins"],
+            ethereum_use: "zk research",
+            best_for: "Wide-state ZK",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Gmimc;
+    use crate::circuits::{self, FieldNode};
+    use crate::fields::{self, Bn254Fr, NamedField};
+    use crate::hashes::poseidon::pack_bytes;
+    use crate::hashes::HashFunction;
+
+    // The packed elements, hashed as one node, give the byte hash's digest:
+    // both pad in the sponge. Lengths cover one, two and several permutations.
+    fn gadget_matches_native<F: NamedField>() {
+        let hash = Gmimc::<F>::new();
+        let gadget = FieldNode(hash.circuit(Vec::new()));
+        for len in [0, 31, 62, 200] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let elements: Vec<Vec<u8>> = pack_bytes::<F>(&data).unwrap().iter().map(fields::to_le_bytes).collect();
+            let context = format!("{}, {} bytes", hash.name(), len);
+            circuits::assert_node_digest(&gadget, &elements, &hash.hash(&data).unwrap(), &context);
+        }
+    }
+
+    #[test]
+    fn gadget_matches_native_bn254() {
+        gadget_matches_native::<Bn254Fr>();
+    }
+
+    #[test]
+    fn gadget_matches_native_bls12_381() {
+        gadget_matches_native::<blstrs::Scalar>();
+    }
+}
@@ -0,0 +1,79 @@
+// The Grain LFSR from the Poseidon reference scripts, which derives round
+// constants and Cauchy MDS matrices from the instance parameters alone.
+// Seeded like the HorizenLabs/zkhash instances, so the same parameters give
+// the same constants. The S-box tag is part of the seed: zkhash's Poseidon
+// instances were generated with tag 1 and its Poseidon2 instances with tag 0.
+pub struct Grain {
+    state: [bool; 80],
+}
+
+impl Grain {
+    // `field_bits` is the bit length of the modulus; `width` the state size
+    pub fn new(sbox_tag: usize, field_bits: usize, width: usize, full_rounds: usize, partial_rounds: usize) -> Grain {
+        let mut seed = Vec::with_capacity(80);
+        let mut push = |value: usize, len: usize| {
+            seed.extend((0..len).rev().map(|i| (value >> i) & 1 == 1));
+        };
+        // Prime field, S-box tag, then the instance parameters
+        push(1, 2);
+        push(sbox_tag, 4);
+        push(field_bits, 12);
+        push(width, 12);
+        push(full_rounds, 10);
+        push(partial_rounds, 10);
+        seed.resize(80, true);
+
+        let mut grain = Grain { state: [false; 80] };
+        grain.state.copy_from_slice(&seed);
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let s = &self.state;
+        let bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0];
+        self.state.rotate_left(1);
+        self.state[79] = bit;
+        bit
+    }
+
+    // Bits come in pairs; the second is kept only if the first is set
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let keep = self.step();
+            let bit = self.step();
+            if keep {
+                return bit;
+            }
+        }
+    }
+
+    // `n` bits, most significant first
+    pub fn bits(&mut self, n: usize) -> u64 {
+        (0..n).fold(0, |acc, _| acc << 1 | self.next_bit() as u64)
+    }
+
+    // Uniform element below `modulus` by rejection, as used for round constants
+    pub fn element(&mut self, modulus: u64, n: usize) -> u64 {
+        loop {
+            let value = self.bits(n);
+            if value < modulus {
+                return value;
+            }
+        }
+    }
+
+    // `n` bits as a little-endian byte string, most significant bit drawn
+    // first, for moduli wider than a word
+    pub fn bytes(&mut self, n: usize) -> Vec<u8> {
+        let mut bytes = vec![0; n.div_ceil(8)];
+        for i in (0..n).rev() {
+            if self.next_bit() {
+                bytes[i / 8] |= 1 << (i % 8);
+            }
+        }
+        bytes
+    }
+}
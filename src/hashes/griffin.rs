@@ -0,0 +1,288 @@
+use std::hint::black_box;
+
+use super::plonk::{self, PlonkishCost};
+use super::poseidon::pack_bytes;
+use super::shake::ShakeSampler;
+use super::{Domain, HashError, HashFunction, SetupPhase, UseCases, NON_STANDARD};
+use crate::circuits::{self, FieldNode, GriffinCircuit, R1csCost};
+use crate::fields::{self, NamedField};
+use crate::merkle::{self, MerkleCost, MerkleSettings};
+
+// Griffin-pi at width 3 with the paper's round count for d = 5 and 128-bit
+// security
+const WIDTH: usize = 3;
+const CAPACITY: usize = 1;
+const ROUNDS: usize = 12;
+
+struct Parameters<F> {
+    // The smallest d coprime to p - 1, and 1 / d modulo p - 1 as
+    // little-endian limbs for `pow_vartime`
+    d: u64,
+    d_inv: Vec<u64>,
+    // t per round, except the last round, which adds none
+    round_constants: Vec<Vec<F>>,
+    // (alpha_i, beta_i) for i = 2 .. t - 1
+    alpha_beta: Vec<(F, F)>,
+}
+
+impl<F: NamedField> Parameters<F> {
+    // `ShakeSampler` seeded with "Griffin": the round constants, then
+    // non-zero alpha and beta with alpha^2 - 4 beta a non-square, so that
+    // the quadratic in each y_i has no root. Wider states scale them as
+    // alpha_i = (i - 1) alpha and beta_i = (i - 1)^2 beta.
+    fn generate() -> Parameters<F> {
+        let (d, d_inv) = fields::invertible_power::<F>();
+        let mut sampler = ShakeSampler::<F>::new("Griffin");
+        let round_constants = (0..ROUNDS - 1)
+            .map(|_| (0..WIDTH).map(|_| sampler.next()).collect())
+            .collect();
+
+        let (alpha, beta) = loop {
+            let alpha = sampler.next_nonzero();
+            let beta = sampler.next_nonzero();
+            let discriminant = alpha.square() - beta.double().double();
+            if alpha != beta && bool::from(discriminant.sqrt().is_none()) {
+                break (alpha, beta);
+            }
+        };
+        let alpha_beta = (2..WIDTH as u64)
+            .map(|i| {
+                let scale = F::from(i - 1);
+                (alpha * scale, beta * scale.square())
+            })
+            .collect();
+
+        Parameters { d, d_inv, round_constants, alpha_beta }
+    }
+}
+
+// Griffin (Grassi, Hao, Rechberger, Schofnegger, Walch, Wang 2022) over a
+// SNARK scalar field. The nonlinear layer is a Horst-like construction:
+//
+//   y_0 = x_0^(1/d),  y_1 = x_1^d,
+//   y_i = x_i (L_i^2 + alpha_i L_i + beta_i)  for i >= 2,
+//
+// where L_i = (i - 1) y_0 + y_1 (+ x_(i-1) for i >= 3). The linear layer is
+// circ(2, 1, 1) at t = 3, applied once before the first round as well. Only
+// two elements get a power map, so a round costs 8 R1CS constraints
+// at t = 3.
+//
+// Bytes are packed as for Poseidon and absorbed with the same additive
+// sponge and 10* padding as Rescue-Prime. The digest is the first state
+// element. The constants are this tool's own, so the instance is marked
+// non-standard.
+pub struct Griffin<F: NamedField> {
+    name: &'static str,
+    parameters: Parameters<F>,
+}
+
+impl<F: NamedField> Griffin<F> {
+    pub fn new() -> Self {
+        Griffin {
+            name: Box::leak(format!("Griffin-{}{}", F::CURVE, NON_STANDARD).into_boxed_str()),
+            parameters: Parameters::generate(),
+        }
+    }
+
+    fn rate() -> usize {
+        WIDTH - CAPACITY
+    }
+
+    fn linear_layer(state: &mut [F]) {
+        let sum: F = state.iter().sum();
+        state.iter_mut().for_each(|x| *x += sum);
+    }
+
+    fn nonlinear_layer(&self, state: &mut [F]) {
+        let parameters = &self.parameters;
+        let x = state.to_vec();
+        state[0] = x[0].pow_vartime(&parameters.d_inv);
+        state[1] = x[1].pow_vartime([parameters.d]);
+        for (i, (alpha, beta)) in (2..WIDTH).zip(&parameters.alpha_beta) {
+            let mut l = F::from(i as u64 - 1) * state[0] + state[1];
+            if i > 2 {
+                l += x[i - 1];
+            }
+            state[i] = x[i] * (l.square() + *alpha * l + beta);
+        }
+    }
+
+    fn permute(&self, state: &mut [F]) {
+        Self::linear_layer(state);
+        for round in 0..ROUNDS {
+            self.nonlinear_layer(state);
+            Self::linear_layer(state);
+            if let Some(constants) = self.parameters.round_constants.get(round) {
+                state.iter_mut().zip(constants).for_each(|(x, c)| *x += c);
+            }
+        }
+    }
+
+    // The sponge's own 10* padding to a multiple of the rate
+    fn pad(mut elements: Vec<F>) -> Vec<F> {
+        elements.push(F::ONE);
+        elements.resize(elements.len().div_ceil(Self::rate()) * Self::rate(), F::ZERO);
+        elements
+    }
+
+    // Packed elements, padded
+    fn absorbed_elements(data: &[u8]) -> Result<Vec<F>, HashError> {
+        Ok(Self::pad(pack_bytes::<F>(data)?))
+    }
+
+    // Absorbs padded elements, returning the first state element
+    fn sponge(&self, elements: &[F]) -> F {
+        let mut state = vec![F::ZERO; WIDTH];
+        for block in elements.chunks(Self::rate()) {
+            state.iter_mut().zip(block).for_each(|(x, m)| *x += m);
+            self.permute(&mut state);
+        }
+        state[0]
+    }
+
+    // The gadget over `elements`, already padded
+    fn circuit(&self, elements: Vec<F>) -> GriffinCircuit<'_, F> {
+        let parameters = &self.parameters;
+        GriffinCircuit {
+            rate: Self::rate(),
+            d: parameters.d,
+            d_inv: &parameters.d_inv,
+            round_constants: &parameters.round_constants,
+            alpha_beta: &parameters.alpha_beta,
+            elements,
+        }
+    }
+}
+
+impl<F: NamedField> HashFunction for Griffin<F> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn output_size(&self) -> usize {
+        fields::repr_len::<F>()
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Field(F::NAME)
+    }
+
+    fn security_bits(&self) -> u32 {
+        128
+    }
+
+    fn parameters(&self) -> Option<String> {
+        Some(format!(
+            "t={}, rate {}, x^{} and x^(1/{}), {} rounds, non-standard constants",
+            WIDTH,
+            Self::rate(),
+            self.parameters.d,
+            self.parameters.d,
+            ROUNDS
+        ))
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        Ok(fields::to_le_bytes(&self.sponge(&Self::absorbed_elements(data)?)))
+    }
+
+    fn field_elements(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        Ok(Some(pack_bytes::<F>(data)?.len()))
+    }
+
+    fn setup_phases(&self) -> Vec<SetupPhase> {
+        vec![SetupPhase {
+            name: "SHAKE128 round constants",
+            run: Box::new(|| {
+                black_box(Parameters::<F>::generate());
+            }),
+        }]
+    }
+
+    // x^(1/5) checked forwards and x^5 at 3 constraints each, and 2 for each
+    // further element (L^2 and the product)
+    fn snark_constraints(&self) -> Option<usize> {
+        Some(ROUNDS * (3 + 3 + 2 * (WIDTH - 2)))
+    }
+
+    fn snark_constraints_computed(&self) -> bool {
+        true
+    }
+
+    fn r1cs_cost(&self, data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        circuits::measure(self.circuit(Self::absorbed_elements(data)?))
+            .map(Some)
+            .map_err(|e| HashError::Synthesis(e.to_string()))
+    }
+
+    // A node absorbs one element per child, padded as any input
+    fn merkle(&self, data: &[u8], settings: &MerkleSettings) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        let node = |children: &[Vec<u8>]| -> Result<Vec<u8>, HashError> {
+            Ok(fields::to_le_bytes(&self.sponge(&Self::pad(merkle::elements::<F>(children)?))))
+        };
+        let gadget = FieldNode(self.circuit(Vec::new()));
+        merkle::measure::<F, _>(&gadget, |data| self.hash(data), node, data, settings).map(Some)
+    }
+
+    // Two power maps, then for each further element L (one or two
+    // additions), L^2 + alpha L + beta in one gate and the product; the
+    // linear layer is the state sum plus one addition per element
+    fn plonk_gates(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        let powers = 2 * plonk::power_gates(self.parameters.d);
+        let horst: usize = (2..WIDTH).map(|i| plonk::sum_gates(if i == 2 { 2 } else { 3 }) + 2).sum();
+        let linear_layer = plonk::sum_gates(WIDTH) + WIDTH;
+        let per_permutation = linear_layer + ROUNDS * (powers + horst + linear_layer);
+        let elements = Self::absorbed_elements(data)?.len();
+        let permutations = elements / Self::rate();
+        Ok(Some(permutations * per_permutation + plonk::absorb_gates(elements, Self::rate())))
+    }
+
+    // One row per round plus the first linear layer's. The first two
+    // outputs get columns, x^(1/d) as a witness checked forwards and x^d so
+    // the Horst products stay at degree 3.
+    fn plonkish_cost(&self, data: &[u8]) -> Result<Option<PlonkishCost>, HashError> {
+        let permutations = Self::absorbed_elements(data)?.len() / Self::rate();
+        Ok(Some(PlonkishCost {
+            advice_columns: WIDTH + 2,
+            rows: plonk::sponge_rows(permutations, 1 + ROUNDS, true),
+            lookup_tables: 0,
+            table_rows: 0,
+            degree: plonk::gate_degree(self.parameters.d.max(3)),
+        }))
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "R1CS and Plonkish circuits: two power maps per round",
+                "Wide states, where the cost per element stays flat",
+            ],
+            bad_for: &["Slow natively: x^(1/d) every round"],
+            ethereum_use: "zk research",
+            best_for: "Low-constraint ZK",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Griffin;
+    use crate::circuits::{self, FieldNode};
+    use crate::fields;
+    use crate::hashes::poseidon::pack_bytes;
+    use crate::hashes::HashFunction;
+
+    // The packed elements, hashed as one node, give the byte hash's digest:
+    // both pad in the sponge. Lengths cover one, two and several permutations.
+    #[test]
+    fn gadget_matches_native() {
+        let hash = Griffin::<blstrs::Scalar>::new();
+        let gadget = FieldNode(hash.circuit(Vec::new()));
+        for len in [0, 31, 62, 200] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let elements: Vec<Vec<u8>> = pack_bytes::<blstrs::Scalar>(&data).unwrap().iter().map(fields::to_le_bytes).collect();
+            let context = format!("{}, {} bytes", hash.name(), len);
+            circuits::assert_node_digest(&gadget, &elements, &hash.hash(&data).unwrap(), &context);
+        }
+    }
+}
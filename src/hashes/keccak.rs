@@ -0,0 +1,99 @@
+use bellpepper::gadgets::multipack;
+use blstrs::Scalar as Fr;
+use tiny_keccak::{Hasher, Keccak};
+
+use super::air::{self, AirCost};
+use super::{Domain, HashError, HashFunction, KnownAnswer, UseCases};
+use crate::circuits::{self, BitGadget, Keccak256Circuit, R1csCost};
+use crate::groth16::{self, Groth16Cost};
+use crate::merkle::{self, MerkleCost, MerkleSettings};
+
+pub struct Keccak256;
+
+impl HashFunction for Keccak256 {
+    fn name(&self) -> &'static str {
+        "Keccak-256"
+    }
+
+    fn output_size(&self) -> usize {
+        32
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Bytes
+    }
+
+    fn security_bits(&self) -> u32 {
+        128
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        let mut keccak = Keccak::v256();
+        let mut output = [0u8; 32];
+        keccak.update(data);
+        keccak.finalize(&mut output);
+        Ok(output.to_vec())
+    }
+
+    fn snark_constraints(&self) -> Option<usize> {
+        Some(150_000)
+    }
+
+    fn r1cs_cost(&self, data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        let circuit = Keccak256Circuit {
+            preimage: data.to_vec(),
+            delimiter: 0x01,
+            expected: self.hash(data)?,
+            public_digest: false,
+        };
+        circuits::measure::<Fr, _>(circuit)
+            .map(Some)
+            .map_err(|e| HashError::Synthesis(e.to_string()))
+    }
+
+    // Keccak-f in Plonky3's layout, one row per round
+    fn air_cost(&self, data: &[u8]) -> Result<Option<AirCost>, HashError> {
+        Ok(Some(air::keccak256(data)))
+    }
+
+    // The gadget's digest bits are little-endian within each byte
+    fn groth16(&self, data: &[u8], samples: usize) -> Result<Option<Groth16Cost>, HashError> {
+        let digest = self.hash(data)?;
+        let inputs = multipack::compute_multipacking(&multipack::bytes_to_bits_le(&digest));
+        let circuit = || Keccak256Circuit {
+            preimage: data.to_vec(),
+            delimiter: 0x01,
+            expected: digest.clone(),
+            public_digest: true,
+        };
+        groth16::prove(circuit, &inputs, samples).map(Some)
+    }
+
+    // A node hashes its children's digests concatenated
+    fn merkle(&self, data: &[u8], settings: &MerkleSettings) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        let node = |children: &[Vec<u8>]| self.hash(&children.concat());
+        let gadget = BitGadget::Keccak256 { delimiter: 0x01 };
+        merkle::measure::<Fr, _>(&gadget, |data| self.hash(data), node, data, settings).map(Some)
+    }
+
+    // Ethereum's empty-input hash (e.g. the code hash of an account without code)
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        vec![KnownAnswer {
+            name: "empty input",
+            expected: "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            actual: self.hash(b"").map(hex::encode).unwrap_or_default(),
+        }]
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "Ethereum smart contracts (native opcode)",
+                "Address generation and transaction hashing",
+            ],
+            bad_for: &["Very expensive in zkSNARKs"],
+            ethereum_use: "Native (EVM)",
+            best_for: "Smart contracts",
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use std::hint::black_box;
+
+use num_bigint::BigUint;
+use ff::Field;
+use tiny_keccak::{Hasher, Keccak};
+
+use super::plonk::{self, PlonkishCost};
+use super::poseidon::pack_bytes;
+use super::{Domain, HashError, HashFunction, KnownAnswer, SetupPhase, UseCases};
+use crate::circuits::{self, FieldNode, MimcSpongeCircuit, R1csCost};
+use crate::fields::{self, Bn254Fr, NamedField};
+use crate::merkle::{self, MerkleCost, MerkleSettings};
+
+// circomlib's MiMCSponge(nInputs, 220, nOutputs)
+const ROUNDS: usize = 220;
+const SEED: &str = "mimcsponge";
+
+// circomlib mimcsponge.js: c = keccak256(SEED), then each constant is the
+// next keccak256(c) reduced modulo p; the first and last round constants are
+// zero
+fn round_constants() -> Vec<Bn254Fr> {
+    let keccak = |data: &[u8]| {
+        let mut hasher = Keccak::v256();
+        hasher.update(data);
+        let mut out = [0u8; 32];
+        hasher.finalize(&mut out);
+        out
+    };
+    let mut c = keccak(SEED.as_bytes());
+    let mut constants = vec![Bn254Fr::from(0)];
+    for _ in 1..ROUNDS - 1 {
+        c = keccak(&c);
+        constants.push(fields::from_biguint(&BigUint::from_bytes_be(&c)));
+    }
+    constants.push(Bn254Fr::from(0));
+    constants
+}
+
+// MiMC-Feistel with x^5, as circomlib's MiMCSponge and Tornado Cash use it.
+// Round i sets t = xL + k + c_i and (xL, xR) = (xR + t^5, xL); the last round
+// does not swap.
+//
+// Over bytes: pack as for Poseidon (31-byte little-endian chunks after 10*
+// padding), then circomlib's `multiHash` with key 0 and one output: for each
+// element, R += element and (R, C) = MiMCFeistel(R, C, 0). The digest is R,
+// little-endian. Digests of whole field-element inputs match circomlib.
+pub struct MimcSponge {
+    constants: Vec<Bn254Fr>,
+}
+
+impl MimcSponge {
+    pub fn new() -> Self {
+        MimcSponge { constants: round_constants() }
+    }
+
+    fn feistel(&self, mut left: Bn254Fr, mut right: Bn254Fr, key: Bn254Fr) -> (Bn254Fr, Bn254Fr) {
+        for (i, c) in self.constants.iter().enumerate() {
+            let t = left + key + c;
+            let t2 = t.square();
+            let t5 = t2.square() * t;
+            if i < ROUNDS - 1 {
+                (left, right) = (right + t5, left);
+            } else {
+                right += t5;
+            }
+        }
+        (left, right)
+    }
+
+    fn multi_hash(&self, elements: &[Bn254Fr]) -> Bn254Fr {
+        let (mut r, mut c) = (Bn254Fr::from(0), Bn254Fr::from(0));
+        for element in elements {
+            (r, c) = self.feistel(r + element, c, Bn254Fr::from(0));
+        }
+        r
+    }
+
+    fn hex(x: &Bn254Fr) -> String {
+        let digits: String = fields::to_le_bytes(x).iter().rev().map(|b| format!("{:02x}", b)).collect();
+        format!("0x{}", digits)
+    }
+}
+
+impl HashFunction for MimcSponge {
+    fn name(&self) -> &'static str {
+        "MiMCSponge-BN254"
+    }
+
+    fn output_size(&self) -> usize {
+        fields::repr_len::<Bn254Fr>()
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Field(<Bn254Fr as NamedField>::NAME)
+    }
+
+    // Capacity of one 254-bit element, as for Poseidon at arity 2
+    fn security_bits(&self) -> u32 {
+        128
+    }
+
+    fn parameters(&self) -> Option<String> {
+        Some(format!("Feistel, rate 1, x^5, {} rounds", ROUNDS))
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        let elements = pack_bytes::<Bn254Fr>(data)?;
+        Ok(fields::to_le_bytes(&self.multi_hash(&elements)))
+    }
+
+    fn field_elements(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        Ok(Some(pack_bytes::<Bn254Fr>(data)?.len()))
+    }
+
+    fn setup_phases(&self) -> Vec<SetupPhase> {
+        vec![SetupPhase {
+            name: "keccak256 constants chain",
+            run: Box::new(|| {
+                black_box(round_constants());
+            }),
+        }]
+    }
+
+    // Computed from the round structure, 3 constraints per round: t^2, t^4
+    // and the output
+    fn snark_constraints(&self) -> Option<usize> {
+        Some(3 * ROUNDS)
+    }
+
+    fn snark_constraints_computed(&self) -> bool {
+        true
+    }
+
+    fn r1cs_cost(&self, data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        let circuit = MimcSpongeCircuit {
+            constants: &self.constants,
+            elements: pack_bytes::<Bn254Fr>(data)?,
+        };
+        circuits::measure(circuit)
+            .map(Some)
+            .map_err(|e| HashError::Synthesis(e.to_string()))
+    }
+
+    // A node is circomlib's `multiHash` of its children, as in Tornado
+    // Cash's tree (there over MiMCSponge(2, 220, 1), one output)
+    fn merkle(&self, data: &[u8], settings: &MerkleSettings) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        let node = |children: &[Vec<u8>]| -> Result<Vec<u8>, HashError> {
+            Ok(fields::to_le_bytes(&self.multi_hash(&merkle::elements::<Bn254Fr>(children)?)))
+        };
+        let gadget = FieldNode(MimcSpongeCircuit {
+            constants: &self.constants,
+            elements: Vec::new(),
+        });
+        merkle::measure::<Bn254Fr, _>(&gadget, |data| self.hash(data), node, data, settings).map(Some)
+    }
+
+    // t^5 and its addition to the other half each round; R += element is
+    // the only absorption cost
+    fn plonk_gates(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        let elements = pack_bytes::<Bn254Fr>(data)?.len();
+        let per_element = ROUNDS * (plonk::power_gates(5) + 1);
+        Ok(Some(elements * per_element + plonk::absorb_gates(elements, 1)))
+    }
+
+    // One row per Feistel round over (L, R), one permutation per element
+    fn plonkish_cost(&self, data: &[u8]) -> Result<Option<PlonkishCost>, HashError> {
+        let elements = pack_bytes::<Bn254Fr>(data)?.len();
+        Ok(Some(PlonkishCost {
+            advice_columns: 2,
+            rows: plonk::sponge_rows(elements, ROUNDS, true),
+            lookup_tables: 0,
+            table_rows: 0,
+            degree: plonk::gate_degree(5),
+        }))
+    }
+
+    // circomlib's MiMCSponge constant c[1] (mimcsponge.circom) and its
+    // sponge test vector for multiHash([1, 2], key 0, 1 output)
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        let digest = self.multi_hash(&[Bn254Fr::from(1), Bn254Fr::from(2)]);
+        vec![
+            KnownAnswer {
+                name: "round constant c[1]",
+                expected: "0x0fbe43c36a80e36d7c7c584d4f8f3759fb51f0d66065d8a227b688d12488c5d4",
+                actual: Self::hex(&self.constants[1]),
+            },
+            KnownAnswer {
+                name: "multiHash([1, 2], k=0)",
+                expected: "0x2bcea035a1251603f1ceaf73cd4ae89427c47075bb8e3a944039ff1e3d6d2a6f",
+                actual: Self::hex(&digest),
+            },
+        ]
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "Existing circom circuits (Tornado Cash-era Merkle trees)",
+                "Compatibility with deployed MiMC commitments",
+            ],
+            bad_for: &["Several times the constraints of Poseidon for new circuits"],
+            ethereum_use: "Tornado Cash (legacy)",
+            best_for: "Legacy circom",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MimcSponge;
+    use crate::circuits::{self, FieldNode, MimcSpongeCircuit};
+    use crate::fields::{self, Bn254Fr};
+    use crate::hashes::poseidon::pack_bytes;
+    use crate::hashes::HashFunction;
+
+    // `multiHash` of the packed elements is the byte hash; lengths cover one
+    // and several elements
+    #[test]
+    fn gadget_matches_native() {
+        let hash = MimcSponge::new();
+        let gadget = FieldNode(MimcSpongeCircuit {
+            constants: &hash.constants,
+            elements: Vec::new(),
+        });
+        for len in [0, 31, 62, 100] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let elements: Vec<Vec<u8>> = pack_bytes::<Bn254Fr>(&data).unwrap().iter().map(fields::to_le_bytes).collect();
+            let context = format!("{}, {} bytes", hash.name(), len);
+            circuits::assert_node_digest(&gadget, &elements, &hash.hash(&data).unwrap(), &context);
+        }
+    }
+}
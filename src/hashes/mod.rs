@@ -0,0 +1,367 @@
+mod air;
+mod anemoi;
+mod blake2s;
+mod blake3;
+mod error;
+mod gmimc;
+mod grain;
+mod griffin;
+mod keccak;
+mod mimc;
+mod monolith;
+mod pedersen;
+mod plonk;
+mod poseidon;
+mod poseidon2;
+mod poseidon_small;
+mod reinforced_concrete;
+mod rescue;
+mod sha256;
+mod sha3;
+mod sha512;
+mod shake;
+mod tip5;
+
+pub use air::AirCost;
+pub use anemoi::Anemoi;
+pub use blake2s::Blake2s;
+pub use blake3::Blake3;
+pub use error::HashError;
+pub use gmimc::Gmimc;
+pub use griffin::Griffin;
+pub use keccak::Keccak256;
+pub use mimc::MimcSponge;
+pub use monolith::Monolith;
+pub use pedersen::Pedersen;
+pub use plonk::{LookupCost, PlonkishCost};
+pub use poseidon::{Poseidon, PoseidonField, SUPPORTED_ARITIES};
+pub use poseidon2::Poseidon2;
+pub use poseidon_small::SmallPoseidon;
+pub use reinforced_concrete::ReinforcedConcrete;
+pub use rescue::RescuePrime;
+pub use sha256::Sha256;
+pub use sha3::Sha3_256;
+pub use sha512::Sha512;
+pub use tip5::Tip5;
+
+use crate::chain::{ChainCost, ChainSettings};
+use crate::circuits::R1csCost;
+use crate::fields::{BabyBear, Bn254Fr, Goldilocks, Mersenne31, NamedField};
+use crate::groth16::Groth16Cost;
+use crate::merkle::{MerkleCost, MerkleSettings};
+
+// Native input domain of a hash function
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Domain {
+    Bytes,
+    Field(&'static str),
+}
+
+impl std::fmt::Display for Domain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Domain::Bytes => write!(f, "bytes"),
+            Domain::Field(field) => write!(f, "{}", field),
+        }
+    }
+}
+
+// Ends the name of an instance whose constants are not its reference
+// implementation's, or could not be checked against it, so its digests may
+// not match the reference
+pub const NON_STANDARD: &str = "*";
+
+// Human-facing guidance shown in the use-case section and summary table
+pub struct UseCases {
+    pub good_for: &'static [&'static str],
+    pub bad_for: &'static [&'static str],
+    pub ethereum_use: &'static str,
+    pub best_for: &'static str,
+}
+
+// A one-off setup step (e.g. constant generation), timed separately from per-hash cost
+pub struct SetupPhase {
+    pub name: &'static str,
+    pub run: Box<dyn Fn()>,
+}
+
+// A reference value checked by the `verify` subcommand
+pub struct KnownAnswer {
+    pub name: &'static str,
+    pub expected: &'static str,
+    pub actual: String,
+}
+
+impl KnownAnswer {
+    pub fn passed(&self) -> bool {
+        self.expected == self.actual
+    }
+}
+
+pub trait HashFunction {
+    fn name(&self) -> &'static str;
+    fn output_size(&self) -> usize;
+    fn domain(&self) -> Domain;
+    fn security_bits(&self) -> u32;
+
+    // Variant parameters worth showing next to the name, e.g. an arity
+    fn parameters(&self) -> Option<String> {
+        None
+    }
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError>;
+
+    // How many field elements `data` is encoded into; None for byte-oriented hashes
+    fn field_elements(&self, _data: &[u8]) -> Result<Option<usize>, HashError> {
+        Ok(None)
+    }
+
+    // Field elements absorbed per permutation; None for hashes that are not
+    // sponges over field elements
+    fn rate(&self) -> Option<usize> {
+        None
+    }
+
+    // Setup work that `hash` does not repeat; empty for hashes without any
+    fn setup_phases(&self) -> Vec<SetupPhase> {
+        Vec::new()
+    }
+
+    // SNARK constraint estimate (from literature); None where the hash is
+    // not meant for a SNARK field
+    fn snark_constraints(&self) -> Option<usize>;
+
+    // Whether `snark_constraints` was counted by this repo rather than taken
+    // from a published figure
+    fn snark_constraints_computed(&self) -> bool {
+        false
+    }
+
+    // R1CS size of hashing `data`, measured by synthesizing the circuit;
+    // None for hashes without a gadget
+    fn r1cs_cost(&self, _data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        Ok(None)
+    }
+
+    // Estimated AIR trace for hashing `data` in a STARK (see `air.rs` for the
+    // layout); None for hashes without one
+    fn air_cost(&self, _data: &[u8]) -> Result<Option<AirCost>, HashError> {
+        Ok(None)
+    }
+
+    // Estimated gates of a vanilla PLONK circuit hashing `data` (see
+    // `plonk.rs` for the gate model); None for hashes without an estimate
+    fn plonk_gates(&self, _data: &[u8]) -> Result<Option<usize>, HashError> {
+        Ok(None)
+    }
+
+    // Gates and lookups of a PLONK circuit with a lookup argument hashing
+    // `data`, for hashes built around table lookups (see `LookupCost`); None
+    // for the rest, whose `plonk_gates` needs no tables
+    fn lookup_cost(&self, _data: &[u8]) -> Result<Option<LookupCost>, HashError> {
+        Ok(None)
+    }
+
+    // Advice columns, rows, lookup tables and gate degree of a Plonkish
+    // circuit hashing `data` (see `PlonkishCost`); None for hashes without
+    // a layout
+    fn plonkish_cost(&self, _data: &[u8]) -> Result<Option<PlonkishCost>, HashError> {
+        Ok(None)
+    }
+
+    // Groth16 over BLS12-381 proving knowledge of a preimage of `data`'s
+    // digest, the digest being public, with proving and verification timed
+    // `samples` times (see `groth16.rs`); None for hashes without a circuit
+    // over that field
+    fn groth16(&self, _data: &[u8], _samples: usize) -> Result<Option<Groth16Cost>, HashError> {
+        Ok(None)
+    }
+
+    // Membership of `data`'s digest in a Merkle tree at each depth in
+    // `settings`, with a node hashing its children's digests (see
+    // `merkle.rs`); None for hashes without a node gadget
+    fn merkle(&self, _data: &[u8], _settings: &MerkleSettings) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        Ok(None)
+    }
+
+    // A chain of iterated hashes from `data`'s digest, proved whole and
+    // folded with Nova (see `chain.rs`); None for hashes the chain benchmark
+    // does not cover
+    fn hash_chain(&self, _data: &[u8], _settings: &ChainSettings) -> Result<Option<ChainCost>, HashError> {
+        Ok(None)
+    }
+
+    // Reference vectors for `verify`; empty when none are available
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        Vec::new()
+    }
+
+    fn use_cases(&self) -> UseCases;
+}
+
+// Selects between variants of the registered hashes
+#[derive(Clone, Debug)]
+pub struct RegistryConfig {
+    pub poseidon_arity: usize,
+    // One Poseidon instance per field, side by side
+    pub poseidon_fields: Vec<PoseidonField>,
+}
+
+impl Default for RegistryConfig {
+    fn default() -> Self {
+        RegistryConfig {
+            poseidon_arity: 2,
+            poseidon_fields: vec![PoseidonField::Bn254, PoseidonField::Bls12_381],
+        }
+    }
+}
+
+// Every stage of the comparison iterates over this list, in this order
+pub fn registry(config: &RegistryConfig) -> Result<Vec<Box<dyn HashFunction>>, HashError> {
+    let mut hashes: Vec<Box<dyn HashFunction>> = vec![
+        Box::new(Sha256),
+        Box::new(Keccak256),
+        Box::new(Sha512),
+        Box::new(Sha3_256),
+        Box::new(Blake2s),
+        Box::new(Blake3),
+    ];
+    for &field in &config.poseidon_fields {
+        hashes.push(poseidon(config.poseidon_arity, field, false)?);
+    }
+    for &field in &config.poseidon_fields {
+        hashes.push(poseidon2(field));
+    }
+    for &field in &config.poseidon_fields {
+        hashes.push(rescue_prime(field));
+    }
+    for &field in &config.poseidon_fields {
+        hashes.push(gmimc(field));
+    }
+    // circomlib's constants, Reinforced Concrete's bucket sizes and Baby
+    // Jubjub are defined over BN254 only
+    if config.poseidon_fields.contains(&PoseidonField::Bn254) {
+        hashes.push(Box::new(MimcSponge::new()));
+        hashes.push(Box::new(ReinforcedConcrete::new()));
+        hashes.push(Box::new(Pedersen::<Bn254Fr>::new()));
+    }
+    // Instantiated over BLS12-381 only, the field of their reference instances
+    // and of Jubjub
+    if config.poseidon_fields.contains(&PoseidonField::Bls12_381) {
+        hashes.push(Box::new(Anemoi::<blstrs::Scalar>::new()));
+        hashes.push(Box::new(Griffin::<blstrs::Scalar>::new()));
+        hashes.push(Box::new(Pedersen::<blstrs::Scalar>::new()));
+    }
+    hashes.push(Box::new(SmallPoseidon::<Goldilocks>::new()));
+    hashes.push(Box::new(SmallPoseidon::<BabyBear>::new()));
+    hashes.push(Box::new(SmallPoseidon::<Mersenne31>::new()));
+    // No reference Poseidon2 instance over Mersenne31 to check against
+    hashes.push(Box::new(Poseidon2::<Goldilocks>::new()));
+    hashes.push(Box::new(Poseidon2::<BabyBear>::new()));
+    hashes.push(Box::new(Tip5::new()));
+    hashes.push(Box::new(Monolith::<Goldilocks>::new()));
+    hashes.push(Box::new(Monolith::<Mersenne31>::new()));
+    Ok(hashes)
+}
+
+// Poseidon at every supported arity, smallest first, for each field, each
+// named by its width
+pub fn poseidon_sweep(fields: &[PoseidonField]) -> Result<Vec<Box<dyn HashFunction>>, HashError> {
+    fields
+        .iter()
+        .flat_map(|&field| SUPPORTED_ARITIES.iter().map(move |&arity| poseidon(arity, field, true)))
+        .collect()
+}
+
+fn poseidon(arity: usize, field: PoseidonField, width_in_name: bool) -> Result<Box<dyn HashFunction>, HashError> {
+    match field {
+        PoseidonField::Bn254 => poseidon_over::<Bn254Fr>(arity, width_in_name),
+        PoseidonField::Bls12_381 => poseidon_over::<blstrs::Scalar>(arity, width_in_name),
+    }
+}
+
+// Poseidon2 has one fixed width (t = 3) per SNARK field
+fn poseidon2(field: PoseidonField) -> Box<dyn HashFunction> {
+    match field {
+        PoseidonField::Bn254 => Box::new(Poseidon2::<Bn254Fr>::new()),
+        PoseidonField::Bls12_381 => Box::new(Poseidon2::<blstrs::Scalar>::new()),
+    }
+}
+
+fn rescue_prime(field: PoseidonField) -> Box<dyn HashFunction> {
+    match field {
+        PoseidonField::Bn254 => Box::new(RescuePrime::<Bn254Fr>::new()),
+        PoseidonField::Bls12_381 => Box::new(RescuePrime::<blstrs::Scalar>::new()),
+    }
+}
+
+fn gmimc(field: PoseidonField) -> Box<dyn HashFunction> {
+    match field {
+        PoseidonField::Bn254 => Box::new(Gmimc::<Bn254Fr>::new()),
+        PoseidonField::Bls12_381 => Box::new(Gmimc::<blstrs::Scalar>::new()),
+    }
+}
+
+fn poseidon_over<F: NamedField>(arity: usize, width_in_name: bool) -> Result<Box<dyn HashFunction>, HashError> {
+    use typenum::{U11, U16, U2, U24, U36, U4, U8};
+
+    match arity {
+        2 => Ok(Box::new(Poseidon::<F, U2>::new(width_in_name))),
+        4 => Ok(Box::new(Poseidon::<F, U4>::new(width_in_name))),
+        8 => Ok(Box::new(Poseidon::<F, U8>::new(width_in_name))),
+        11 => Ok(Box::new(Poseidon::<F, U11>::new(width_in_name))),
+        16 => Ok(Box::new(Poseidon::<F, U16>::new(width_in_name))),
+        24 => Ok(Box::new(Poseidon::<F, U24>::new(width_in_name))),
+        36 => Ok(Box::new(Poseidon::<F, U36>::new(width_in_name))),
+        _ => Err(HashError::InvalidParameters(format!(
+            "unsupported Poseidon arity {} (supported: {:?})",
+            arity, SUPPORTED_ARITIES
+        ))),
+    }
+}
+
+// Case- and punctuation-insensitive name match, so "sha256" selects "SHA-256".
+// A query naming the name up to any '-' selects every variant under it, so
+// "poseidon" selects both "Poseidon-BN254" and "Poseidon-BLS12-381", and
+// "poseidon-bn254" every width of "Poseidon-BN254-t5" in a sweep.
+pub fn matches_name(hash: &dyn HashFunction, query: &str) -> bool {
+    fn normalize(s: &str) -> String {
+        s.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_ascii_lowercase()
+    }
+    let name = hash.name();
+    let query = normalize(query);
+    normalize(name) == query || name.match_indices('-').any(|(i, _)| normalize(&name[..i]) == query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{matches_name, poseidon_sweep, registry, PoseidonField, RegistryConfig};
+
+    #[test]
+    fn known_answers_pass() {
+        for hash in registry(&RegistryConfig::default()).unwrap() {
+            for answer in hash.known_answers() {
+                assert!(
+                    answer.passed(),
+                    "{} {}: expected {}, got {}",
+                    hash.name(),
+                    answer.name,
+                    answer.expected,
+                    answer.actual
+                );
+            }
+        }
+    }
+
+    // Every arity of a sweep has its own name, which selects it alone, while
+    // the field's name still selects them all
+    #[test]
+    fn sweep_names_select_each_arity() {
+        let sweep = poseidon_sweep(&[PoseidonField::Bn254]).unwrap();
+        let selected = |query: &str| sweep.iter().filter(|h| matches_name(h.as_ref(), query)).count();
+        assert_eq!(selected("poseidon-bn254-t5"), 1);
+        assert_eq!(selected("poseidon-bn254"), sweep.len());
+        for hash in &sweep {
+            assert_eq!(selected(hash.name()), 1, "{}", hash.name());
+        }
+    }
+}
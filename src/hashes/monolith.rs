@@ -0,0 +1,327 @@
+use std::hint::black_box;
+use std::marker::PhantomData;
+
+use super::air::{self, AirCost};
+use super::plonk::{self, LookupCost, PlonkishCost};
+use super::poseidon_small::{encode_digest, pack_bytes};
+use super::shake::SmallShakeSampler;
+use super::{Domain, HashError, HashFunction, KnownAnswer, SetupPhase, UseCases, NON_STANDARD};
+use crate::fields::{Goldilocks, Mersenne31, SmallPrime};
+
+// Monolith-64 and Monolith-31 both use 6 rounds; the last adds no constants
+const ROUNDS: usize = 6;
+
+// Standard Monolith instance over a word-sized field
+pub trait MonolithInstance: SmallPrime {
+    const WIDTH: usize;
+    const CAPACITY: usize;
+    // Elements that go through the lookup S-boxes
+    const BARS: usize;
+    // Digest length in field elements
+    const OUTPUT: usize;
+    // First row of the circulant Concrete matrix
+    const MDS_FIRST_ROW: &'static [u64];
+
+    // First two outputs of Concrete and of the permutation on (0, 1, .., t - 1),
+    // as "0x.., 0x.."
+    const KAT_CONCRETE: Option<&'static str>;
+    const KAT_PERMUTATION: Option<&'static str>;
+}
+
+// Monolith-64 at t = 12, as for Plonky2's Poseidon
+impl MonolithInstance for Goldilocks {
+    const WIDTH: usize = 12;
+    const CAPACITY: usize = 4;
+    const BARS: usize = 4;
+    const OUTPUT: usize = 4;
+    const MDS_FIRST_ROW: &'static [u64] = &[7, 23, 8, 26, 13, 10, 9, 7, 6, 22, 21, 8];
+    // No reference vectors available offline
+    const KAT_CONCRETE: Option<&'static str> = None;
+    const KAT_PERMUTATION: Option<&'static str> = None;
+}
+
+// Monolith-31 at t = 16, the Plonky3 instance; its matrix is Tip5's
+impl MonolithInstance for Mersenne31 {
+    const WIDTH: usize = 16;
+    const CAPACITY: usize = 8;
+    const BARS: usize = 8;
+    const OUTPUT: usize = 8;
+    const MDS_FIRST_ROW: &'static [u64] = &[
+        61402, 17845, 26798, 59689, 12021, 40901, 41351, 27521, 56951, 12034, 53865, 43244, 7454, 33823, 28750, 1108,
+    ];
+    const KAT_CONCRETE: Option<&'static str> = Some("0x34f41d, 0x3cb0b2");
+    const KAT_PERMUTATION: Option<&'static str> = Some("0x244efdff, 0x114aaee6");
+}
+
+// Limb widths of a Bar: bytes, with a narrower top limb for 31-bit fields
+fn limb_bits<F: SmallPrime>() -> Vec<u32> {
+    let bits = F::bits() as u32;
+    (0..bits.div_ceil(8)).map(|i| (bits - 8 * i).min(8)).collect()
+}
+
+// The chi-like S-boxes of the specification: on bytes
+// y ^ (!(y <<< 1) & (y <<< 2) & (y <<< 3)), and on the 7-bit top limb
+// y ^ (!(y <<< 1) & (y <<< 2)), each followed by a rotation by one. Both fix
+// 0 and the all-ones limb, so a Bar keeps its output below p.
+fn s_box(y: u8) -> u8 {
+    (y ^ !y.rotate_left(1) & y.rotate_left(2) & y.rotate_left(3)).rotate_left(1)
+}
+
+fn s_box_7(y: u8) -> u8 {
+    let rotate = |y: u8, n: u32| (y << n | y >> (7 - n)) & 0x7f;
+    rotate((y ^ !rotate(y, 1) & rotate(y, 2)) & 0x7f, 1)
+}
+
+struct Parameters {
+    // One table per limb width in `limb_bits`, indexed by limb
+    tables: Vec<Vec<u8>>,
+    // One row per round except the last
+    round_constants: Vec<Vec<u64>>,
+}
+
+impl Parameters {
+    // SHAKE128 of "Monolith", the width and round count, the modulus and the
+    // limb widths, as in the reference instances
+    fn generate<F: MonolithInstance>() -> Parameters {
+        let tables = limb_bits::<F>()
+            .into_iter()
+            .map(|bits| match bits {
+                8 => (0..=u8::MAX).map(s_box).collect(),
+                _ => (0..1 << bits).map(|y| s_box_7(y as u8)).collect(),
+            })
+            .collect();
+
+        let mut seed = b"Monolith".to_vec();
+        seed.extend_from_slice(&[F::WIDTH as u8, ROUNDS as u8]);
+        seed.extend_from_slice(&F::MODULUS.to_le_bytes()[..F::bits().div_ceil(8)]);
+        seed.extend(limb_bits::<F>().into_iter().map(|bits| bits as u8));
+        let mut sampler = SmallShakeSampler::<F>::new(&seed);
+        let round_constants = (0..ROUNDS - 1)
+            .map(|_| (0..F::WIDTH).map(|_| sampler.next()).collect())
+            .collect();
+
+        Parameters { tables, round_constants }
+    }
+}
+
+// Monolith (Grassi, Khovratovich, Lüftenegger, Rechberger, Schofnegger,
+// Walch 2023) over a word-sized STARK field. The permutation is Concrete,
+// then per round Bars, Bricks, Concrete and the round constants (none in the
+// last round):
+//
+// - Bars: the first BARS elements are split into limbs, each limb goes
+//   through its S-box table, and the limbs are recombined.
+// - Bricks: x_i += x_(i-1)^2 from the last element down, a Feistel-like
+//   square map.
+// - Concrete: the circulant matrix with MDS_FIRST_ROW as its first row.
+//
+// Bytes are packed and absorbed as for Poseidon over the same field
+// (overwrite mode); the digest is the first OUTPUT elements.
+pub struct Monolith<F: MonolithInstance> {
+    name: &'static str,
+    parameters: Parameters,
+    _field: PhantomData<F>,
+}
+
+impl<F: MonolithInstance> Monolith<F> {
+    pub fn new() -> Self {
+        Monolith {
+            name: Box::leak(format!("Monolith-{}{}", F::NAME, Self::marker()).into_boxed_str()),
+            parameters: Parameters::generate::<F>(),
+            _field: PhantomData,
+        }
+    }
+
+    fn rate() -> usize {
+        F::WIDTH - F::CAPACITY
+    }
+
+    // Instances without reference vectors are marked: their constants follow
+    // the specification's derivation but are unchecked
+    fn marker() -> &'static str {
+        if F::KAT_PERMUTATION.is_some() { "" } else { NON_STANDARD }
+    }
+
+    fn bar(&self, x: u64) -> u64 {
+        let mut out = 0;
+        let mut shift = 0;
+        for table in &self.parameters.tables {
+            let limb = (x >> shift) as usize & (table.len() - 1);
+            out |= (table[limb] as u64) << shift;
+            shift += table.len().trailing_zeros();
+        }
+        out
+    }
+
+    fn bricks(state: &mut [u64]) {
+        for i in (1..state.len()).rev() {
+            state[i] = F::add(state[i], F::mul(state[i - 1], state[i - 1]));
+        }
+    }
+
+    fn concrete(state: &mut [u64]) {
+        let input = state.to_vec();
+        for (i, out) in state.iter_mut().enumerate() {
+            *out = input.iter().enumerate().fold(0, |acc, (j, &x)| {
+                F::add(acc, F::mul(F::MDS_FIRST_ROW[(F::WIDTH + j - i) % F::WIDTH], x))
+            });
+        }
+    }
+
+    fn permute(&self, state: &mut [u64]) {
+        Self::concrete(state);
+        for round in 0..ROUNDS {
+            for x in &mut state[..F::BARS] {
+                *x = self.bar(*x);
+            }
+            Self::bricks(state);
+            Self::concrete(state);
+            if let Some(constants) = self.parameters.round_constants.get(round) {
+                state.iter_mut().zip(constants).for_each(|(x, &c)| *x = F::add(*x, c));
+            }
+        }
+    }
+
+    fn permutations(data: &[u8]) -> usize {
+        pack_bytes::<F>(data, Self::rate()).len() / Self::rate()
+    }
+}
+
+impl<F: MonolithInstance> HashFunction for Monolith<F> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn output_size(&self) -> usize {
+        F::OUTPUT * F::bits().div_ceil(8)
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Field(F::NAME)
+    }
+
+    // Half the capacity in bits, capped at the 128-bit design target
+    fn security_bits(&self) -> u32 {
+        (F::CAPACITY * F::bits() / 2).min(128) as u32
+    }
+
+    fn parameters(&self) -> Option<String> {
+        let unchecked = if F::KAT_PERMUTATION.is_some() { "" } else { ", unchecked constants" };
+        Some(format!("t={}, rate {}, {} bars, {} rounds{}", F::WIDTH, Self::rate(), F::BARS, ROUNDS, unchecked))
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        let mut state = vec![0; F::WIDTH];
+        for block in pack_bytes::<F>(data, Self::rate()).chunks(Self::rate()) {
+            state[..block.len()].copy_from_slice(block);
+            self.permute(&mut state);
+        }
+        Ok(encode_digest::<F>(&state[..F::OUTPUT]))
+    }
+
+    fn field_elements(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        Ok(Some(pack_bytes::<F>(data, Self::rate()).len()))
+    }
+
+    fn setup_phases(&self) -> Vec<SetupPhase> {
+        vec![SetupPhase {
+            name: "S-box tables and SHAKE128 constants",
+            run: Box::new(|| {
+                black_box(Parameters::generate::<F>());
+            }),
+        }]
+    }
+
+    // Not a SNARK-field hash: its arithmetic would have to be emulated in R1CS
+    fn snark_constraints(&self) -> Option<usize> {
+        None
+    }
+
+    // One row of t cells after each round's Bricks (degree 2), plus input and
+    // output cells for every limb of every Bar
+    fn air_cost(&self, data: &[u8]) -> Result<Option<AirCost>, HashError> {
+        let limbs = limb_bits::<F>().len();
+        let per_round = F::WIDTH + F::BARS * 2 * limbs;
+        let permutations = Self::permutations(data);
+        Ok(Some(AirCost {
+            width: F::WIDTH + ROUNDS * per_round,
+            rows: permutations,
+            degree: air::DEGREE,
+            absorbed_bytes: permutations * Self::rate() * ((F::bits() - 1) / 8),
+        }))
+    }
+
+    // Each Bar splits and rebuilds its element with one lookup per limb;
+    // Bricks is a square and an addition per element after the first, and
+    // Concrete is a dense matrix in the PLONK model
+    fn lookup_cost(&self, data: &[u8]) -> Result<Option<LookupCost>, HashError> {
+        let limbs = limb_bits::<F>().len();
+        let bars = F::BARS * 2 * plonk::decomposition_gates(limbs);
+        let bricks = 2 * (F::WIDTH - 1);
+        let concrete = plonk::dense_matrix_gates(F::WIDTH);
+        let permutations = Self::permutations(data);
+        Ok(Some(LookupCost {
+            gates: permutations * ((ROUNDS + 1) * concrete + ROUNDS * (bars + bricks)),
+            lookups: permutations * ROUNDS * F::BARS * limbs,
+            table_rows: {
+                // Limbs of the same width share a table
+                let mut widths = limb_bits::<F>();
+                widths.dedup();
+                widths.iter().map(|bits| 1 << bits).sum()
+            },
+        }))
+    }
+
+    // One row per round holding the state and every Bar's input and output
+    // limbs, plus the initial Concrete's row; Bricks squares, so degree 2.
+    // Limbs of the same width share a table.
+    fn plonkish_cost(&self, data: &[u8]) -> Result<Option<PlonkishCost>, HashError> {
+        let limbs = limb_bits::<F>();
+        let mut widths = limbs.clone();
+        widths.dedup();
+        Ok(Some(PlonkishCost {
+            advice_columns: F::WIDTH + F::BARS * 2 * limbs.len(),
+            rows: plonk::sponge_rows(Self::permutations(data), 1 + ROUNDS, false),
+            lookup_tables: widths.len(),
+            table_rows: self.lookup_cost(data)?.map_or(0, |cost| cost.table_rows),
+            degree: plonk::gate_degree(2),
+        }))
+    }
+
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        let first = |values: &[u64]| format!("{:#x}, {:#x}", values[0], values[1]);
+        let input: Vec<u64> = (0..F::WIDTH as u64).collect();
+        let mut answers = Vec::new();
+        if let Some(expected) = F::KAT_CONCRETE {
+            let mut state = input.clone();
+            Self::concrete(&mut state);
+            answers.push(KnownAnswer {
+                name: "concrete(0, 1, .., t-1) [0..2]",
+                expected,
+                actual: first(&state),
+            });
+        }
+        if let Some(expected) = F::KAT_PERMUTATION {
+            let mut state = input;
+            self.permute(&mut state);
+            answers.push(KnownAnswer {
+                name: "permutation(0, 1, .., t-1) [0..2]",
+                expected,
+                actual: first(&state),
+            });
+        }
+        answers
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "STARK provers with lookup arguments",
+                "Fast natively: table lookups instead of large power maps",
+            ],
+            bad_for: &["Circuits without lookups: Bars need bit decomposition"],
+            ethereum_use: "STARK research",
+            best_for: "Lookup-based STARKs",
+        }
+    }
+}
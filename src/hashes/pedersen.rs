@@ -0,0 +1,442 @@
+use std::cell::RefCell;
+use std::hint::black_box;
+
+use blake2s_simd::Params;
+use ff::{Field, PrimeField};
+use num_bigint::BigUint;
+
+use super::plonk;
+use super::{Domain, HashError, HashFunction, KnownAnswer, SetupPhase, UseCases, NON_STANDARD};
+use crate::circuits::{self, PedersenCircuit, R1csCost};
+use crate::fields::{self, Bn254Fr, NamedField};
+
+// Sapling's uniform random string, prefixed to every group hash input
+const URS: &[u8] = b"096b36a5804bfacef1691e173c366a47ff5ba84a44f26ddd7e8d9f79d5b42df0";
+
+// Both curves have cofactor 8
+const COFACTOR_BITS: usize = 3;
+
+// A twisted Edwards curve a x^2 + y^2 = 1 + d x^2 y^2 over a SNARK scalar
+// field, with the window layout of its reference Pedersen hash
+pub trait EmbeddedCurve: NamedField {
+    const CURVE_NAME: &'static str;
+    // Order of the prime-order subgroup, hex
+    const SUBGROUP_ORDER: &'static str;
+    // Each chunk is MAGNITUDE_BITS bits of magnitude and one sign bit
+    const MAGNITUDE_BITS: usize;
+    const CHUNKS_PER_SEGMENT: usize;
+    // BLAKE2s personalization of the generator group hash
+    const PERSONALIZATION: &'static [u8; 8];
+    // A point (x, y) from the curve's reference implementation, hex
+    const REFERENCE_POINT: (&'static str, &'static str);
+    const ETHEREUM_USE: &'static str;
+
+    // (a, d)
+    fn coefficients() -> (Self, Self);
+}
+
+// Jubjub, embedded in BLS12-381, with Sapling's 3-bit chunks and 63 chunks
+// per segment
+impl EmbeddedCurve for blstrs::Scalar {
+    const CURVE_NAME: &'static str = "Jubjub";
+    const SUBGROUP_ORDER: &'static str = "0e7db4ea6533afa906673b0101343b00a6682093ccc81082d0970e5ed6f72cb7";
+    const MAGNITUDE_BITS: usize = 2;
+    const CHUNKS_PER_SEGMENT: usize = 63;
+    const PERSONALIZATION: &'static [u8; 8] = b"Zcash_PH";
+    // The jubjub crate's full-group generator
+    const REFERENCE_POINT: (&'static str, &'static str) = (
+        "0x62edcbb8bf3787c88b0f03ddd60a8187caf55d1b29bf81afe4b3d35df1a7adfe",
+        "0x000000000000000000000000000000000000000000000000000000000000000b",
+    );
+    const ETHEREUM_USE: &'static str = "Zcash Sapling";
+
+    // a = -1, d = -10240 / 10241
+    fn coefficients() -> (Self, Self) {
+        let d = -Self::from(10240) * Self::from(10241).invert().unwrap();
+        (-Self::ONE, d)
+    }
+}
+
+// Baby Jubjub (EIP-2494), embedded in BN254, with circomlib's 4-bit windows
+// and 50 windows per segment. circomlib derives its generators with
+// BLAKE-256, which has no crate here, so they come from the Sapling group
+// hash under a personalization of our own and digests differ from
+// circomlib's.
+impl EmbeddedCurve for Bn254Fr {
+    const CURVE_NAME: &'static str = "Baby Jubjub";
+    const SUBGROUP_ORDER: &'static str = "060c89ce5c263405370a08b6d0302b0bab3eedb83920ee0a677297dc392126f1";
+    const MAGNITUDE_BITS: usize = 3;
+    const CHUNKS_PER_SEGMENT: usize = 50;
+    const PERSONALIZATION: &'static [u8; 8] = b"BabyJJPH";
+    // EIP-2494's generator of the full group
+    const REFERENCE_POINT: (&'static str, &'static str) = (
+        "0x023343e3445b673d38bcba38f25645adb494b1255b1162bb40f41a59f4d4b45e",
+        "0x0c19139cb84c680a6e14116da06056174a0cfa121e6e5c2450f87d64fc000001",
+    );
+    const ETHEREUM_USE: &'static str = "Tornado Cash commitments";
+
+    fn coefficients() -> (Self, Self) {
+        (Self::from(168700), Self::from(168696))
+    }
+}
+
+fn parse_hex<F: PrimeField>(hex: &str) -> F {
+    fields::from_biguint(&BigUint::parse_bytes(hex.trim_start_matches("0x").as_bytes(), 16).unwrap())
+}
+
+fn hex<F: PrimeField>(x: &F) -> String {
+    let digits: String = fields::to_le_bytes(x).iter().rev().map(|b| format!("{:02x}", b)).collect();
+    format!("0x{}", digits)
+}
+
+// Extended coordinates: x = X / Z, y = Y / Z, T = X Y / Z
+#[derive(Clone, Copy)]
+struct Point<F> {
+    x: F,
+    y: F,
+    t: F,
+    z: F,
+}
+
+impl<F: Field> Point<F> {
+    fn identity() -> Self {
+        Point { x: F::ZERO, y: F::ONE, t: F::ZERO, z: F::ONE }
+    }
+
+    fn from_affine(x: F, y: F) -> Self {
+        Point { x, y, t: x * y, z: F::ONE }
+    }
+
+    fn to_affine(self) -> (F, F) {
+        let z_inv = self.z.invert().unwrap();
+        (self.x * z_inv, self.y * z_inv)
+    }
+
+    fn negate(self) -> Self {
+        Point { x: -self.x, t: -self.t, ..self }
+    }
+
+    fn is_identity(&self) -> bool {
+        self.x.is_zero_vartime() && self.y == self.z
+    }
+}
+
+struct Curve<F> {
+    a: F,
+    d: F,
+}
+
+impl<F: PrimeField> Curve<F> {
+    // Hisil-Wong-Carter-Dawson unified addition; complete on both curves,
+    // where a is a square and d is not
+    fn add(&self, p: &Point<F>, q: &Point<F>) -> Point<F> {
+        let a = p.x * q.x;
+        let b = p.y * q.y;
+        let c = self.d * p.t * q.t;
+        let d = p.z * q.z;
+        let e = (p.x + p.y) * (q.x + q.y) - a - b;
+        let (f, g, h) = (d - c, d + c, b - self.a * a);
+        Point { x: e * f, y: g * h, t: e * h, z: f * g }
+    }
+
+    fn double_n(&self, p: &Point<F>, n: usize) -> Point<F> {
+        (0..n).fold(*p, |acc, _| self.add(&acc, &acc))
+    }
+
+    // Double-and-add, most significant bit first
+    fn mul(&self, p: &Point<F>, scalar: &BigUint) -> Point<F> {
+        let bytes = scalar.to_bytes_be();
+        let bits = bytes.iter().flat_map(|byte| (0..8).rev().map(move |i| byte >> i & 1 == 1));
+        bits.fold(Point::identity(), |acc, bit| {
+            let acc = self.add(&acc, &acc);
+            if bit { self.add(&acc, p) } else { acc }
+        })
+    }
+
+    // 32 bytes: y little-endian, with the parity of x in the top bit. None
+    // for a non-canonical y, an x^2 with no square root, or -0.
+    fn decompress(&self, bytes: &[u8]) -> Option<Point<F>> {
+        let mut y_bytes = bytes.to_vec();
+        let sign = y_bytes[31] >> 7;
+        y_bytes[31] &= 0x7f;
+        let y: F = fields::from_le_bytes(&y_bytes)?;
+        let y2 = y.square();
+        let x2 = (F::ONE - y2) * Option::<F>::from((self.a - self.d * y2).invert())?;
+        let x = Option::<F>::from(x2.sqrt())?;
+        if x.is_zero_vartime() && sign == 1 {
+            return None;
+        }
+        let x = if fields::to_le_bytes(&x)[0] & 1 == sign { x } else { -x };
+        Some(Point::from_affine(x, y))
+    }
+
+    // Montgomery B v^2 = u^3 + A u^2 + u with A = 2 (a + d) / (a - d) and
+    // B = 4 / (a - d)
+    fn montgomery_coefficients(&self) -> (F, F) {
+        let inv = (self.a - self.d).invert().unwrap();
+        (F::from(2) * (self.a + self.d) * inv, F::from(4) * inv)
+    }
+
+    // u = (1 + y) / (1 - y), v = u / x
+    fn to_montgomery(p: &Point<F>) -> (F, F) {
+        let (x, y) = p.to_affine();
+        let u = (F::ONE + y) * (F::ONE - y).invert().unwrap();
+        (u, u * x.invert().unwrap())
+    }
+}
+
+// Per chunk of a segment, the multiples 1 .. 2^m of its window base
+// 2^((m + 2) j) G
+type WindowTable<F> = Vec<Vec<Point<F>>>;
+
+// Sapling's FindGroupHash: the first BLAKE2s(URS || LE32(segment) || i)
+// that decompresses to a point whose cofactor multiple is not the identity
+fn generator<F: EmbeddedCurve>(curve: &Curve<F>, segment: u32) -> Point<F> {
+    (0..=u8::MAX)
+        .find_map(|i| {
+            let mut message = URS.to_vec();
+            message.extend_from_slice(&segment.to_le_bytes());
+            message.push(i);
+            let digest = Params::new().hash_length(32).personal(F::PERSONALIZATION).hash(&message);
+            let point = curve.double_n(&curve.decompress(digest.as_bytes())?, COFACTOR_BITS);
+            (!point.is_identity()).then_some(point)
+        })
+        .expect("a group hash succeeds within 256 tries")
+}
+
+fn window_table<F: EmbeddedCurve>(curve: &Curve<F>, segment: u32) -> WindowTable<F> {
+    let mut base = generator(curve, segment);
+    (0..F::CHUNKS_PER_SEGMENT)
+        .map(|_| {
+            let mut multiples = vec![base];
+            for k in 1..1 << F::MAGNITUDE_BITS {
+                multiples.push(curve.add(&multiples[k - 1], &base));
+            }
+            base = curve.double_n(&base, F::MAGNITUDE_BITS + 2);
+            multiples
+        })
+        .collect()
+}
+
+// Pedersen hash on an embedded twisted Edwards curve, in the style of
+// Sapling's PedersenHash. The input bits (least significant first within each
+// byte, then a 1 bit and zeros up to a whole chunk) are cut into chunks of m
+// magnitude bits and one sign bit. Chunk j of segment i adds
+//
+//   (1 - 2 s) (1 + magnitude) 2^((m + 2) j) G_i,
+//
+// so it is one table lookup and one curve addition natively. Each segment's
+// G_i comes from Sapling's group hash; G_i and the window tables are derived
+// on first use and cached, so only the first hash of a longer input pays for
+// its new segments. The digest is the x coordinate of the sum.
+//
+// The 1 bit keeps the empty input from summing to the identity, whose x
+// coordinate would be a fixed digest of zero.
+//
+// Over Jubjub the chunking and generators follow Sapling, but Sapling's
+// personalization prefix is omitted; over Baby Jubjub the generators come
+// from this repo's own personalization, not circomlib's. No digest vectors
+// were available for either, so the name ends in `NON_STANDARD`.
+pub struct Pedersen<F: EmbeddedCurve> {
+    name: &'static str,
+    curve: Curve<F>,
+    segments: RefCell<Vec<WindowTable<F>>>,
+}
+
+impl<F: EmbeddedCurve> Pedersen<F> {
+    pub fn new() -> Self {
+        let (a, d) = F::coefficients();
+        Pedersen {
+            name: Box::leak(format!("Pedersen-{}{}", F::CURVE, NON_STANDARD).into_boxed_str()),
+            curve: Curve { a, d },
+            segments: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn chunk_bits() -> usize {
+        F::MAGNITUDE_BITS + 1
+    }
+
+    fn bits(data: &[u8]) -> Vec<bool> {
+        let mut bits: Vec<bool> = data.iter().flat_map(|byte| (0..8).map(move |i| byte >> i & 1 == 1)).collect();
+        bits.push(true);
+        bits.resize(bits.len().div_ceil(Self::chunk_bits()) * Self::chunk_bits(), false);
+        bits
+    }
+
+    fn chunks(data: &[u8]) -> usize {
+        Self::bits(data).len() / Self::chunk_bits()
+    }
+
+    // Derives window tables until there are `count`
+    fn extend_segments(&self, count: usize) {
+        let mut segments = self.segments.borrow_mut();
+        while segments.len() < count {
+            let table = window_table(&self.curve, segments.len() as u32);
+            segments.push(table);
+        }
+    }
+}
+
+impl<F: EmbeddedCurve> HashFunction for Pedersen<F> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn output_size(&self) -> usize {
+        fields::repr_len::<F>()
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Field(F::NAME)
+    }
+
+    // Discrete logarithms in the prime-order subgroup, by Pollard's rho
+    fn security_bits(&self) -> u32 {
+        (BigUint::parse_bytes(F::SUBGROUP_ORDER.as_bytes(), 16).unwrap().bits() / 2) as u32
+    }
+
+    fn parameters(&self) -> Option<String> {
+        Some(format!(
+            "{}, {}-bit chunks, {} per segment, non-standard generators",
+            F::CURVE_NAME,
+            Self::chunk_bits(),
+            F::CHUNKS_PER_SEGMENT
+        ))
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        let m = F::MAGNITUDE_BITS;
+        let bits = Self::bits(data);
+        self.extend_segments(Self::chunks(data).div_ceil(F::CHUNKS_PER_SEGMENT));
+        let segments = self.segments.borrow();
+        let mut sum = Point::identity();
+        for (k, chunk) in bits.chunks(Self::chunk_bits()).enumerate() {
+            let magnitude = chunk[..m].iter().rev().fold(0, |acc, &bit| acc << 1 | bit as usize);
+            let point = segments[k / F::CHUNKS_PER_SEGMENT][k % F::CHUNKS_PER_SEGMENT][magnitude];
+            sum = self.curve.add(&sum, &if chunk[m] { point.negate() } else { point });
+        }
+        Ok(fields::to_le_bytes(&sum.to_affine().0))
+    }
+
+    fn setup_phases(&self) -> Vec<SetupPhase> {
+        vec![SetupPhase {
+            name: "group hash and window table, per segment",
+            run: Box::new(|| {
+                let (a, d) = F::coefficients();
+                black_box(window_table(&Curve { a, d }, 0));
+            }),
+        }]
+    }
+
+    // One full segment with the input bits taken as already boolean, as in
+    // Sapling's gadget: a signed lookup and a Montgomery addition per chunk
+    fn snark_constraints(&self) -> Option<usize> {
+        let lookup = (1 << F::MAGNITUDE_BITS) - F::MAGNITUDE_BITS - 1 + 1;
+        Some(F::CHUNKS_PER_SEGMENT * (lookup + 3))
+    }
+
+    // Counted from the gadget's structure above, not taken from Sapling's
+    // or circomlib's published figures
+    fn snark_constraints_computed(&self) -> bool {
+        true
+    }
+
+    fn r1cs_cost(&self, data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        let (montgomery_a, montgomery_b) = self.curve.montgomery_coefficients();
+        let segments = Self::chunks(data).div_ceil(F::CHUNKS_PER_SEGMENT);
+        self.extend_segments(segments);
+        let tables: Vec<Vec<Vec<(F, F)>>> = self.segments.borrow()[..segments]
+            .iter()
+            .map(|table| table.iter().map(|window| window.iter().map(Curve::to_montgomery).collect()).collect())
+            .collect();
+        let circuit = PedersenCircuit {
+            magnitude_bits: F::MAGNITUDE_BITS,
+            chunks_per_segment: F::CHUNKS_PER_SEGMENT,
+            a: self.curve.a,
+            d: self.curve.d,
+            montgomery_a,
+            montgomery_b,
+            tables: &tables,
+            bits: Self::bits(data),
+        };
+        circuits::measure(circuit)
+            .map(Some)
+            .map_err(|e| HashError::Synthesis(e.to_string()))
+    }
+
+    // Per chunk: a boolean check per bit, the lookup's monomials, one
+    // linear combination each for u and v over 2^m terms, and the
+    // negation. A Montgomery addition is two subtractions and a product for
+    // lambda, lambda^2 and two subtractions for u3, and a subtraction, a
+    // product and a subtraction for v3. A segment costs 2 gates back to
+    // Edwards form, and an Edwards addition 11: four products and the two
+    // sums they need, a sum and a product for x3, two sums and a product
+    // for y3.
+    fn plonk_gates(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        let m = F::MAGNITUDE_BITS;
+        let lookup = ((1 << m) - m - 1) + 2 * plonk::sum_gates(1 << m) + 1;
+        let chunk = Self::chunk_bits() + lookup;
+        let (chunks, segments) = (Self::chunks(data), Self::chunks(data).div_ceil(F::CHUNKS_PER_SEGMENT));
+        let montgomery_additions = chunks - segments;
+        let edwards_additions = segments.saturating_sub(1);
+        Ok(Some(chunks * chunk + 9 * montgomery_additions + 2 * segments + 11 * edwards_additions))
+    }
+
+    // The reference point decompressed from its y and the parity of its x,
+    // and the order of its cofactor multiple
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        let (x, y) = F::REFERENCE_POINT;
+        let mut encoding = fields::to_le_bytes(&parse_hex::<F>(y));
+        encoding[31] |= (fields::to_le_bytes(&parse_hex::<F>(x))[0] & 1) << 7;
+        let point = self.curve.decompress(&encoding);
+        let order = BigUint::parse_bytes(F::SUBGROUP_ORDER.as_bytes(), 16).unwrap();
+        let describe = |p: Point<F>| match p.is_identity() {
+            true => "identity".to_string(),
+            false => hex(&p.to_affine().0),
+        };
+        vec![
+            KnownAnswer {
+                name: "decompress(reference y) x",
+                expected: x,
+                actual: point.map(|p| hex(&p.to_affine().0)).unwrap_or_else(|| "not on the curve".to_string()),
+            },
+            KnownAnswer {
+                name: "[subgroup order] [8] reference point",
+                expected: "identity",
+                actual: point
+                    .map(|p| describe(self.curve.mul(&self.curve.double_n(&p, COFACTOR_BITS), &order)))
+                    .unwrap_or_else(|| "not on the curve".to_string()),
+            },
+        ]
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "Circuits over the curve's base field: a few constraints per input bit",
+                "Commitments: the hash is additively homomorphic",
+            ],
+            bad_for: &["Slow natively, and linear in its input: not a random oracle"],
+            ethereum_use: F::ETHEREUM_USE,
+            best_for: "Commitments in circuits",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pedersen;
+    use crate::fields::Bn254Fr;
+    use crate::hashes::HashFunction;
+
+    // The padding bit keeps the empty input off the identity's x = 0
+    #[test]
+    fn empty_input_is_not_the_identity() {
+        for hash in [&Pedersen::<Bn254Fr>::new() as &dyn HashFunction, &Pedersen::<blstrs::Scalar>::new()] {
+            let empty = hash.hash(&[]).unwrap();
+            assert_ne!(empty, vec![0; empty.len()], "{}", hash.name());
+            assert_ne!(empty, hash.hash(&[0]).unwrap(), "{}", hash.name());
+        }
+    }
+}
@@ -0,0 +1,78 @@
+// Gate counts for a vanilla PLONK arithmetization, where every gate is
+// q_L a + q_R b + q_O c + q_M ab + q_C = 0 over three wires. Scaling a wire
+// or adding a constant is a selector, so round constants and matrix entries
+// fold into the gates that use them; only additions and multiplications of
+// two wires cost a gate. Custom gates and wider rows (TurboPLONK, halo2) fit
+// several of these into one row, so these are upper bounds for such systems.
+
+use serde::Serialize;
+
+// Cost of a circuit that also uses a lookup argument (plookup, LogUp). Gates
+// follow the model above; each lookup is one row checked against a fixed
+// table, and the table's rows are paid once per circuit, not per hash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct LookupCost {
+    pub gates: usize,
+    pub lookups: usize,
+    pub table_rows: usize,
+}
+
+// x^alpha by square-and-multiply, one gate per squaring or multiplication
+pub fn power_gates(alpha: u64) -> usize {
+    (63 - alpha.leading_zeros() + alpha.count_ones() - 1) as usize
+}
+
+// A linear combination of `terms` wires, one addition gate per extra term
+pub fn sum_gates(terms: usize) -> usize {
+    terms.saturating_sub(1)
+}
+
+// A dense width x width matrix applied to the state
+pub fn dense_matrix_gates(width: usize) -> usize {
+    width * sum_gates(width)
+}
+
+// Additive sponge absorption: the first block lands on a known state, every
+// later element costs one addition
+pub fn absorb_gates(elements: usize, rate: usize) -> usize {
+    elements.saturating_sub(rate)
+}
+
+// A wire split into (or rebuilt from) `limbs` limbs: one linear combination
+// with constant weights. Each limb is range-checked by the table it is looked
+// up in.
+pub fn decomposition_gates(limbs: usize) -> usize {
+    sum_gates(limbs)
+}
+
+// Cost in a Plonkish layout (halo2, as used by Scroll and the PSE zkEVM):
+// a grid of advice columns and rows, custom gates relating one row to the
+// next, and lookups into fixed tables. A permutation keeps its state in one
+// row and each round is one gate to the next row, as halo2_gadgets lays out
+// Poseidon; intermediates the gate cannot express at its degree get extra
+// columns. A halo2 circuit pays for the next power of two above its rows
+// and its tables' rows, so rows, not gates, set the proving cost.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct PlonkishCost {
+    pub advice_columns: usize,
+    pub rows: usize,
+    pub lookup_tables: usize,
+    // Fixed table rows, which share the circuit's domain
+    pub table_rows: usize,
+    // Highest gate degree, the selector included as halo2 counts it
+    pub degree: usize,
+}
+
+// A gate enforcing a constraint of `degree`, behind its selector
+pub fn gate_degree(degree: u64) -> usize {
+    degree as usize + 1
+}
+
+// Rows of a sponge over `permutations` permutations: the input row, then
+// `rows_per_permutation` per permutation. An additive sponge adds each
+// later block to the state in one more row; an overwrite sponge copies the
+// block into the next permutation's input row for free.
+pub fn sponge_rows(permutations: usize, rows_per_permutation: usize, additive: bool) -> usize {
+    let absorb = if additive { permutations.saturating_sub(1) } else { 0 };
+    1 + permutations * rows_per_permutation + absorb
+}
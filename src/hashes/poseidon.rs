@@ -0,0 +1,369 @@
+use std::hint::black_box;
+
+use neptune::hash_type::HashType;
+use neptune::poseidon::{Arity, PoseidonConstants};
+use neptune::sponge::api::{IOPattern, SpongeAPI, SpongeOp};
+use neptune::sponge::vanilla::{Mode, Sponge};
+use neptune::Strength;
+
+use super::air::{self, AirCost};
+use super::plonk::{self, PlonkishCost};
+use super::{Domain, HashError, HashFunction, SetupPhase, UseCases};
+use crate::chain::{self, ChainCost, ChainSettings};
+use crate::circuits::{self, FieldNode, PoseidonCircuit, R1csCost};
+use crate::fields::{self, NamedField};
+use crate::groth16::Groth16Cost;
+use crate::merkle::{self, MerkleCost, MerkleSettings};
+use crate::nova;
+
+// Arities neptune ships optimized constants for
+pub const SUPPORTED_ARITIES: &[usize] = &[2, 4, 8, 11, 16, 24, 36];
+
+// The arity of the literature's figures and of the default instance, whose
+// name leaves out its width
+pub const DEFAULT_ARITY: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum PoseidonField {
+    #[value(name = "bn254")]
+    Bn254,
+    #[value(name = "bls12-381")]
+    Bls12_381,
+}
+
+// SAFE encodes absorb lengths in 31 bits of the IO pattern
+const MAX_ABSORB: usize = (1 << 31) - 1;
+
+// Variable-length Poseidon over bytes:
+//
+// 1. Pad the message with 0x01 followed by zero bytes up to a multiple of
+//    k = (NUM_BITS - 1) / 8 bytes (31 for BN254 and BLS12-381), so messages
+//    differing only in trailing zeros stay distinct.
+// 2. Read each k-byte chunk as a little-endian field element.
+// 3. Absorb all elements into a SAFE sponge (neptune's `SpongeAPI`) with IO
+//    pattern [Absorb(n), Squeeze(1)]; the pattern, and with it the element
+//    count, is bound into the initial capacity.
+// 4. Squeeze one element and return its canonical encoding, little-endian
+//    (32 bytes for BN254 and BLS12-381).
+//
+// Nothing above is specific to a field: any `ff::PrimeField` with a
+// `NamedField` impl works, and neptune derives the round constants from the
+// field itself. The field is part of the hash name.
+//
+// Constants are generated once and shared, so `hash` measures steady-state
+// cost only; setup is reported via `setup_phases`. The per-call sponge is
+// just `arity + 1` field elements of state.
+pub struct Poseidon<F: NamedField, A: Arity<F>> {
+    name: &'static str,
+    constants: &'static PoseidonConstants<F, A>,
+}
+
+impl<F: NamedField, A: Arity<F>> Poseidon<F, A> {
+    // Named by field, and by width t = arity + 1 when `width_in_name` is set
+    // or for any other arity than the default, e.g. "Poseidon-BN254-t5", so
+    // every arity of a sweep has its own name
+    pub fn new(width_in_name: bool) -> Self {
+        let width = match width_in_name || A::to_usize() != DEFAULT_ARITY {
+            true => format!("-t{}", A::to_usize() + 1),
+            false => String::new(),
+        };
+        // Leaked so sponges can borrow them for the rest of the run
+        let constants = PoseidonConstants::new_with_strength_and_type(Strength::Standard, HashType::Sponge);
+        Poseidon {
+            name: Box::leak(format!("Poseidon-{}{}", F::CURVE, width).into_boxed_str()),
+            constants: Box::leak(Box::new(constants)),
+        }
+    }
+
+    fn sponge(&self) -> Sponge<'static, F, A> {
+        // Scoped import: `SpongeTrait` and `SpongeAPI` both define absorb/squeeze
+        use neptune::sponge::vanilla::SpongeTrait;
+        Sponge::new_with_constants(self.constants, Mode::Simplex)
+    }
+
+    // Steps 3 and 4 over already-packed elements
+    fn hash_elements(&self, elements: &[F]) -> Result<F, HashError> {
+        if elements.len() > MAX_ABSORB {
+            return Err(HashError::TooManyInputs { max: MAX_ABSORB, given: elements.len() });
+        }
+        let length = elements.len() as u32;
+
+        let acc = &mut ();
+        let mut sponge = self.sponge();
+        sponge.start(IOPattern(vec![SpongeOp::Absorb(length), SpongeOp::Squeeze(1)]), None, acc);
+        sponge.absorb(length, elements, acc);
+        let digest = sponge.squeeze(1, acc)[0];
+        sponge
+            .finish(acc)
+            .map_err(|_| HashError::InvalidParameters("sponge IO pattern not completed".to_string()))?;
+        Ok(digest)
+    }
+
+    // A Merkle node over its children's digests, one element each
+    fn node(&self, children: &[Vec<u8>]) -> Result<Vec<u8>, HashError> {
+        Ok(fields::to_le_bytes(&self.hash_elements(&merkle::elements::<F>(children)?)?))
+    }
+
+    fn node_gadget(&self) -> FieldNode<PoseidonCircuit<'_, F, A>> {
+        FieldNode(PoseidonCircuit {
+            constants: self.constants,
+            elements: Vec::new(),
+            public_digest: false,
+        })
+    }
+}
+
+// Applies the 10* byte padding and packs the result into field elements
+pub fn pack_bytes<F: NamedField>(data: &[u8]) -> Result<Vec<F>, HashError> {
+    let chunk_len = fields::bytes_per_element::<F>();
+    if chunk_len == 0 {
+        return Err(HashError::InvalidParameters(format!("{} is too small to pack bytes into", F::NAME)));
+    }
+    let mut padded = data.to_vec();
+    padded.push(0x01);
+    padded.resize(padded.len().div_ceil(chunk_len) * chunk_len, 0);
+
+    padded
+        .chunks(chunk_len)
+        .map(|chunk| {
+            fields::from_le_bytes(chunk).ok_or_else(|| HashError::NonCanonicalEncoding {
+                field: F::NAME,
+                bytes: chunk.to_vec(),
+            })
+        })
+        .collect()
+}
+
+impl<F: NamedField, A: Arity<F> + Arity<nova::Scalar> + Sync> HashFunction for Poseidon<F, A> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn output_size(&self) -> usize {
+        fields::repr_len::<F>()
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Field(F::NAME)
+    }
+
+    fn security_bits(&self) -> u32 {
+        128
+    }
+
+    fn parameters(&self) -> Option<String> {
+        Some(format!("arity {}", A::to_usize()))
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        let digest = self.hash_elements(&pack_bytes::<F>(data)?)?;
+        Ok(fields::to_le_bytes(&digest))
+    }
+
+    fn field_elements(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        Ok(Some(pack_bytes::<F>(data)?.len()))
+    }
+
+    fn rate(&self) -> Option<usize> {
+        Some(A::to_usize())
+    }
+
+    fn setup_phases(&self) -> Vec<SetupPhase> {
+        // Raw parameters, reused to time neptune's preprocessing on its own:
+        // the sparse MDS factors and compressed round constants derived from
+        // an existing MDS matrix and round constants. The Grain LFSR and MDS
+        // generation are private to neptune, so they are only part of the total
+        let width = self.constants.width();
+        let mds = self.constants.mds_matrices.m.clone();
+        let round_constants = self.constants.round_constants.clone().unwrap_or_default();
+        let full_rounds = self.constants.full_rounds;
+        let partial_rounds = self.constants.partial_rounds;
+        let hash_type = self.constants.hash_type.clone();
+        let strength = self.constants.strength;
+
+        vec![
+            SetupPhase {
+                name: "constants generation (total)",
+                run: Box::new(|| {
+                    black_box(PoseidonConstants::<F, A>::new_with_strength_and_type(
+                        Strength::Standard,
+                        HashType::Sponge,
+                    ));
+                }),
+            },
+            SetupPhase {
+                name: "sparse MDS/constant compression",
+                run: Box::new(move || {
+                    black_box(PoseidonConstants::<F, A>::new_from_parameters(
+                        width,
+                        mds.clone(),
+                        round_constants.clone(),
+                        full_rounds,
+                        partial_rounds,
+                        hash_type.clone(),
+                        strength,
+                    ));
+                }),
+            },
+        ]
+    }
+
+    // The literature's ~100 at the default arity. Other widths have no
+    // published figure, so theirs is computed from the round numbers, 3
+    // constraints per x^5 S-box.
+    fn snark_constraints(&self) -> Option<usize> {
+        if A::to_usize() == DEFAULT_ARITY {
+            return Some(100);
+        }
+        let constants = self.constants;
+        Some(3 * (constants.full_rounds * constants.width() + constants.partial_rounds))
+    }
+
+    fn snark_constraints_computed(&self) -> bool {
+        A::to_usize() != DEFAULT_ARITY
+    }
+
+    fn r1cs_cost(&self, data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        let circuit = PoseidonCircuit {
+            constants: self.constants,
+            elements: pack_bytes::<F>(data)?,
+            public_digest: false,
+        };
+        circuits::measure(circuit)
+            .map(Some)
+            .map_err(|e| HashError::Synthesis(e.to_string()))
+    }
+
+    // The digest is one public element; fields without a pairing curve here
+    // return None
+    fn groth16(&self, data: &[u8], samples: usize) -> Result<Option<Groth16Cost>, HashError> {
+        let elements = pack_bytes::<F>(data)?;
+        let digest = self.hash(data)?;
+        let digest = fields::from_le_bytes::<F>(&digest).ok_or_else(|| HashError::NonCanonicalEncoding {
+            field: F::NAME,
+            bytes: digest.clone(),
+        })?;
+        let circuit = || PoseidonCircuit {
+            constants: self.constants,
+            elements: elements.clone(),
+            public_digest: true,
+        };
+        F::groth16(circuit, &[digest], samples)
+    }
+
+    // A node absorbs one element per child; the IO pattern binds the count
+    fn merkle(&self, data: &[u8], settings: &MerkleSettings) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        merkle::measure::<F, _>(&self.node_gadget(), |data| self.hash(data), |children| self.node(children), data, settings).map(Some)
+    }
+
+    // Each step hashes the previous state as a node with one child. Nova
+    // folds over BN254 only, with constants derived again for its own type
+    // of the same field.
+    fn hash_chain(&self, data: &[u8], settings: &ChainSettings) -> Result<Option<ChainCost>, HashError> {
+        let nova_constants = (fields::modulus::<F>() == fields::modulus::<nova::Scalar>()).then(|| {
+            PoseidonConstants::<nova::Scalar, A>::new_with_strength_and_type(Strength::Standard, HashType::Sponge)
+        });
+        let nova_gadget = nova_constants.as_ref().map(|constants| {
+            FieldNode(PoseidonCircuit {
+                constants,
+                elements: Vec::new(),
+                public_digest: false,
+            })
+        });
+        let node = |children: &[Vec<u8>]| self.node(children);
+        chain::measure::<F, _, _>(&self.node_gadget(), nova_gadget.as_ref(), |data| self.hash(data), node, data, settings).map(Some)
+    }
+
+    // One AIR row per permutation, as for the STARK-field Poseidons; the
+    // sparse partial-round matrices change nothing in the trace
+    fn air_cost(&self, data: &[u8]) -> Result<Option<AirCost>, HashError> {
+        let permutations = pack_bytes::<F>(data)?.len().div_ceil(A::to_usize()).max(1);
+        let constants = self.constants;
+        Ok(Some(AirCost {
+            width: air::spn_width(constants.width(), constants.full_rounds, constants.partial_rounds, 5),
+            rows: permutations,
+            degree: air::DEGREE,
+            absorbed_bytes: permutations * A::to_usize() * fields::bytes_per_element::<F>(),
+        }))
+    }
+
+    // Full rounds apply the dense MDS matrix. neptune applies partial rounds
+    // with sparse matrices: a dense first row, and one addition for each
+    // other element
+    fn plonk_gates(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        let width = self.constants.width();
+        let sbox = plonk::power_gates(5);
+        let full = width * sbox + plonk::dense_matrix_gates(width);
+        let partial = sbox + plonk::sum_gates(width) + (width - 1);
+        let per_permutation = self.constants.full_rounds * full + self.constants.partial_rounds * partial;
+        let elements = pack_bytes::<F>(data)?.len();
+        let permutations = elements.div_ceil(A::to_usize()).max(1);
+        Ok(Some(permutations * per_permutation + plonk::absorb_gates(elements, A::to_usize())))
+    }
+
+    // halo2_gadgets' Pow5 chip: the state plus one column for the S-box of
+    // the first of two partial rounds sharing a row
+    fn plonkish_cost(&self, data: &[u8]) -> Result<Option<PlonkishCost>, HashError> {
+        let rows = self.constants.full_rounds + self.constants.partial_rounds.div_ceil(2);
+        let permutations = pack_bytes::<F>(data)?.len().div_ceil(A::to_usize()).max(1);
+        Ok(Some(PlonkishCost {
+            advice_columns: self.constants.width() + 1,
+            rows: plonk::sponge_rows(permutations, rows, true),
+            lookup_tables: 0,
+            table_rows: 0,
+            degree: plonk::gate_degree(5),
+        }))
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "Zero-knowledge proof systems",
+                "Rollups and Layer 2 solutions",
+                "Privacy-preserving applications",
+            ],
+            bad_for: &["Not hardware-accelerated like SHA-256"],
+            ethereum_use: "zkApps/Rollups",
+            best_for: "Zero-knowledge",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use neptune::poseidon::Arity;
+    use typenum::{U2, U4};
+
+    use super::{pack_bytes, Poseidon};
+    use crate::circuits;
+    use crate::fields::{self, Bn254Fr, NamedField};
+    use crate::hashes::HashFunction;
+    use crate::nova;
+
+    // The packed elements, hashed as one node by `PoseidonCircuit`, give
+    // neptune's native digest of the bytes: the sponge's IO pattern binds the
+    // element count, so neither side pads. Lengths cover one, two and several
+    // permutations.
+    fn gadget_matches_native<F: NamedField, A: Arity<F> + Arity<nova::Scalar> + Sync>() {
+        let hash = Poseidon::<F, A>::new(false);
+        let gadget = hash.node_gadget();
+        for len in [0, 31, 62, 200] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let elements: Vec<Vec<u8>> = pack_bytes::<F>(&data).unwrap().iter().map(fields::to_le_bytes).collect();
+            let context = format!("{}, arity {}, {} bytes", hash.name(), A::to_usize(), len);
+            circuits::assert_node_digest(&gadget, &elements, &hash.hash(&data).unwrap(), &context);
+        }
+    }
+
+    #[test]
+    fn gadget_matches_native_bn254() {
+        gadget_matches_native::<Bn254Fr, U2>();
+        gadget_matches_native::<Bn254Fr, U4>();
+    }
+
+    #[test]
+    fn gadget_matches_native_bls12_381() {
+        gadget_matches_native::<blstrs::Scalar, U2>();
+        gadget_matches_native::<blstrs::Scalar, U4>();
+    }
+}
@@ -0,0 +1,738 @@
+use std::hint::black_box;
+use std::marker::PhantomData;
+
+use bellpepper_core::SynthesisError;
+
+use super::air::{self, AirCost};
+use super::grain::Grain;
+use super::plonk::{self, PlonkishCost};
+use super::{Domain, HashError, HashFunction, KnownAnswer, SetupPhase, UseCases};
+use crate::circuits::{self, FieldNode, Poseidon2Circuit, R1csCost};
+use crate::fields::{self, BabyBear, Bn254Fr, Goldilocks, NamedField, SmallPrime};
+use crate::merkle::{self, MerkleCost, MerkleSettings};
+
+// A Poseidon2 instance: the field's arithmetic plus the width, S-box and
+// round numbers of the HorizenLabs/zkhash reference instance over it
+pub trait Poseidon2Instance: 'static {
+    type Element: Copy + PartialEq;
+
+    // Hash name suffix, e.g. "BN254"
+    const LABEL: &'static str;
+    // Domain name, e.g. "BN254 Fr"
+    const FIELD: &'static str;
+    const WIDTH: usize;
+    const CAPACITY: usize;
+    const ALPHA: u64;
+    const FULL_ROUNDS: usize;
+    const PARTIAL_ROUNDS: usize;
+    // Digest length in field elements
+    const OUTPUT: usize;
+    // Same claim as the Poseidon instance over the field and capacity
+    const SECURITY_BITS: u32;
+    // Word-sized field proven in a STARK rather than a SNARK scalar field
+    const STARK_FIELD: bool;
+    // Internal matrix 1 + diag(d), stored as d (zkhash's MAT_DIAG_M_1). For
+    // t = 3 this is the fixed [[2, 1, 1], [1, 2, 1], [1, 1, 3]]; wider
+    // states use sampled diagonals
+    const INTERNAL_DIAGONAL: &'static [u64];
+
+    // Reference values from zkhash, as "0x.., 0x.."
+    const KAT_ROUND_CONSTANTS: &'static str;
+    // First two outputs of permuting (0, 1, .., t - 1)
+    const KAT_PERMUTATION: Option<&'static str>;
+
+    fn bits() -> usize;
+    fn from_u64(value: u64) -> Self::Element;
+    fn add(a: Self::Element, b: Self::Element) -> Self::Element;
+    fn mul(a: Self::Element, b: Self::Element) -> Self::Element;
+    // One round constant drawn from the Grain stream
+    fn sample(grain: &mut Grain) -> Self::Element;
+    // None if the value is not below the modulus
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self::Element>;
+    // ceil(bits / 8) bytes
+    fn to_le_bytes(element: &Self::Element) -> Vec<u8>;
+
+    // Synthesizes a hash over `elements`; None for fields without a gadget
+    fn r1cs_cost(
+        _round_constants: &[Vec<Self::Element>],
+        _internal_diagonal: &[Self::Element],
+        _elements: Vec<Self::Element>,
+    ) -> Option<Result<R1csCost, SynthesisError>> {
+        None
+    }
+
+    // Merkle membership of `data`'s digest, `hash` and `node` being the
+    // native hash and node hash; None for fields without a gadget
+    fn merkle(
+        _round_constants: &[Vec<Self::Element>],
+        _internal_diagonal: &[Self::Element],
+        _hash: &NativeHash,
+        _node: &NativeNode,
+        _data: &[u8],
+        _settings: &MerkleSettings,
+    ) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        Ok(None)
+    }
+}
+
+// The native hash and node hash `merkle` checks the gadget against
+type NativeHash<'a> = dyn Fn(&[u8]) -> Result<Vec<u8>, HashError> + 'a;
+type NativeNode<'a> = dyn Fn(&[Vec<u8>]) -> Result<Vec<u8>, HashError> + 'a;
+
+// Rejection-sampled like the word-sized fields, over NUM_BITS-bit strings
+fn sample_scalar<F: NamedField>(grain: &mut Grain) -> F {
+    loop {
+        if let Some(element) = fields::from_le_bytes(&grain.bytes(F::NUM_BITS as usize)) {
+            return element;
+        }
+    }
+}
+
+fn scalar_circuit<'a, F: NamedField, I: Poseidon2Instance<Element = F>>(
+    round_constants: &'a [Vec<F>],
+    internal_diagonal: &'a [F],
+    elements: Vec<F>,
+) -> Poseidon2Circuit<'a, F> {
+    Poseidon2Circuit {
+        rate: I::WIDTH - I::CAPACITY,
+        alpha: I::ALPHA,
+        full_rounds: I::FULL_ROUNDS,
+        output: I::OUTPUT,
+        round_constants,
+        internal_diagonal,
+        elements,
+    }
+}
+
+fn scalar_r1cs_cost<F: NamedField, I: Poseidon2Instance<Element = F>>(
+    round_constants: &[Vec<F>],
+    internal_diagonal: &[F],
+    elements: Vec<F>,
+) -> Option<Result<R1csCost, SynthesisError>> {
+    Some(circuits::measure(scalar_circuit::<F, I>(round_constants, internal_diagonal, elements)))
+}
+
+fn scalar_merkle<F: NamedField, I: Poseidon2Instance<Element = F>>(
+    round_constants: &[Vec<F>],
+    internal_diagonal: &[F],
+    hash: &NativeHash,
+    node: &NativeNode,
+    data: &[u8],
+    settings: &MerkleSettings,
+) -> Result<Option<Vec<MerkleCost>>, HashError> {
+    let gadget = FieldNode(scalar_circuit::<F, I>(round_constants, internal_diagonal, Vec::new()));
+    merkle::measure::<F, _>(&gadget, hash, node, data, settings).map(Some)
+}
+
+impl Poseidon2Instance for Bn254Fr {
+    type Element = Bn254Fr;
+
+    const LABEL: &'static str = <Bn254Fr as NamedField>::CURVE;
+    const FIELD: &'static str = <Bn254Fr as NamedField>::NAME;
+    const WIDTH: usize = 3;
+    const CAPACITY: usize = 1;
+    const ALPHA: u64 = 5;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 56;
+    const OUTPUT: usize = 1;
+    const SECURITY_BITS: u32 = 128;
+    const STARK_FIELD: bool = false;
+    const INTERNAL_DIAGONAL: &'static [u64] = &[1, 1, 2];
+    const KAT_ROUND_CONSTANTS: &'static str = "0x1d066a255517b7fd8bddd3a93f7804ef7f8fcde48bb4c37a59a09a1a97052816, \
+         0x29daefb55f6f2dc6ac3f089cebcc6120b7c6fef31367b68eb7238547d32c1610";
+    const KAT_PERMUTATION: Option<&'static str> = Some(
+        "0x0bb61d24daca55eebcb1929a82650f328134334da98ea4f847f760054f4a3033, \
+         0x303b6f7c86d043bfcbcc80214f26a30277a15d3f74ca654992defe7ff8d03570",
+    );
+
+    fn bits() -> usize {
+        <Bn254Fr as ff::PrimeField>::NUM_BITS as usize
+    }
+
+    fn from_u64(value: u64) -> Self {
+        value.into()
+    }
+
+    fn add(a: Self, b: Self) -> Self {
+        a + b
+    }
+
+    fn mul(a: Self, b: Self) -> Self {
+        a * b
+    }
+
+    fn sample(grain: &mut Grain) -> Self {
+        sample_scalar(grain)
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        fields::from_le_bytes(bytes)
+    }
+
+    fn to_le_bytes(element: &Self) -> Vec<u8> {
+        fields::to_le_bytes(element)
+    }
+
+    fn r1cs_cost(
+        round_constants: &[Vec<Self>],
+        internal_diagonal: &[Self],
+        elements: Vec<Self>,
+    ) -> Option<Result<R1csCost, SynthesisError>> {
+        scalar_r1cs_cost::<Self, Self>(round_constants, internal_diagonal, elements)
+    }
+
+    fn merkle(
+        round_constants: &[Vec<Self>],
+        internal_diagonal: &[Self],
+        hash: &NativeHash,
+        node: &NativeNode,
+        data: &[u8],
+        settings: &MerkleSettings,
+    ) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        scalar_merkle::<Self, Self>(round_constants, internal_diagonal, hash, node, data, settings)
+    }
+}
+
+impl Poseidon2Instance for blstrs::Scalar {
+    type Element = blstrs::Scalar;
+
+    const LABEL: &'static str = <blstrs::Scalar as NamedField>::CURVE;
+    const FIELD: &'static str = <blstrs::Scalar as NamedField>::NAME;
+    const WIDTH: usize = 3;
+    const CAPACITY: usize = 1;
+    const ALPHA: u64 = 5;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 56;
+    const OUTPUT: usize = 1;
+    const SECURITY_BITS: u32 = 128;
+    const STARK_FIELD: bool = false;
+    const INTERNAL_DIAGONAL: &'static [u64] = &[1, 1, 2];
+    const KAT_ROUND_CONSTANTS: &'static str = "0x6f007a551156b3a449e44936b7c093644a0ed33f33eaccc628e942e836c1a875, \
+         0x360d7470611e473d353f628f76d110f34e71162f31003b7057538c2596426303";
+    const KAT_PERMUTATION: Option<&'static str> = Some(
+        "0x1b152349b1950b6a8ca75ee4407b6e26ca5cca5650534e56ef3fd45761fbf5f0, \
+         0x4c5793c87d51bdc2c08a32108437dc0000bd0275868f09ebc5f36919af5b3891",
+    );
+
+    fn bits() -> usize {
+        <blstrs::Scalar as ff::PrimeField>::NUM_BITS as usize
+    }
+
+    fn from_u64(value: u64) -> Self {
+        value.into()
+    }
+
+    fn add(a: Self, b: Self) -> Self {
+        a + b
+    }
+
+    fn mul(a: Self, b: Self) -> Self {
+        a * b
+    }
+
+    fn sample(grain: &mut Grain) -> Self {
+        sample_scalar(grain)
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        fields::from_le_bytes(bytes)
+    }
+
+    fn to_le_bytes(element: &Self) -> Vec<u8> {
+        fields::to_le_bytes(element)
+    }
+
+    fn r1cs_cost(
+        round_constants: &[Vec<Self>],
+        internal_diagonal: &[Self],
+        elements: Vec<Self>,
+    ) -> Option<Result<R1csCost, SynthesisError>> {
+        scalar_r1cs_cost::<Self, Self>(round_constants, internal_diagonal, elements)
+    }
+
+    fn merkle(
+        round_constants: &[Vec<Self>],
+        internal_diagonal: &[Self],
+        hash: &NativeHash,
+        node: &NativeNode,
+        data: &[u8],
+        settings: &MerkleSettings,
+    ) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        scalar_merkle::<Self, Self>(round_constants, internal_diagonal, hash, node, data, settings)
+    }
+}
+
+// Word-sized fields reuse the `SmallPrime` arithmetic
+fn small_from_le_bytes<F: SmallPrime>(bytes: &[u8]) -> Option<u64> {
+    let value = bytes.iter().rev().fold(0u128, |acc, &b| acc << 8 | b as u128);
+    (value < F::MODULUS as u128).then_some(value as u64)
+}
+
+fn small_to_le_bytes<F: SmallPrime>(element: &u64) -> Vec<u8> {
+    element.to_le_bytes()[..F::bits().div_ceil(8)].to_vec()
+}
+
+// Plonky2's width, as for `SmallPoseidon<Goldilocks>`
+impl Poseidon2Instance for Goldilocks {
+    type Element = u64;
+
+    const LABEL: &'static str = <Goldilocks as SmallPrime>::NAME;
+    const FIELD: &'static str = <Goldilocks as SmallPrime>::NAME;
+    const WIDTH: usize = 12;
+    const CAPACITY: usize = 4;
+    const ALPHA: u64 = 7;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 22;
+    const OUTPUT: usize = 4;
+    const SECURITY_BITS: u32 = 128;
+    const STARK_FIELD: bool = true;
+    const INTERNAL_DIAGONAL: &'static [u64] = &[
+        0xc3b6c08e23ba9300, 0xd84b5de94a324fb6, 0x0d0c371c5b35b84f, 0x7964f570e7188037,
+        0x5daf18bbd996604b, 0x6743bc47b9595257, 0x5528b9362c59bb70, 0xac45e25b7127b68b,
+        0xa2077d7dfbb606b5, 0xf3faac6faee378ae, 0x0c6388b51545e883, 0xd27dbb6944917b60,
+    ];
+    const KAT_ROUND_CONSTANTS: &'static str = "0x13dcf33aba214f46, 0x30b3b654a1da6d83";
+    const KAT_PERMUTATION: Option<&'static str> = Some("0x01eaef96bdf1c0c1, 0x1f0d2cc525b2540c");
+
+    fn bits() -> usize {
+        <Goldilocks as SmallPrime>::bits()
+    }
+
+    fn from_u64(value: u64) -> u64 {
+        value % <Goldilocks as SmallPrime>::MODULUS
+    }
+
+    fn add(a: u64, b: u64) -> u64 {
+        <Goldilocks as SmallPrime>::add(a, b)
+    }
+
+    fn mul(a: u64, b: u64) -> u64 {
+        <Goldilocks as SmallPrime>::mul(a, b)
+    }
+
+    fn sample(grain: &mut Grain) -> u64 {
+        grain.element(<Goldilocks as SmallPrime>::MODULUS, <Goldilocks as SmallPrime>::bits())
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Option<u64> {
+        small_from_le_bytes::<Goldilocks>(bytes)
+    }
+
+    fn to_le_bytes(element: &u64) -> Vec<u8> {
+        small_to_le_bytes::<Goldilocks>(element)
+    }
+}
+
+// Width 16, as for `SmallPoseidon<BabyBear>`. zkhash publishes no
+// permutation vector at this width, only its constants.
+impl Poseidon2Instance for BabyBear {
+    type Element = u64;
+
+    const LABEL: &'static str = <BabyBear as SmallPrime>::NAME;
+    const FIELD: &'static str = <BabyBear as SmallPrime>::NAME;
+    const WIDTH: usize = 16;
+    const CAPACITY: usize = 8;
+    const ALPHA: u64 = 7;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 13;
+    const OUTPUT: usize = 8;
+    const SECURITY_BITS: u32 = 124;
+    const STARK_FIELD: bool = true;
+    const INTERNAL_DIAGONAL: &'static [u64] = &[
+        0x0a632d94, 0x6db657b7, 0x56fbdc9e, 0x052b3d8a, 0x33745201, 0x5c03108c, 0x0beba37b, 0x258c2e8b,
+        0x12029f39, 0x694909ce, 0x6d231724, 0x21c3b222, 0x3c0904a5, 0x01d6acda, 0x27705c83, 0x5231c802,
+    ];
+    const KAT_ROUND_CONSTANTS: &'static str = "0x69cbb6af, 0x46ad93f9";
+    const KAT_PERMUTATION: Option<&'static str> = None;
+
+    fn bits() -> usize {
+        <BabyBear as SmallPrime>::bits()
+    }
+
+    fn from_u64(value: u64) -> u64 {
+        value % <BabyBear as SmallPrime>::MODULUS
+    }
+
+    fn add(a: u64, b: u64) -> u64 {
+        <BabyBear as SmallPrime>::add(a, b)
+    }
+
+    fn mul(a: u64, b: u64) -> u64 {
+        <BabyBear as SmallPrime>::mul(a, b)
+    }
+
+    fn sample(grain: &mut Grain) -> u64 {
+        grain.element(<BabyBear as SmallPrime>::MODULUS, <BabyBear as SmallPrime>::bits())
+    }
+
+    fn from_le_bytes(bytes: &[u8]) -> Option<u64> {
+        small_from_le_bytes::<BabyBear>(bytes)
+    }
+
+    fn to_le_bytes(element: &u64) -> Vec<u8> {
+        small_to_le_bytes::<BabyBear>(element)
+    }
+}
+
+// Round constants of one instance: t per full round, one per partial round
+struct Constants<E> {
+    round_constants: Vec<Vec<E>>,
+    internal_diagonal: Vec<E>,
+}
+
+impl<E> Constants<E> {
+    fn generate<I: Poseidon2Instance<Element = E>>() -> Constants<E> {
+        let (t, half_full) = (I::WIDTH, I::FULL_ROUNDS / 2);
+        let mut grain = Grain::new(0, I::bits(), t, I::FULL_ROUNDS, I::PARTIAL_ROUNDS);
+        let round_constants = (0..I::FULL_ROUNDS + I::PARTIAL_ROUNDS)
+            .map(|round| {
+                let partial = round >= half_full && round < half_full + I::PARTIAL_ROUNDS;
+                (0..if partial { 1 } else { t }).map(|_| I::sample(&mut grain)).collect()
+            })
+            .collect();
+        let internal_diagonal = I::INTERNAL_DIAGONAL.iter().map(|&d| I::from_u64(d)).collect();
+        Constants { round_constants, internal_diagonal }
+    }
+}
+
+// Poseidon2 (Grassi, Khovratovich, Schofnegger 2023) keeps Poseidon's
+// HADES round structure but swaps the dense MDS matrix for two cheap ones:
+// full (external) rounds use circ(2, 1, 1) for t = 3 and a fixed 4x4 block
+// matrix for t = 4k, partial (internal) rounds use 1 + diag(d), so a round
+// costs O(t) additions instead of t^2 multiplications. It also adds an
+// external linear layer before the first round.
+//
+// Over bytes it is the same sponge as `SmallPoseidon`: 0x01-padded
+// (bits - 1) / 8-byte little-endian chunks, zero-padded to the rate and
+// written over the rate part of the state before each permutation, then the
+// first OUTPUT elements little-endian. Constants match the zkhash reference
+// instances, so the permutation can be checked against its vectors.
+pub struct Poseidon2<I: Poseidon2Instance> {
+    name: &'static str,
+    constants: Constants<I::Element>,
+    _instance: PhantomData<I>,
+}
+
+impl<I: Poseidon2Instance> Poseidon2<I> {
+    pub fn new() -> Self {
+        Poseidon2 {
+            name: Box::leak(format!("Poseidon2-{}", I::LABEL).into_boxed_str()),
+            constants: Constants::generate::<I>(),
+            _instance: PhantomData,
+        }
+    }
+
+    fn rate() -> usize {
+        I::WIDTH - I::CAPACITY
+    }
+
+    fn sbox(x: I::Element) -> I::Element {
+        let x2 = I::mul(x, x);
+        let x4 = I::mul(x2, x2);
+        match I::ALPHA {
+            3 => I::mul(x2, x),
+            5 => I::mul(x4, x),
+            _ => I::mul(I::mul(x4, x2), x),
+        }
+    }
+
+    fn sum(state: &[I::Element]) -> I::Element {
+        state[1..].iter().fold(state[0], |acc, &x| I::add(acc, x))
+    }
+
+    // circ(2, 1, ..) up to t = 3; beyond, the 4x4 matrix on each chunk of
+    // four, then each chunk's column sums added to every chunk
+    fn external_matmul(state: &mut [I::Element]) {
+        if I::WIDTH <= 3 {
+            let sum = Self::sum(state);
+            state.iter_mut().for_each(|x| *x = I::add(*x, sum));
+            return;
+        }
+        let double = |x| I::add(x, x);
+        for chunk in state.chunks_mut(4) {
+            let t0 = I::add(chunk[0], chunk[1]);
+            let t1 = I::add(chunk[2], chunk[3]);
+            let t2 = I::add(double(chunk[1]), t1);
+            let t3 = I::add(double(chunk[3]), t0);
+            let t4 = I::add(double(double(t1)), t3);
+            let t5 = I::add(double(double(t0)), t2);
+            chunk.copy_from_slice(&[I::add(t3, t5), t5, I::add(t2, t4), t4]);
+        }
+        if I::WIDTH > 4 {
+            let mut columns = [state[0], state[1], state[2], state[3]];
+            for chunk in state[4..].chunks(4) {
+                columns.iter_mut().zip(chunk).for_each(|(c, &x)| *c = I::add(*c, x));
+            }
+            for (i, x) in state.iter_mut().enumerate() {
+                *x = I::add(*x, columns[i % 4]);
+            }
+        }
+    }
+
+    fn internal_matmul(&self, state: &mut [I::Element]) {
+        let sum = Self::sum(state);
+        for (x, &d) in state.iter_mut().zip(&self.constants.internal_diagonal) {
+            *x = I::add(I::mul(*x, d), sum);
+        }
+    }
+
+    fn permute(&self, state: &mut [I::Element]) {
+        let half_full = I::FULL_ROUNDS / 2;
+        Self::external_matmul(state);
+        for (round, constants) in self.constants.round_constants.iter().enumerate() {
+            if round < half_full || round >= half_full + I::PARTIAL_ROUNDS {
+                for (x, &c) in state.iter_mut().zip(constants) {
+                    *x = Self::sbox(I::add(*x, c));
+                }
+                Self::external_matmul(state);
+            } else {
+                state[0] = Self::sbox(I::add(state[0], constants[0]));
+                self.internal_matmul(state);
+            }
+        }
+    }
+
+    fn pack_bytes(data: &[u8]) -> Result<Vec<I::Element>, HashError> {
+        let chunk_len = (I::bits() - 1) / 8;
+        let mut padded = data.to_vec();
+        padded.push(0x01);
+        padded.resize(padded.len().div_ceil(chunk_len) * chunk_len, 0);
+
+        let elements = padded
+            .chunks(chunk_len)
+            .map(|chunk| {
+                I::from_le_bytes(chunk).ok_or_else(|| HashError::NonCanonicalEncoding {
+                    field: I::FIELD,
+                    bytes: chunk.to_vec(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::pad(elements))
+    }
+
+    // Zeros up to a multiple of the rate
+    fn pad(mut elements: Vec<I::Element>) -> Vec<I::Element> {
+        elements.resize(elements.len().div_ceil(Self::rate()) * Self::rate(), I::from_u64(0));
+        elements
+    }
+
+    // Writes padded elements over the rate part of the state, returning the
+    // digest bytes
+    fn sponge(&self, elements: &[I::Element]) -> Vec<u8> {
+        let mut state = vec![I::from_u64(0); I::WIDTH];
+        for block in elements.chunks(Self::rate()) {
+            state[..block.len()].copy_from_slice(block);
+            self.permute(&mut state);
+        }
+        state[..I::OUTPUT].iter().flat_map(I::to_le_bytes).collect()
+    }
+
+    // Big-endian hex of the first two elements, as printed by zkhash
+    fn first_two(values: &[I::Element]) -> String {
+        let hex = |x: &I::Element| -> String {
+            I::to_le_bytes(x).iter().rev().map(|b| format!("{:02x}", b)).collect()
+        };
+        format!("0x{}, 0x{}", hex(&values[0]), hex(&values[1]))
+    }
+}
+
+impl<I: Poseidon2Instance> HashFunction for Poseidon2<I> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn output_size(&self) -> usize {
+        I::OUTPUT * I::bits().div_ceil(8)
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Field(I::FIELD)
+    }
+
+    fn security_bits(&self) -> u32 {
+        I::SECURITY_BITS
+    }
+
+    fn parameters(&self) -> Option<String> {
+        Some(format!(
+            "t={}, rate {}, x^{}, {}+{} rounds",
+            I::WIDTH,
+            Self::rate(),
+            I::ALPHA,
+            I::FULL_ROUNDS,
+            I::PARTIAL_ROUNDS
+        ))
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        Ok(self.sponge(&Self::pack_bytes(data)?))
+    }
+
+    fn field_elements(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        Ok(Some(Self::pack_bytes(data)?.len()))
+    }
+
+    fn setup_phases(&self) -> Vec<SetupPhase> {
+        vec![SetupPhase {
+            name: "Grain constants generation",
+            run: Box::new(|| {
+                black_box(Constants::generate::<I>());
+            }),
+        }]
+    }
+
+    // Computed from the round structure: the cheaper matrices are linear and
+    // free in R1CS, so only the S-boxes cost, 3 constraints each for x^5
+    fn snark_constraints(&self) -> Option<usize> {
+        (!I::STARK_FIELD).then_some(3 * (I::FULL_ROUNDS * I::WIDTH + I::PARTIAL_ROUNDS))
+    }
+
+    fn snark_constraints_computed(&self) -> bool {
+        true
+    }
+
+    fn r1cs_cost(&self, data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        let elements = Self::pack_bytes(data)?;
+        I::r1cs_cost(&self.constants.round_constants, &self.constants.internal_diagonal, elements)
+            .transpose()
+            .map_err(|e| HashError::Synthesis(e.to_string()))
+    }
+
+    // A node absorbs its children's digest elements, zero-padded to the rate
+    fn merkle(&self, data: &[u8], settings: &MerkleSettings) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        let element_bytes = I::bits().div_ceil(8);
+        let node = |children: &[Vec<u8>]| -> Result<Vec<u8>, HashError> {
+            let elements = children
+                .iter()
+                .flat_map(|child| child.chunks(element_bytes))
+                .map(|chunk| {
+                    I::from_le_bytes(chunk).ok_or_else(|| HashError::NonCanonicalEncoding {
+                        field: I::FIELD,
+                        bytes: chunk.to_vec(),
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(self.sponge(&Self::pad(elements)))
+        };
+        let constants = &self.constants;
+        I::merkle(&constants.round_constants, &constants.internal_diagonal, &|data| self.hash(data), &node, data, settings)
+    }
+
+    // Same trace shape as `SmallPoseidon`, over any field: the linear layers
+    // add no cells
+    fn air_cost(&self, data: &[u8]) -> Result<Option<AirCost>, HashError> {
+        let permutations = Self::pack_bytes(data)?.len() / Self::rate();
+        Ok(Some(AirCost {
+            width: air::spn_width(I::WIDTH, I::FULL_ROUNDS, I::PARTIAL_ROUNDS, I::ALPHA),
+            rows: permutations,
+            degree: air::DEGREE,
+            absorbed_bytes: permutations * Self::rate() * ((I::bits() - 1) / 8),
+        }))
+    }
+
+    // SNARK fields only (t = 3): circ(2, 1, 1) is the state sum plus one
+    // addition per element, and 1 + diag(d) has the same shape with the
+    // diagonal in the selectors. The overwrite sponge absorbs for free.
+    fn plonk_gates(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        if I::STARK_FIELD {
+            return Ok(None);
+        }
+        let matrix = plonk::sum_gates(I::WIDTH) + I::WIDTH;
+        let sbox = plonk::power_gates(I::ALPHA);
+        let per_permutation =
+            matrix + I::FULL_ROUNDS * (I::WIDTH * sbox + matrix) + I::PARTIAL_ROUNDS * (sbox + matrix);
+        let permutations = Self::pack_bytes(data)?.len() / Self::rate();
+        Ok(Some(permutations * per_permutation))
+    }
+
+    // Poseidon's layout, with one more row for the linear layer before the
+    // first round
+    fn plonkish_cost(&self, data: &[u8]) -> Result<Option<PlonkishCost>, HashError> {
+        if I::STARK_FIELD {
+            return Ok(None);
+        }
+        let rows = 1 + I::FULL_ROUNDS + I::PARTIAL_ROUNDS.div_ceil(2);
+        let permutations = Self::pack_bytes(data)?.len() / Self::rate();
+        Ok(Some(PlonkishCost {
+            advice_columns: I::WIDTH + 1,
+            rows: plonk::sponge_rows(permutations, rows, false),
+            lookup_tables: 0,
+            table_rows: 0,
+            degree: plonk::gate_degree(I::ALPHA),
+        }))
+    }
+
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        let mut answers = vec![KnownAnswer {
+            name: "round constants [0..2]",
+            expected: I::KAT_ROUND_CONSTANTS,
+            actual: Self::first_two(&self.constants.round_constants[0]),
+        }];
+        if let Some(expected) = I::KAT_PERMUTATION {
+            let mut state: Vec<I::Element> = (0..I::WIDTH as u64).map(I::from_u64).collect();
+            self.permute(&mut state);
+            answers.push(KnownAnswer {
+                name: "permutation(0, 1, .., t-1) [0..2]",
+                expected,
+                actual: Self::first_two(&state),
+            });
+        }
+        answers
+    }
+
+    fn use_cases(&self) -> UseCases {
+        if I::STARK_FIELD {
+            UseCases {
+                good_for: &[
+                    "STARK provers (Plonky3, RISC Zero, SP1)",
+                    "Merkle commitments where native hashing dominates",
+                ],
+                bad_for: &["Not native to BN254/BLS12-381 SNARKs (field emulation)"],
+                ethereum_use: "STARK zkVMs",
+                best_for: "STARK provers",
+            }
+        } else {
+            UseCases {
+                good_for: &[
+                    "Zero-knowledge proof systems",
+                    "Plonkish circuits, where the cheap linear layers save gates",
+                ],
+                bad_for: &["Younger than Poseidon, fewer audited deployments"],
+                ethereum_use: "zkApps/Rollups",
+                best_for: "Zero-knowledge",
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scalar_circuit, Poseidon2, Poseidon2Instance};
+    use crate::circuits::{self, FieldNode};
+    use crate::fields::{self, Bn254Fr, NamedField};
+    use crate::hashes::HashFunction;
+
+    // The packed elements, hashed as one node, give the byte hash's digest.
+    // Lengths cover one, two and several permutations.
+    fn gadget_matches_native<F: NamedField + Poseidon2Instance<Element = F>>() {
+        let hash = Poseidon2::<F>::new();
+        let constants = &hash.constants;
+        let gadget = FieldNode(scalar_circuit::<F, F>(&constants.round_constants, &constants.internal_diagonal, Vec::new()));
+        for len in [0, 31, 62, 200] {
+            let data: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+            let elements: Vec<Vec<u8>> = Poseidon2::<F>::pack_bytes(&data).unwrap().iter().map(fields::to_le_bytes).collect();
+            let context = format!("{}, {} bytes", hash.name(), len);
+            circuits::assert_node_digest(&gadget, &elements, &hash.hash(&data).unwrap(), &context);
+        }
+    }
+
+    #[test]
+    fn gadget_matches_native_bn254() {
+        gadget_matches_native::<Bn254Fr>();
+    }
+
+    #[test]
+    fn gadget_matches_native_bls12_381() {
+        gadget_matches_native::<blstrs::Scalar>();
+    }
+}
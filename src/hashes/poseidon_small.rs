@@ -0,0 +1,316 @@
+use std::hint::black_box;
+use std::marker::PhantomData;
+
+use super::air::{self, AirCost};
+use super::grain::Grain;
+use super::{Domain, HashError, HashFunction, KnownAnswer, SetupPhase, UseCases, NON_STANDARD};
+use crate::fields::{BabyBear, Goldilocks, Mersenne31, SmallPrime};
+
+// Standard Poseidon instance over a STARK field: width, capacity, S-box
+// exponent and round numbers for 128-bit security, as computed by the
+// reference round-number script
+pub trait SmallInstance: SmallPrime {
+    const WIDTH: usize;
+    const CAPACITY: usize;
+    const ALPHA: u64;
+    const FULL_ROUNDS: usize;
+    const PARTIAL_ROUNDS: usize;
+    // Digest length in field elements
+    const OUTPUT: usize;
+
+    // Reference values from the zkhash instances, as "0x.., 0x.."
+    const KAT_ROUND_CONSTANTS: Option<&'static str>;
+    const KAT_MDS: Option<&'static str>;
+    // First two outputs of permuting (0, 1, .., t - 1)
+    const KAT_PERMUTATION: Option<&'static str>;
+}
+
+// Plonky2's width and rounds
+impl SmallInstance for Goldilocks {
+    const WIDTH: usize = 12;
+    const CAPACITY: usize = 4;
+    const ALPHA: u64 = 7;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 22;
+    const OUTPUT: usize = 4;
+    const KAT_ROUND_CONSTANTS: Option<&'static str> = Some("0xe034a8785fd284a7, 0xe2463f1ea42e1b80");
+    const KAT_MDS: Option<&'static str> = Some("0x5f1d4cecfc89ba7a, 0xca24b673d0b08c7");
+    const KAT_PERMUTATION: Option<&'static str> = Some("0xe9ad770762f48ef5, 0xc12796961ddc7859");
+}
+
+// Width 16 as in Plonky3, RISC Zero and SP1
+impl SmallInstance for BabyBear {
+    const WIDTH: usize = 16;
+    const CAPACITY: usize = 8;
+    const ALPHA: u64 = 7;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 13;
+    const OUTPUT: usize = 8;
+    const KAT_ROUND_CONSTANTS: Option<&'static str> = Some("0x22d14fc7, 0x47743d29");
+    const KAT_MDS: Option<&'static str> = Some("0x6ed88b54, 0x365c29f9");
+    const KAT_PERMUTATION: Option<&'static str> = None;
+}
+
+// 3 and 7 divide p - 1, so the smallest valid S-box is x^5
+impl SmallInstance for Mersenne31 {
+    const WIDTH: usize = 16;
+    const CAPACITY: usize = 8;
+    const ALPHA: u64 = 5;
+    const FULL_ROUNDS: usize = 8;
+    const PARTIAL_ROUNDS: usize = 14;
+    const OUTPUT: usize = 8;
+    // No published reference instance to check against
+    const KAT_ROUND_CONSTANTS: Option<&'static str> = None;
+    const KAT_MDS: Option<&'static str> = None;
+    const KAT_PERMUTATION: Option<&'static str> = None;
+}
+
+// Steps 1 and 2 of the sponge below, shared by the other word-sized field
+// hashes: bytes padded with 0x01 into (bits - 1) / 8-byte little-endian
+// elements, then zero elements up to a multiple of `rate`
+pub fn pack_bytes<F: SmallPrime>(data: &[u8], rate: usize) -> Vec<u64> {
+    let chunk_len = (F::bits() - 1) / 8;
+    let mut padded = data.to_vec();
+    padded.push(0x01);
+    padded.resize(padded.len().div_ceil(chunk_len) * chunk_len, 0);
+
+    let mut elements: Vec<u64> = padded
+        .chunks(chunk_len)
+        .map(|chunk| chunk.iter().rev().fold(0, |acc, &b| acc << 8 | b as u64))
+        .collect();
+    elements.resize(elements.len().div_ceil(rate) * rate, 0);
+    elements
+}
+
+// Step 3: each element little-endian in ceil(bits / 8) bytes
+pub fn encode_digest<F: SmallPrime>(elements: &[u64]) -> Vec<u8> {
+    let element_bytes = F::bits().div_ceil(8);
+    elements
+        .iter()
+        .flat_map(|x| x.to_le_bytes().into_iter().take(element_bytes))
+        .collect()
+}
+
+// Round constants and MDS matrix of one instance
+struct Constants {
+    round_constants: Vec<u64>,
+    mds: Vec<Vec<u64>>,
+}
+
+impl Constants {
+    // Grain-LFSR round constants, then the first Cauchy matrix 1 / (x_i + y_j)
+    // whose 2t sampled points are distinct and give no zero denominator
+    fn generate<F: SmallInstance>() -> Constants {
+        let (t, n) = (F::WIDTH, F::bits());
+        let mut grain = Grain::new(1, n, t, F::FULL_ROUNDS, F::PARTIAL_ROUNDS);
+        let round_constants = (0..(F::FULL_ROUNDS + F::PARTIAL_ROUNDS) * t)
+            .map(|_| grain.element(F::MODULUS, n))
+            .collect();
+
+        let mds = loop {
+            let points: Vec<u64> = (0..2 * t).map(|_| grain.bits(n) % F::MODULUS).collect();
+            let mut sorted = points.clone();
+            sorted.sort_unstable();
+            sorted.dedup();
+            if sorted.len() != points.len() {
+                continue;
+            }
+            let (xs, ys) = points.split_at(t);
+            if xs.iter().any(|&x| ys.iter().any(|&y| F::add(x, y) == 0)) {
+                continue;
+            }
+            break xs
+                .iter()
+                .map(|&x| ys.iter().map(|&y| F::inv(F::add(x, y))).collect())
+                .collect();
+        };
+        Constants { round_constants, mds }
+    }
+}
+
+// Poseidon over a word-sized STARK field, as a sponge over bytes:
+//
+// 1. Pad the message with 0x01 and zero bytes to a multiple of
+//    k = (bits - 1) / 8 bytes (7 for Goldilocks, 3 for the 31-bit fields),
+//    and read each chunk as a little-endian element.
+// 2. Zero-pad the elements to a multiple of the rate and overwrite the rate
+//    part of the state with each block before permuting, like Plonky2/3's
+//    sponges. The message's last element is non-zero, so this is injective.
+// 3. Return the first OUTPUT elements, each little-endian in ceil(bits / 8)
+//    bytes (32 bytes in total for all three instances).
+//
+// Constants match the HorizenLabs/zkhash reference instances, not Plonky2's
+// own hand-picked MDS and constants.
+pub struct SmallPoseidon<F: SmallInstance> {
+    name: &'static str,
+    constants: Constants,
+    _field: PhantomData<F>,
+}
+
+impl<F: SmallInstance> SmallPoseidon<F> {
+    pub fn new() -> Self {
+        SmallPoseidon {
+            name: Box::leak(format!("Poseidon-{}{}", F::NAME, Self::marker()).into_boxed_str()),
+            constants: Constants::generate::<F>(),
+            _field: PhantomData,
+        }
+    }
+
+    fn rate() -> usize {
+        F::WIDTH - F::CAPACITY
+    }
+
+    // Instances without a permutation vector are marked: their constants
+    // follow the reference derivation, but the permutation is unchecked
+    fn marker() -> &'static str {
+        if F::KAT_PERMUTATION.is_some() { "" } else { NON_STANDARD }
+    }
+
+    fn permute(&self, state: &mut [u64]) {
+        let half_full = F::FULL_ROUNDS / 2;
+        let rounds = F::FULL_ROUNDS + F::PARTIAL_ROUNDS;
+        let mut next = vec![0; F::WIDTH];
+        for (round, constants) in self.constants.round_constants.chunks(F::WIDTH).enumerate() {
+            for (x, c) in state.iter_mut().zip(constants) {
+                *x = F::add(*x, *c);
+            }
+            if round < half_full || round >= rounds - half_full {
+                for x in state.iter_mut() {
+                    *x = F::pow(*x, F::ALPHA);
+                }
+            } else {
+                state[0] = F::pow(state[0], F::ALPHA);
+            }
+            for (out, row) in next.iter_mut().zip(&self.constants.mds) {
+                *out = row.iter().zip(state.iter()).fold(0, |acc, (m, x)| F::add(acc, F::mul(*m, *x)));
+            }
+            state.copy_from_slice(&next);
+        }
+    }
+
+    fn pack_bytes(data: &[u8]) -> Vec<u64> {
+        pack_bytes::<F>(data, Self::rate())
+    }
+
+    // Permutations one hash of `data` runs
+    fn permutations(data: &[u8]) -> usize {
+        Self::pack_bytes(data).len() / Self::rate()
+    }
+}
+
+impl<F: SmallInstance> HashFunction for SmallPoseidon<F> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn output_size(&self) -> usize {
+        F::OUTPUT * F::bits().div_ceil(8)
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Field(F::NAME)
+    }
+
+    // Half the capacity in bits, capped at the 128-bit design target
+    fn security_bits(&self) -> u32 {
+        (F::CAPACITY * F::bits() / 2).min(128) as u32
+    }
+
+    fn parameters(&self) -> Option<String> {
+        let unchecked = match (F::KAT_PERMUTATION, F::KAT_ROUND_CONSTANTS) {
+            (Some(_), _) => "",
+            (None, Some(_)) => ", unchecked permutation",
+            (None, None) => ", unchecked constants",
+        };
+        Some(format!(
+            "t={}, rate {}, x^{}, {}+{} rounds{}",
+            F::WIDTH,
+            Self::rate(),
+            F::ALPHA,
+            F::FULL_ROUNDS,
+            F::PARTIAL_ROUNDS,
+            unchecked
+        ))
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        let mut state = vec![0; F::WIDTH];
+        for block in Self::pack_bytes(data).chunks(Self::rate()) {
+            state[..block.len()].copy_from_slice(block);
+            self.permute(&mut state);
+        }
+
+        Ok(encode_digest::<F>(&state[..F::OUTPUT]))
+    }
+
+    fn field_elements(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        Ok(Some(Self::pack_bytes(data).len()))
+    }
+
+    fn setup_phases(&self) -> Vec<SetupPhase> {
+        vec![SetupPhase {
+            name: "Grain constants generation",
+            run: Box::new(|| {
+                black_box(Constants::generate::<F>());
+            }),
+        }]
+    }
+
+    // Not a SNARK-field hash: its arithmetic would have to be emulated in R1CS
+    fn snark_constraints(&self) -> Option<usize> {
+        None
+    }
+
+    // One AIR row per permutation; with degree-3 constraints an S-box above
+    // x^3 needs one extra committed intermediate (x^3)
+    fn air_cost(&self, data: &[u8]) -> Result<Option<AirCost>, HashError> {
+        let permutations = Self::permutations(data);
+        Ok(Some(AirCost {
+            width: air::spn_width(F::WIDTH, F::FULL_ROUNDS, F::PARTIAL_ROUNDS, F::ALPHA),
+            rows: permutations,
+            degree: air::DEGREE,
+            absorbed_bytes: permutations * Self::rate() * ((F::bits() - 1) / 8),
+        }))
+    }
+
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        let first = |values: &[u64]| format!("{:#x}, {:#x}", values[0], values[1]);
+        let mut answers = Vec::new();
+        if let Some(expected) = F::KAT_ROUND_CONSTANTS {
+            answers.push(KnownAnswer {
+                name: "round constants [0..2]",
+                expected,
+                actual: first(&self.constants.round_constants),
+            });
+        }
+        if let Some(expected) = F::KAT_MDS {
+            answers.push(KnownAnswer {
+                name: "MDS row 0 [0..2]",
+                expected,
+                actual: first(&self.constants.mds[0]),
+            });
+        }
+        if let Some(expected) = F::KAT_PERMUTATION {
+            let mut state: Vec<u64> = (0..F::WIDTH as u64).collect();
+            self.permute(&mut state);
+            answers.push(KnownAnswer {
+                name: "permutation(0, 1, .., t-1) [0..2]",
+                expected,
+                actual: first(&state),
+            });
+        }
+        answers
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "STARK provers (Plonky2/3, RISC Zero, SP1)",
+                "Recursive STARK verification and Merkle commitments",
+            ],
+            bad_for: &["Not native to BN254/BLS12-381 SNARKs (field emulation)"],
+            ethereum_use: "STARK zkVMs",
+            best_for: "STARK provers",
+        }
+    }
+}
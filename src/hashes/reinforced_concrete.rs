@@ -0,0 +1,323 @@
+use std::hint::black_box;
+
+use ff::Field;
+
+use super::plonk::{self, LookupCost, PlonkishCost};
+use super::poseidon::pack_bytes;
+use super::shake::ShakeSampler;
+use super::{Domain, HashError, HashFunction, SetupPhase, UseCases};
+use crate::fields::{self, Bn254Fr, NamedField};
+
+const WIDTH: usize = 3;
+const CAPACITY: usize = 1;
+// Concrete layers C^(0) .. C^(7); the one before C^(4) is Bars, the others
+// are Bricks
+const CONCRETE_LAYERS: usize = 8;
+const BARS_LAYER: usize = 4;
+// Bricks: x_1^d, and (alpha_i, beta_i) of the quadratics for x_2 and x_3
+const D: u64 = 5;
+const ALPHA_BETA: [(u64, u64); 2] = [(1, 3), (2, 4)];
+// p', the S-box domain of the BN254 instance: digits below it go through the
+// S-box, larger ones are left alone
+const S_BOX_DOMAIN: u64 = 659;
+// Every bucket fits a 10-bit table
+const MAX_RADIX: u64 = 1 << 10;
+
+// Little-endian u64 limbs of a canonical element
+fn to_limbs(x: &Bn254Fr) -> Vec<u64> {
+    fields::to_le_bytes(x)
+        .chunks(8)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().expect("8-byte chunks")))
+        .collect()
+}
+
+fn from_limbs(limbs: &[u64]) -> Option<Bn254Fr> {
+    let bytes: Vec<u8> = limbs.iter().flat_map(|limb| limb.to_le_bytes()).collect();
+    fields::from_le_bytes(&bytes)
+}
+
+// Divides `limbs` by `divisor` in place and returns the remainder
+fn div_rem(limbs: &mut [u64], divisor: u64) -> u64 {
+    let mut remainder = 0u128;
+    for limb in limbs.iter_mut().rev() {
+        let acc = remainder << 64 | *limb as u128;
+        *limb = (acc / divisor as u128) as u64;
+        remainder = acc % divisor as u128;
+    }
+    remainder as u64
+}
+
+// limbs = limbs * factor + addend
+fn mul_add(limbs: &mut [u64], factor: u64, addend: u64) {
+    let mut carry = addend as u128;
+    for limb in limbs.iter_mut() {
+        let acc = *limb as u128 * factor as u128 + carry;
+        *limb = acc as u64;
+        carry = acc >> 64;
+    }
+}
+
+fn to_f64(limbs: &[u64]) -> f64 {
+    limbs.iter().rev().fold(0.0, |acc, &limb| acc * 2f64.powi(64) + limb as f64)
+}
+
+struct Parameters {
+    // s_1 .. s_n, most significant first
+    radices: Vec<u64>,
+    // The S-box on [0, p') and the identity above, up to the largest s_i
+    table: Vec<u64>,
+    s_box_exponent: u64,
+    round_constants: Vec<[Bn254Fr; WIDTH]>,
+}
+
+impl Parameters {
+    fn generate() -> Parameters {
+        let radices = Self::radices();
+        let s_box_exponent = (3u64..)
+            .find(|&e| (2..=e).all(|k| !e.is_multiple_of(k) || !(S_BOX_DOMAIN - 1).is_multiple_of(k)))
+            .expect("some exponent is coprime to p' - 1");
+        let table = (0..*radices.iter().max().expect("at least one radix"))
+            .map(|v| match v < S_BOX_DOMAIN {
+                true => (0..s_box_exponent).fold(1, |acc, _| acc * v % S_BOX_DOMAIN),
+                false => v,
+            })
+            .collect();
+
+        let mut sampler = ShakeSampler::<Bn254Fr>::new("ReinforcedConcrete");
+        let round_constants = (0..CONCRETE_LAYERS)
+            .map(|_| std::array::from_fn(|_| sampler.next()))
+            .collect();
+
+        Parameters { radices, table, s_box_exponent, round_constants }
+    }
+
+    // Bars is a permutation of F_p when every digit of p - 1 is at least p':
+    // a smaller value first differs from p - 1 in a digit below p - 1's, the
+    // S-box keeps that digit below p - 1's, and the larger digits before it
+    // are left alone. The radices are found from the least significant digit
+    // up: with k divisions left, the one closest to the k-th root that
+    // would leave a leading digit of about 841 (the middle of [p', 1024))
+    // among those giving a digit >= p'. The smallest number of divisions that
+    // lands the leading digit in [p', 1024) is used, and s_1 is that digit
+    // plus one.
+    fn radices() -> Vec<u64> {
+        let p_minus_1 = to_limbs(&-Bn254Fr::ONE);
+        let leading_target = (S_BOX_DOMAIN + MAX_RADIX) as f64 / 2.0;
+        (1..)
+            .find_map(|divisions| {
+                let mut x = p_minus_1.clone();
+                let mut radices = Vec::with_capacity(divisions + 1);
+                for remaining in (1..=divisions).rev() {
+                    let target = (to_f64(&x) / leading_target).powf(1.0 / remaining as f64);
+                    let radix = (S_BOX_DOMAIN + 1..MAX_RADIX)
+                        .filter(|&s| div_rem(&mut x.clone(), s) >= S_BOX_DOMAIN)
+                        .min_by(|&a, &b| (a as f64 - target).abs().total_cmp(&(b as f64 - target).abs()))?;
+                    div_rem(&mut x, radix);
+                    radices.push(radix);
+                }
+                let leading = x[0];
+                let fits = x[1..].iter().all(|&limb| limb == 0) && (S_BOX_DOMAIN..MAX_RADIX).contains(&leading);
+                fits.then(|| {
+                    radices.push(leading + 1);
+                    radices.reverse();
+                    radices
+                })
+            })
+            .expect("some digit count fits")
+    }
+}
+
+// Reinforced Concrete (Grassi, Khovratovich, Lüftenegger, Rechberger,
+// Schofnegger, Walch 2022) over BN254 with t = 3, the lookup-based design
+// built for Plonkish circuits. The permutation interleaves 8 Concrete layers
+// (circ(2, 1, 1) plus constants) with 6 Bricks and one Bars, in order of
+// application:
+//
+//   C0 B C1 B C2 B C3 Bars C4 B C5 B C6 B C7
+//
+// Bricks is (x_1^5, x_2 (x_1^2 + x_1 + 3), x_3 (x_2^2 + 2 x_2 + 4)). Bars
+// writes each element in the mixed radix (s_1, .., s_n), sends every digit
+// below p' through an S-box on F_p' and rebuilds the element; in a circuit
+// each digit is one lookup.
+//
+// The reference instance fixes its radices, S-box and constants in tables
+// that were not available offline, so they are derived here instead: the
+// radices as in `Parameters::radices`, the S-box as the smallest invertible
+// power map on F_p' and the constants from `ShakeSampler`. Digests therefore
+// differ from the reference; the structure and cost do not.
+//
+// Bytes are packed as for Poseidon and absorbed with the same additive
+// sponge and 10* padding as Rescue-Prime. The digest is the first state
+// element.
+pub struct ReinforcedConcrete {
+    parameters: Parameters,
+}
+
+impl ReinforcedConcrete {
+    pub fn new() -> Self {
+        ReinforcedConcrete { parameters: Parameters::generate() }
+    }
+
+    fn rate() -> usize {
+        WIDTH - CAPACITY
+    }
+
+    fn concrete(&self, state: &mut [Bn254Fr; WIDTH], layer: usize) {
+        let sum: Bn254Fr = state.iter().sum();
+        for (x, c) in state.iter_mut().zip(&self.parameters.round_constants[layer]) {
+            *x += sum + c;
+        }
+    }
+
+    fn bricks(state: &mut [Bn254Fr; WIDTH]) {
+        let [x1, x2, x3] = *state;
+        let x1_2 = x1.square();
+        let quadratic = |x: Bn254Fr, x_2: Bn254Fr, (alpha, beta): (u64, u64)| {
+            x_2 + x * Bn254Fr::from(alpha) + Bn254Fr::from(beta)
+        };
+        state[0] = x1_2.square() * x1;
+        state[1] = x2 * quadratic(x1, x1_2, ALPHA_BETA[0]);
+        state[2] = x3 * quadratic(x2, x2.square(), ALPHA_BETA[1]);
+    }
+
+    fn bar(&self, x: &Bn254Fr) -> Bn254Fr {
+        let (radices, table) = (&self.parameters.radices, &self.parameters.table);
+        let mut limbs = to_limbs(x);
+        let digits: Vec<u64> = radices[1..].iter().rev().map(|&s| div_rem(&mut limbs, s)).collect();
+
+        let mut out = vec![0; limbs.len()];
+        out[0] = table[limbs[0] as usize];
+        for (&s, &digit) in radices[1..].iter().zip(digits.iter().rev()) {
+            mul_add(&mut out, s, table[digit as usize]);
+        }
+        from_limbs(&out).expect("Bars maps F_p to itself")
+    }
+
+    fn permute(&self, state: &mut [Bn254Fr; WIDTH]) {
+        self.concrete(state, 0);
+        for layer in 1..CONCRETE_LAYERS {
+            if layer == BARS_LAYER {
+                state.iter_mut().for_each(|x| *x = self.bar(x));
+            } else {
+                Self::bricks(state);
+            }
+            self.concrete(state, layer);
+        }
+    }
+
+    // Packed elements with the sponge's own 10* padding to a multiple of the rate
+    fn absorbed_elements(data: &[u8]) -> Result<Vec<Bn254Fr>, HashError> {
+        let mut elements = pack_bytes::<Bn254Fr>(data)?;
+        elements.push(Bn254Fr::ONE);
+        elements.resize(elements.len().div_ceil(Self::rate()) * Self::rate(), Bn254Fr::ZERO);
+        Ok(elements)
+    }
+}
+
+impl HashFunction for ReinforcedConcrete {
+    // Ends in `NON_STANDARD`: the radices, S-box and constants are derived
+    // here rather than taken from the reference
+    fn name(&self) -> &'static str {
+        "RC-BN254*"
+    }
+
+    fn output_size(&self) -> usize {
+        fields::repr_len::<Bn254Fr>()
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Field(Bn254Fr::NAME)
+    }
+
+    fn security_bits(&self) -> u32 {
+        128
+    }
+
+    fn parameters(&self) -> Option<String> {
+        Some(format!(
+            "Reinforced Concrete, t={}, rate {}, {} digits, S-box x^{} on F_{}, non-standard constants",
+            WIDTH,
+            Self::rate(),
+            self.parameters.radices.len(),
+            self.parameters.s_box_exponent,
+            S_BOX_DOMAIN
+        ))
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        let mut state = [Bn254Fr::ZERO; WIDTH];
+        for block in Self::absorbed_elements(data)?.chunks(Self::rate()) {
+            state.iter_mut().zip(block).for_each(|(x, m)| *x += m);
+            self.permute(&mut state);
+        }
+        Ok(fields::to_le_bytes(&state[0]))
+    }
+
+    fn field_elements(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        Ok(Some(pack_bytes::<Bn254Fr>(data)?.len()))
+    }
+
+    fn setup_phases(&self) -> Vec<SetupPhase> {
+        vec![SetupPhase {
+            name: "radix search and SHAKE128 constants",
+            run: Box::new(|| {
+                black_box(Parameters::generate());
+            }),
+        }]
+    }
+
+    // Bars has no efficient R1CS form: without lookups each digit needs a
+    // bit decomposition, so `lookup_cost` is the meaningful figure
+    fn snark_constraints(&self) -> Option<usize> {
+        None
+    }
+
+    // Bricks: x_1^5 (whose x_1^2 the quadratic reuses), then the quadratic
+    // and product for x_2, and x_2^2, the quadratic and product for x_3.
+    // Concrete: the state sum plus one addition per element. Bars splits and
+    // rebuilds each element, with one lookup per digit.
+    fn lookup_cost(&self, data: &[u8]) -> Result<Option<LookupCost>, HashError> {
+        let digits = self.parameters.radices.len();
+        let bricks = plonk::power_gates(D) + 2 + 3;
+        let concrete = plonk::sum_gates(WIDTH) + WIDTH;
+        let bars = WIDTH * 2 * plonk::decomposition_gates(digits);
+        let per_permutation = CONCRETE_LAYERS * concrete + (CONCRETE_LAYERS - 2) * bricks + bars;
+        let elements = Self::absorbed_elements(data)?.len();
+        let permutations = elements / Self::rate();
+        Ok(Some(LookupCost {
+            gates: permutations * per_permutation + plonk::absorb_gates(elements, Self::rate()),
+            lookups: permutations * WIDTH * digits,
+            // One row per (bucket, digit) pair, so a lookup also range-checks
+            // its digit against its own s_i
+            table_rows: self.parameters.radices.iter().sum::<u64>() as usize,
+        }))
+    }
+
+    // A row per Bricks layer and for the first and last Concrete. Bars is
+    // a running-sum decomposition, one digit of every element per row: in
+    // and out running sums, the digit and its image, four columns each.
+    fn plonkish_cost(&self, data: &[u8]) -> Result<Option<PlonkishCost>, HashError> {
+        let digits = self.parameters.radices.len();
+        let rows = 2 + (CONCRETE_LAYERS - 2) + digits;
+        let permutations = Self::absorbed_elements(data)?.len() / Self::rate();
+        Ok(Some(PlonkishCost {
+            advice_columns: 4 * WIDTH,
+            rows: plonk::sponge_rows(permutations, rows, true),
+            lookup_tables: 1,
+            table_rows: self.lookup_cost(data)?.map_or(0, |cost| cost.table_rows),
+            degree: plonk::gate_degree(D),
+        }))
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "Plonkish circuits with lookups (plookup, halo2)",
+                "Fast natively for a SNARK-field hash: one Bars layer",
+            ],
+            bad_for: &["R1CS and circuits without lookups"],
+            ethereum_use: "zk research",
+            best_for: "Lookup-based PLONK",
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use bellpepper::gadgets::multipack;
+use blstrs::Scalar as Fr;
+use sha2::Digest;
+
+use super::air::{self, AirCost};
+use super::{Domain, HashError, HashFunction, KnownAnswer, UseCases};
+use crate::chain::{self, ChainCost, ChainSettings};
+use crate::circuits::{self, BitGadget, R1csCost, Sha256Circuit};
+use crate::groth16::{self, Groth16Cost};
+use crate::merkle::{self, MerkleCost, MerkleSettings};
+
+pub struct Sha256;
+
+impl HashFunction for Sha256 {
+    fn name(&self) -> &'static str {
+        "SHA-256"
+    }
+
+    fn output_size(&self) -> usize {
+        32
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Bytes
+    }
+
+    fn security_bits(&self) -> u32 {
+        128
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        let mut hasher = sha2::Sha256::new();
+        hasher.update(data);
+        Ok(hasher.finalize().to_vec())
+    }
+
+    fn snark_constraints(&self) -> Option<usize> {
+        Some(25_000)
+    }
+
+    fn r1cs_cost(&self, data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        let circuit = Sha256Circuit {
+            preimage: data.to_vec(),
+            public_digest: false,
+        };
+        circuits::measure::<Fr, _>(circuit)
+            .map(Some)
+            .map_err(|e| HashError::Synthesis(e.to_string()))
+    }
+
+    // One AIR row per round of each compression, plus one for the final
+    // feed-forward addition. A row holds the working variables a..h and the
+    // schedule's last 16 words as bits, so that b..d, f..h and the older
+    // words are copies of the previous row's, and 3-bit carries for the sums
+    // giving the new a, the new e and the next schedule word. Each bit of
+    // the Sigmas, Ch and Maj is a polynomial of degree at most 3 in bits.
+    fn air_cost(&self, data: &[u8]) -> Result<Option<AirCost>, HashError> {
+        let compressions = (data.len() + 9).div_ceil(64);
+        Ok(Some(AirCost {
+            width: 8 * 32 + 16 * 32 + 3 * 3,
+            rows: compressions * (64 + 1),
+            degree: air::DEGREE,
+            absorbed_bytes: compressions * 64,
+        }))
+    }
+
+    // The gadget's digest bits are big-endian within each byte
+    fn groth16(&self, data: &[u8], samples: usize) -> Result<Option<Groth16Cost>, HashError> {
+        let inputs = multipack::compute_multipacking(&multipack::bytes_to_bits(&self.hash(data)?));
+        let circuit = || Sha256Circuit {
+            preimage: data.to_vec(),
+            public_digest: true,
+        };
+        groth16::prove(circuit, &inputs, samples).map(Some)
+    }
+
+    // A node hashes its children's digests concatenated, as in Ethereum's
+    // deposit contract tree
+    fn merkle(&self, data: &[u8], settings: &MerkleSettings) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        let node = |children: &[Vec<u8>]| self.hash(&children.concat());
+        merkle::measure::<Fr, _>(&BitGadget::Sha256, |data| self.hash(data), node, data, settings).map(Some)
+    }
+
+    // Each step hashes the previous 32-byte state, one compression plus the
+    // padding block. The gadget works over any field, so Nova folds it over
+    // BN254 as well.
+    fn hash_chain(&self, data: &[u8], settings: &ChainSettings) -> Result<Option<ChainCost>, HashError> {
+        let node = |children: &[Vec<u8>]| self.hash(&children.concat());
+        let gadget = BitGadget::Sha256;
+        chain::measure::<Fr, _, _>(&gadget, Some(&gadget), |data| self.hash(data), node, data, settings).map(Some)
+    }
+
+    // FIPS 180-2 example
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        vec![KnownAnswer {
+            name: "\"abc\"",
+            expected: "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            actual: self.hash(b"abc").map(hex::encode).unwrap_or_default(),
+        }]
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "General-purpose cryptographic hashing",
+                "Bitcoin and legacy systems",
+            ],
+            bad_for: &["Not optimized for zkSNARKs (high constraint count)"],
+            ethereum_use: "Legacy systems",
+            best_for: "General purpose",
+        }
+    }
+}
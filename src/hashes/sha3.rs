@@ -0,0 +1,134 @@
+use blstrs::Scalar as Fr;
+use tiny_keccak::{Hasher, Sha3};
+
+use super::air::{self, AirCost};
+use super::{Domain, HashError, HashFunction, KnownAnswer, UseCases};
+use crate::circuits::{self, BitGadget, Keccak256Circuit, R1csCost};
+use crate::merkle::{self, MerkleCost, MerkleSettings};
+
+// Rate in bytes of both Keccak-256 and SHA3-256 (512-bit capacity)
+const RATE_BYTES: usize = 136;
+
+// First padding byte: FIPS 202 appends the suffix bits 01 to SHA-3 messages
+// before the Keccak pad10*1, the original submission Ethereum adopted does not
+const SHA3_DELIMITER: u8 = 0x06;
+const KECCAK_DELIMITER: u8 = 0x01;
+
+// The Keccak[512] sponge with a 32-byte output and the given first padding
+// byte, for checking that the padding is the only difference between the two
+fn sponge(data: &[u8], delimiter: u8) -> Vec<u8> {
+    let mut padded = data.to_vec();
+    padded.push(delimiter);
+    padded.resize(padded.len().div_ceil(RATE_BYTES) * RATE_BYTES, 0);
+    *padded.last_mut().expect("at least one padding byte") |= 0x80;
+
+    let mut state = [0u64; 25];
+    for block in padded.chunks(RATE_BYTES) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().expect("8-byte lane"));
+        }
+        tiny_keccak::keccakf(&mut state);
+    }
+    state[..4].iter().flat_map(|lane| lane.to_le_bytes()).collect()
+}
+
+// FIPS 202 SHA3-256: the Keccak-256 permutation and rate with different
+// padding, so its digests differ from Ethereum's keccak256
+pub struct Sha3_256;
+
+impl HashFunction for Sha3_256 {
+    fn name(&self) -> &'static str {
+        "SHA3-256"
+    }
+
+    fn output_size(&self) -> usize {
+        32
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Bytes
+    }
+
+    fn security_bits(&self) -> u32 {
+        128
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        let mut sha3 = Sha3::v256();
+        let mut output = [0u8; 32];
+        sha3.update(data);
+        sha3.finalize(&mut output);
+        Ok(output.to_vec())
+    }
+
+    // Keccak-256's: only the padding bits' values differ
+    fn snark_constraints(&self) -> Option<usize> {
+        Some(150_000)
+    }
+
+    fn r1cs_cost(&self, data: &[u8]) -> Result<Option<R1csCost>, HashError> {
+        let circuit = Keccak256Circuit {
+            preimage: data.to_vec(),
+            delimiter: SHA3_DELIMITER,
+            expected: self.hash(data)?,
+            public_digest: false,
+        };
+        circuits::measure::<Fr, _>(circuit)
+            .map(Some)
+            .map_err(|e| HashError::Synthesis(e.to_string()))
+    }
+
+    // Keccak-256's: the padding changes no cell
+    fn air_cost(&self, data: &[u8]) -> Result<Option<AirCost>, HashError> {
+        Ok(Some(air::keccak256(data)))
+    }
+
+    // A node hashes its children's digests concatenated
+    fn merkle(&self, data: &[u8], settings: &MerkleSettings) -> Result<Option<Vec<MerkleCost>>, HashError> {
+        let node = |children: &[Vec<u8>]| self.hash(&children.concat());
+        let gadget = BitGadget::Keccak256 { delimiter: SHA3_DELIMITER };
+        merkle::measure::<Fr, _>(&gadget, |data| self.hash(data), node, data, settings).map(Some)
+    }
+
+    // FIPS 202 examples, then one sponge run with each padding against the
+    // SHA3-256 and Keccak-256 digests of the empty input
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        vec![
+            KnownAnswer {
+                name: "\"abc\"",
+                expected: "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+                actual: self.hash(b"abc").map(hex::encode).unwrap_or_default(),
+            },
+            KnownAnswer {
+                name: "empty input",
+                expected: "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+                actual: self.hash(b"").map(hex::encode).unwrap_or_default(),
+            },
+            KnownAnswer {
+                name: "sponge with 0x06 padding = SHA3-256(\"\")",
+                expected: "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+                actual: hex::encode(sponge(b"", SHA3_DELIMITER)),
+            },
+            KnownAnswer {
+                name: "sponge with 0x01 padding = Keccak-256(\"\")",
+                expected: "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+                actual: hex::encode(sponge(b"", KECCAK_DELIMITER)),
+            },
+        ]
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "Standards-compliant hashing (FIPS 202)",
+                "Hardware implementations",
+            ],
+            bad_for: &[
+                "Very expensive in zkSNARKs",
+                "Not interchangeable with Ethereum's keccak256 (padding differs)",
+            ],
+            ethereum_use: "None (EVM uses Keccak)",
+            best_for: "Standards compliance",
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use sha2::Digest;
+
+use super::{Domain, HashError, HashFunction, KnownAnswer, UseCases};
+
+pub struct Sha512;
+
+impl HashFunction for Sha512 {
+    fn name(&self) -> &'static str {
+        "SHA-512"
+    }
+
+    fn output_size(&self) -> usize {
+        64
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Bytes
+    }
+
+    fn security_bits(&self) -> u32 {
+        256
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        let mut hasher = sha2::Sha512::new();
+        hasher.update(data);
+        Ok(hasher.finalize().to_vec())
+    }
+
+    // No gadget here: SHA-256's 25,000 scaled to 64-bit words and 80 rounds
+    fn snark_constraints(&self) -> Option<usize> {
+        Some(62_500)
+    }
+
+    fn snark_constraints_computed(&self) -> bool {
+        true
+    }
+
+    // FIPS 180-2 example
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        vec![KnownAnswer {
+            name: "\"abc\"",
+            expected: "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            actual: self.hash(b"abc").map(hex::encode).unwrap_or_default(),
+        }]
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "Fast on 64-bit CPUs",
+                "Ed25519 signatures and HMAC-SHA-512 key derivation",
+            ],
+            bad_for: &["Very expensive in zkSNARKs: 64-bit words"],
+            ethereum_use: "None",
+            best_for: "64-bit general purpose",
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::marker::PhantomData;
+
+use ff::PrimeField;
+use num_bigint::BigUint;
+use tiny_keccak::{Hasher, Shake, Xof};
+
+use crate::fields::{self, SmallPrime};
+
+// Field elements drawn from SHAKE128 of a seed string followed by the modulus
+// as little-endian u64 limbs, the derivation of the HorizenLabs/zkhash
+// instances. Each draw reads ceil(bits / 8) bytes as a little-endian integer,
+// masks the top byte to the field's bit length and rejects values >= p.
+pub struct ShakeSampler<F: PrimeField> {
+    shake: Shake,
+    modulus: BigUint,
+    _field: PhantomData<F>,
+}
+
+impl<F: PrimeField> ShakeSampler<F> {
+    pub fn new(seed: &str) -> Self {
+        let modulus = fields::modulus::<F>();
+        let mut shake = Shake::v128();
+        shake.update(seed.as_bytes());
+        for limb in modulus.to_u64_digits() {
+            shake.update(&limb.to_le_bytes());
+        }
+        ShakeSampler { shake, modulus, _field: PhantomData }
+    }
+
+    pub fn next(&mut self) -> F {
+        let bytes = (F::NUM_BITS as usize).div_ceil(8);
+        let mask = match F::NUM_BITS % 8 {
+            0 => 0xff,
+            bits => (1u8 << bits) - 1,
+        };
+        let mut buffer = vec![0u8; bytes];
+        loop {
+            self.shake.squeeze(&mut buffer);
+            buffer[bytes - 1] &= mask;
+            let value = BigUint::from_bytes_le(&buffer);
+            if value < self.modulus {
+                return fields::from_biguint(&value);
+            }
+        }
+    }
+
+    // As `next`, redrawing zero
+    pub fn next_nonzero(&mut self) -> F {
+        loop {
+            let value = self.next();
+            if !bool::from(value.is_zero()) {
+                return value;
+            }
+        }
+    }
+}
+
+// Elements of a word-sized field drawn from SHAKE128 of raw seed bytes, as in
+// the Plonky3 Monolith instances: each draw reads ceil(bits / 8) bytes as a
+// little-endian integer, unmasked, and rejects values >= p.
+pub struct SmallShakeSampler<F: SmallPrime> {
+    shake: Shake,
+    _field: PhantomData<F>,
+}
+
+impl<F: SmallPrime> SmallShakeSampler<F> {
+    pub fn new(seed: &[u8]) -> Self {
+        let mut shake = Shake::v128();
+        shake.update(seed);
+        SmallShakeSampler { shake, _field: PhantomData }
+    }
+
+    pub fn next(&mut self) -> u64 {
+        let mut buffer = [0u8; 8];
+        let bytes = F::bits().div_ceil(8);
+        loop {
+            self.shake.squeeze(&mut buffer[..bytes]);
+            let value = u64::from_le_bytes(buffer);
+            if value < F::MODULUS {
+                return value;
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::hint::black_box;
+
+use super::air::{self, AirCost};
+use super::plonk::{self, LookupCost, PlonkishCost};
+use super::poseidon_small::{encode_digest, pack_bytes};
+use super::shake::SmallShakeSampler;
+use super::{Domain, HashError, HashFunction, KnownAnswer, SetupPhase, UseCases};
+use crate::fields::{Goldilocks, SmallPrime};
+
+const WIDTH: usize = 16;
+const RATE: usize = 10;
+const ROUNDS: usize = 5;
+// Elements that go through the split-and-lookup S-box; the rest get x^7
+const SPLIT_AND_LOOKUP: usize = 4;
+const ALPHA: u64 = 7;
+const OUTPUT: usize = 5;
+const LIMBS: usize = 8;
+
+// First column of the circulant MDS matrix
+const MDS_FIRST_COLUMN: [u64; WIDTH] = [
+    61402, 1108, 28750, 33823, 7454, 43244, 53865, 12034, 56951, 27521, 41351, 40901, 12021, 59689, 26798, 17845,
+];
+
+// 2^64 modulo p, the Montgomery radix of Goldilocks
+const MONTGOMERY_R: u64 = 0xffff_ffff;
+
+struct Parameters {
+    // x -> (x + 1)^3 - 1 modulo 257 on bytes; it fixes 0 and 255
+    table: [u8; 256],
+    montgomery_r_inv: u64,
+    round_constants: Vec<[u64; WIDTH]>,
+}
+
+impl Parameters {
+    // The round constants come from `SmallShakeSampler` seeded with "Tip5"
+    // and the modulus; Triton VM's own constants were not available to
+    // check against
+    fn generate() -> Parameters {
+        let mut table = [0u8; 256];
+        for (x, entry) in table.iter_mut().enumerate() {
+            let y = (x as u64 + 1).pow(3) % 257;
+            *entry = ((y + 256) % 257) as u8;
+        }
+
+        let mut seed = b"Tip5".to_vec();
+        seed.extend_from_slice(&Goldilocks::MODULUS.to_le_bytes());
+        let mut sampler = SmallShakeSampler::<Goldilocks>::new(&seed);
+        let round_constants = (0..ROUNDS)
+            .map(|_| std::array::from_fn(|_| sampler.next()))
+            .collect();
+
+        Parameters {
+            table,
+            montgomery_r_inv: Goldilocks::inv(MONTGOMERY_R),
+            round_constants,
+        }
+    }
+}
+
+// Tip5 (Szepieniec, Lemmens, Sauer, Threadbare, Al-Kindi 2023), the hash of
+// Triton VM, over Goldilocks with t = 16, rate 10 and 5 rounds. Each round
+// applies the S-box layer, the circulant MDS matrix and the round constants.
+// The first 4 elements go through split-and-lookup: the element's Montgomery
+// form x 2^64 mod p is split into 8 bytes, each byte is replaced through an
+// 8-bit table, and the bytes are read back as a Montgomery form. The table
+// fixes 0 and 255, so the result stays below p. The other 12 elements get
+// x^7.
+//
+// Bytes are packed and absorbed as for Poseidon over Goldilocks (overwrite
+// mode). The digest is the first 5 elements, 40 bytes.
+pub struct Tip5 {
+    parameters: Parameters,
+}
+
+impl Tip5 {
+    pub fn new() -> Self {
+        Tip5 { parameters: Parameters::generate() }
+    }
+
+    fn split_and_lookup(&self, x: u64) -> u64 {
+        let bytes = Goldilocks::mul(x, MONTGOMERY_R).to_le_bytes();
+        let raw = u64::from_le_bytes(bytes.map(|b| self.parameters.table[b as usize]));
+        Goldilocks::mul(raw, self.parameters.montgomery_r_inv)
+    }
+
+    fn mds(state: &mut [u64; WIDTH]) {
+        let input = *state;
+        for (i, out) in state.iter_mut().enumerate() {
+            *out = input.iter().enumerate().fold(0, |acc, (j, &x)| {
+                Goldilocks::add(acc, Goldilocks::mul(MDS_FIRST_COLUMN[(WIDTH + i - j) % WIDTH], x))
+            });
+        }
+    }
+
+    fn permute(&self, state: &mut [u64; WIDTH]) {
+        for constants in &self.parameters.round_constants {
+            for x in &mut state[..SPLIT_AND_LOOKUP] {
+                *x = self.split_and_lookup(*x);
+            }
+            for x in &mut state[SPLIT_AND_LOOKUP..] {
+                let x2 = Goldilocks::mul(*x, *x);
+                let x3 = Goldilocks::mul(x2, *x);
+                *x = Goldilocks::mul(Goldilocks::mul(x3, x3), *x);
+            }
+            Self::mds(state);
+            state.iter_mut().zip(constants).for_each(|(x, &c)| *x = Goldilocks::add(*x, c));
+        }
+    }
+}
+
+impl HashFunction for Tip5 {
+    // Ends in `NON_STANDARD`: the round constants are not Triton VM's
+    fn name(&self) -> &'static str {
+        "Tip5-Goldilocks*"
+    }
+
+    fn output_size(&self) -> usize {
+        OUTPUT * Goldilocks::bits().div_ceil(8)
+    }
+
+    fn domain(&self) -> Domain {
+        Domain::Field(Goldilocks::NAME)
+    }
+
+    fn security_bits(&self) -> u32 {
+        128
+    }
+
+    fn parameters(&self) -> Option<String> {
+        Some(format!(
+            "t={}, rate {}, {} lookup S-boxes + x^{}, {} rounds, non-standard constants",
+            WIDTH, RATE, SPLIT_AND_LOOKUP, ALPHA, ROUNDS
+        ))
+    }
+
+    fn hash(&self, data: &[u8]) -> Result<Vec<u8>, HashError> {
+        let mut state = [0; WIDTH];
+        for block in pack_bytes::<Goldilocks>(data, RATE).chunks(RATE) {
+            state[..RATE].copy_from_slice(block);
+            self.permute(&mut state);
+        }
+        Ok(encode_digest::<Goldilocks>(&state[..OUTPUT]))
+    }
+
+    fn field_elements(&self, data: &[u8]) -> Result<Option<usize>, HashError> {
+        Ok(Some(pack_bytes::<Goldilocks>(data, RATE).len()))
+    }
+
+    fn setup_phases(&self) -> Vec<SetupPhase> {
+        vec![SetupPhase {
+            name: "SHAKE128 round constants",
+            run: Box::new(|| {
+                black_box(Parameters::generate());
+            }),
+        }]
+    }
+
+    // Not a SNARK-field hash: its arithmetic would have to be emulated in R1CS
+    fn snark_constraints(&self) -> Option<usize> {
+        None
+    }
+
+    // Per round, 8 input and 8 output byte cells for each lookup S-box and
+    // x^3 and x^7 for each power map at constraint degree 3
+    fn air_cost(&self, data: &[u8]) -> Result<Option<AirCost>, HashError> {
+        let per_round = SPLIT_AND_LOOKUP * 2 * LIMBS + (WIDTH - SPLIT_AND_LOOKUP) * 2;
+        let permutations = pack_bytes::<Goldilocks>(data, RATE).len() / RATE;
+        Ok(Some(AirCost {
+            width: WIDTH + ROUNDS * per_round,
+            rows: permutations,
+            degree: air::DEGREE,
+            absorbed_bytes: permutations * RATE * ((Goldilocks::bits() - 1) / 8),
+        }))
+    }
+
+    // Each lookup S-box splits the (scaled) element into 8 bytes and
+    // rebuilds it, with one lookup per byte; x^7 and the dense MDS matrix as
+    // in the PLONK model. The overwrite sponge absorbs for free.
+    fn lookup_cost(&self, data: &[u8]) -> Result<Option<LookupCost>, HashError> {
+        let split = 2 * plonk::decomposition_gates(LIMBS);
+        let per_round = SPLIT_AND_LOOKUP * split
+            + (WIDTH - SPLIT_AND_LOOKUP) * plonk::power_gates(ALPHA)
+            + plonk::dense_matrix_gates(WIDTH);
+        let permutations = pack_bytes::<Goldilocks>(data, RATE).len() / RATE;
+        Ok(Some(LookupCost {
+            gates: permutations * ROUNDS * per_round,
+            lookups: permutations * ROUNDS * SPLIT_AND_LOOKUP * LIMBS,
+            table_rows: self.parameters.table.len(),
+        }))
+    }
+
+    // One row per round holding the state and, for each lookup S-box, its
+    // input and output bytes; the power maps stay inline at degree 7
+    fn plonkish_cost(&self, data: &[u8]) -> Result<Option<PlonkishCost>, HashError> {
+        let permutations = pack_bytes::<Goldilocks>(data, RATE).len() / RATE;
+        Ok(Some(PlonkishCost {
+            advice_columns: WIDTH + SPLIT_AND_LOOKUP * 2 * LIMBS,
+            rows: plonk::sponge_rows(permutations, ROUNDS, false),
+            lookup_tables: 1,
+            table_rows: self.lookup_cost(data)?.map_or(0, |cost| cost.table_rows),
+            degree: plonk::gate_degree(ALPHA),
+        }))
+    }
+
+    // The table's definition in the specification, (x + 1)^3 - 1 modulo 257
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        vec![KnownAnswer {
+            name: "lookup table [0..8]",
+            expected: "0, 7, 26, 63, 124, 215, 85, 254",
+            actual: self.parameters.table[..8].iter().map(|b| b.to_string()).collect::<Vec<_>>().join(", "),
+        }]
+    }
+
+    fn use_cases(&self) -> UseCases {
+        UseCases {
+            good_for: &[
+                "STARK provers with lookup arguments (Triton VM)",
+                "Recursive verification: 5 rounds per permutation",
+            ],
+            bad_for: &["Circuits without lookups: the byte S-boxes need bit decomposition"],
+            ethereum_use: "STARK zkVMs",
+            best_for: "Lookup-based STARKs",
+        }
+    }
+}
@@ -1,163 +1,348 @@
-use sha2::{Sha256, Digest};
-use tiny_keccak::{Hasher, Keccak};
-use neptune::poseidon::PoseidonConstants;
-use blstrs::Scalar as Fr;
-use ff::PrimeField;
-use std::time::Instant;
-
-const NUM_ITERATIONS: usize = 1000;
-const INPUT_DATA: &[u8] = b"This is a test message.";
-
-// Hash function wrappers
-fn hash_sha256(data: &[u8]) -> Vec<u8> {
-    let mut hasher = Sha256::new();
-    hasher.update(data);
-    hasher.finalize().to_vec()
+mod bench;
+mod chain;
+mod circuits;
+mod cli;
+mod fields;
+mod groth16;
+mod hashes;
+mod merkle;
+mod nova;
+mod output;
+mod report;
+
+use bench::{BenchConfig, BenchResult, Iterations, Measurement};
+use circuits::R1csCost;
+use clap::Parser;
+use cli::{BenchArgs, ChainArgs, Cli, Command, MerkleArgs, OutputFormat, ProveArgs};
+use hashes::{HashError, HashFunction};
+use std::hint::black_box;
+use std::time::Duration;
+
+// Setup phases are expensive, so they are sampled one run at a time
+const SETUP_SAMPLES: usize = 10;
+
+// Benchmark function; the input is hashed once up front so a failing hash
+// is reported instead of timed
+fn benchmark_hash(hash: &dyn HashFunction, input: &[u8], config: &BenchConfig) -> Result<BenchResult, HashError> {
+    hash.hash(input)?;
+    Ok(bench::run(hash.name(), config, || hash.hash(black_box(input))))
 }
 
-fn hash_keccak256(data: &[u8]) -> Vec<u8> {
-    let mut keccak = Keccak::v256();
-    let mut output = [0u8; 32];
-    keccak.update(data);
-    keccak.finalize(&mut output);
-    output.to_vec()
+// One-off setup phases, reported separately from the per-hash numbers
+fn benchmark_setup(hash: &dyn HashFunction) -> Vec<BenchResult> {
+    let config = BenchConfig {
+        warmup: Duration::ZERO,
+        samples: SETUP_SAMPLES,
+        iterations: Iterations::Fixed(1),
+    };
+    hash.setup_phases()
+        .into_iter()
+        .map(|phase| bench::run(phase.name, &config, || (phase.run)()))
+        .collect()
 }
 
-fn hash_poseidon(data: &[u8]) -> Vec<u8> {
-    use neptune::Poseidon;
-    
-    let constants = PoseidonConstants::<Fr, typenum::U2>::new();
-    let mut p = Poseidon::<Fr, typenum::U2>::new(&constants);
-    
-    // Convert input data to field elements (simplified approach)
-    // We'll just take the first 31 bytes and convert to a field element
-    let mut bytes = [0u8; 32];
-    let len = data.len().min(31);
-    bytes[1..len+1].copy_from_slice(&data[..len]);
-    let input = Fr::from_repr(bytes.into()).unwrap_or_else(|| Fr::from(0u64));
-    
-    // Input and hash using Poseidon
-    p.input(input).unwrap();
-    let hash = p.hash();
-    
-    let mut result = [0u8; 32];
-    hash.to_repr().as_ref()[..32].iter().enumerate().for_each(|(i, &b)| result[i] = b);
-    result.to_vec()
+// Times every hash and its setup; progress is printed only for text output
+fn run_benchmarks(
+    registry: &[Box<dyn HashFunction>],
+    input: &[u8],
+    config: &BenchConfig,
+    text: bool,
+) -> Result<Vec<Measurement>, String> {
+    if text {
+        report::print_section("1. Performance Benchmarks");
+        println!();
+    }
+    let timings: Vec<BenchResult> = registry
+        .iter()
+        .map(|hash| {
+            let result = benchmark_hash(hash.as_ref(), input, config)
+                .map_err(|e| format!("{}: {}", hash.name(), e))?;
+            if text {
+                report::print_result(hash.name(), &result, input.len());
+            }
+            Ok(result)
+        })
+        .collect::<Result<_, String>>()?;
+
+    let setups: Vec<Vec<BenchResult>> = registry.iter().map(|hash| benchmark_setup(hash.as_ref())).collect();
+    if text && setups.iter().any(|phases| !phases.is_empty()) {
+        println!("\n  One-off setup (excluded from the per-hash figures above):\n");
+        for (hash, phases) in registry.iter().zip(&setups) {
+            for result in phases {
+                report::print_setup(hash.as_ref(), result);
+            }
+        }
+    }
+
+    Ok(timings
+        .into_iter()
+        .zip(setups)
+        .map(|(timing, setup)| Measurement {
+            timing: Some(timing),
+            setup,
+            groth16: None,
+            merkle: Vec::new(),
+            chain: None,
+            known_answers: Vec::new(),
+        })
+        .collect())
 }
 
-// Benchmark function
-fn benchmark_hash<F>(name: &str, hash_fn: F) -> u128 
-where
-    F: Fn(&[u8]) -> Vec<u8>
-{
-    let start = Instant::now();
-    for _ in 0..NUM_ITERATIONS {
-        let _ = hash_fn(INPUT_DATA);
+// Proves every hash with a BLS12-381 circuit, parallel to `registry`;
+// progress is printed only for text output
+fn run_proofs(registry: &[Box<dyn HashFunction>], input: &[u8], args: &ProveArgs, text: bool) -> Result<Vec<Measurement>, String> {
+    if args.samples == 0 {
+        return Err("--samples must be at least 1".to_string());
+    }
+    if text {
+        report::print_section("Groth16 over BLS12-381");
+        println!();
     }
-    let duration = start.elapsed();
-    
-    println!("  {} => {} ms ({} μs per hash)", 
-             name, 
-             duration.as_millis(),
-             duration.as_micros() / NUM_ITERATIONS as u128);
-    
-    duration.as_millis()
+    registry
+        .iter()
+        .map(|hash| {
+            let groth16 = hash.groth16(input, args.samples).map_err(|e| format!("{}: {}", hash.name(), e))?;
+            if let (true, Some(cost)) = (text, &groth16) {
+                report::print_proof(hash.name(), cost);
+            }
+            Ok(Measurement {
+                timing: None,
+                setup: Vec::new(),
+                groth16,
+                merkle: Vec::new(),
+                chain: None,
+                known_answers: Vec::new(),
+            })
+        })
+        .collect()
 }
 
-// SNARK constraint estimates (from literature)
-fn get_snark_constraints(hash_type: &str) -> usize {
-    match hash_type {
-        "SHA-256" => 25_000,
-        "Keccak-256" => 150_000,
-        "Poseidon" => 100,
-        _ => 0,
+// Measures every hash's Merkle membership circuit at each depth, parallel to
+// `registry`; progress is printed only for text output
+fn run_merkle(registry: &[Box<dyn HashFunction>], input: &[u8], args: &MerkleArgs, text: bool) -> Result<Vec<Measurement>, String> {
+    let settings = args.settings()?;
+    if text {
+        report::print_section(&format!("Merkle Membership (arity {})", settings.arity));
+        println!();
     }
+    registry
+        .iter()
+        .map(|hash| {
+            let costs = hash.merkle(input, &settings).map_err(|e| format!("{}: {}", hash.name(), e))?;
+            if let (true, Some(costs)) = (text, &costs) {
+                report::print_merkle(hash.name(), costs);
+            }
+            Ok(Measurement {
+                timing: None,
+                setup: Vec::new(),
+                groth16: None,
+                merkle: costs.unwrap_or_default(),
+                chain: None,
+                known_answers: Vec::new(),
+            })
+        })
+        .collect()
 }
 
-fn print_header() {
-    println!("\n{}", "=".repeat(70));
-    println!("    Ethereum Hash Function Comparison Framework");
-    println!("{}", "=".repeat(70));
-    println!("\nComparing traditional vs SNARK-friendly hash functions");
-    println!("Iterations: {}", NUM_ITERATIONS);
-    println!("{}\n", "=".repeat(70));
+// Measures the hash chain for every hash that has one, parallel to
+// `registry`; progress is printed only for text output
+fn run_chain(registry: &[Box<dyn HashFunction>], input: &[u8], args: &ChainArgs, text: bool) -> Result<Vec<Measurement>, String> {
+    let settings = args.settings()?;
+    if text {
+        report::print_section(&format!(
+            "Hash Chains ({} steps, {} hash{} per step)",
+            settings.steps,
+            settings.hashes_per_step,
+            if settings.hashes_per_step == 1 { "" } else { "es" }
+        ));
+        println!();
+    }
+    registry
+        .iter()
+        .map(|hash| {
+            let chain = hash.hash_chain(input, &settings).map_err(|e| format!("{}: {}", hash.name(), e))?;
+            if let (true, Some(cost)) = (text, &chain) {
+                report::print_chain(hash.name(), cost);
+            }
+            Ok(Measurement {
+                timing: None,
+                setup: Vec::new(),
+                groth16: None,
+                merkle: Vec::new(),
+                chain,
+                known_answers: Vec::new(),
+            })
+        })
+        .collect()
 }
 
-fn print_section(title: &str) {
-    println!("\n>>> {}", title);
-    println!("{}", "-".repeat(70));
+// Runs every hash's known-answer checks, parallel to `registry`
+fn run_known_answers(registry: &[Box<dyn HashFunction>]) -> Vec<Measurement> {
+    registry
+        .iter()
+        .map(|hash| Measurement {
+            timing: None,
+            setup: Vec::new(),
+            groth16: None,
+            merkle: Vec::new(),
+            chain: None,
+            known_answers: hash.known_answers(),
+        })
+        .collect()
 }
 
-fn print_use_cases() {
-    print_section("Use Case Recommendations");
-    
-    println!("\n  SHA-256:");
-    println!("    ✓ General-purpose cryptographic hashing");
-    println!("    ✓ Bitcoin and legacy systems");
-    println!("    ✗ Not optimized for zkSNARKs (high constraint count)");
-    
-    println!("\n  Keccak-256:");
-    println!("    ✓ Ethereum smart contracts (native opcode)");
-    println!("    ✓ Address generation and transaction hashing");
-    println!("    ✗ Very expensive in zkSNARKs");
-    
-    println!("\n  Poseidon:");
-    println!("    ✓ Zero-knowledge proof systems");
-    println!("    ✓ Rollups and Layer 2 solutions");
-    println!("    ✓ Privacy-preserving applications");
-    println!("    ✗ Not hardware-accelerated like SHA-256");
+// Synthesizes each hash's circuit over `input`, parallel to `registry`
+fn measure_circuits(registry: &[Box<dyn HashFunction>], input: &[u8]) -> Result<Vec<Option<R1csCost>>, String> {
+    registry
+        .iter()
+        .map(|hash| hash.r1cs_cost(input).map_err(|e| format!("{}: {}", hash.name(), e)))
+        .collect()
 }
 
-fn print_summary_table(sha_time: u128, keccak_time: u128, poseidon_time: u128) {
-    print_section("Summary Comparison Table");
-    
-    println!("\n  {:<15} {:<20} {:<20} {:<20}", 
-             "Property", "SHA-256", "Keccak-256", "Poseidon");
-    println!("  {}", "-".repeat(75));
-    println!("  {:<15} {:<20} {:<20} {:<20}", 
-             "Speed", 
-             format!("{} ms", sha_time),
-             format!("{} ms", keccak_time),
-             format!("{} ms", poseidon_time));
-    println!("  {:<15} {:<20} {:<20} {:<20}", 
-             "SNARK Cost",
-             "~25,000 constr.",
-             "~150,000 constr.",
-             "~100 constr.");
-    println!("  {:<15} {:<20} {:<20} {:<20}", 
-             "Ethereum Use",
-             "Legacy systems",
-             "Native (EVM)",
-             "zkApps/Rollups");
-    println!("  {:<15} {:<20} {:<20} {:<20}", 
-             "Best For",
-             "General purpose",
-             "Smart contracts",
-             "Zero-knowledge");
-    println!();
+// Applies --hashes, keeping registry order
+fn select(registry: Vec<Box<dyn HashFunction>>, names: &[String]) -> Result<Vec<Box<dyn HashFunction>>, String> {
+    if names.is_empty() {
+        return Ok(registry);
+    }
+    if let Some(unknown) = names.iter().find(|n| !registry.iter().any(|h| hashes::matches_name(h.as_ref(), n))) {
+        let known: Vec<&str> = registry.iter().map(|h| h.name()).collect();
+        return Err(format!("unknown hash '{}' (available: {})", unknown, known.join(", ")));
+    }
+    Ok(registry
+        .into_iter()
+        .filter(|h| names.iter().any(|n| hashes::matches_name(h.as_ref(), n)))
+        .collect())
+}
+
+fn run_text(command: Command, registry: &[Box<dyn HashFunction>], input: &[u8]) -> Result<(), String> {
+    match command {
+        Command::Report(args) => {
+            let config = args.config()?;
+            report::print_header(registry, input, Some(&config));
+
+            // 1. Performance Benchmarks
+            let measurements = run_benchmarks(registry, input, &config, true)?;
+
+            // 2. SNARK Constraint Analysis
+            let costs = measure_circuits(registry, input)?;
+            report::print_constraints("2. SNARK Constraint Estimates", registry, input, &costs)?;
+            report::print_air_costs(registry, input, &costs)?;
+            report::print_plonk_costs(registry, input)?;
+            report::print_lookup_costs(registry, input)?;
+            report::print_plonkish_costs(registry, input)?;
+            report::print_family_comparisons(registry, input, &measurements, &costs)?;
+
+            // 3. Use Cases
+            report::print_use_cases(registry);
+
+            // 4. Summary Table
+            report::print_summary_table(registry, input, &measurements, &costs)?;
+        }
+        Command::Bench(args) => {
+            let config = args.config()?;
+            report::print_header(registry, input, Some(&config));
+            run_benchmarks(registry, input, &config, true)?;
+        }
+        Command::Hash => {
+            report::print_section("Digests");
+            println!();
+            report::print_digests(registry, input)?;
+        }
+        Command::Sweep(args) => {
+            let config = args.config()?;
+            report::print_header(registry, input, Some(&config));
+            let measurements = run_benchmarks(registry, input, &config, false)?;
+            let costs = measure_circuits(registry, input)?;
+            report::print_sweep(registry, input, &measurements, &costs)?;
+        }
+        Command::Verify => {
+            report::print_section("Known-Answer Tests");
+            println!();
+            report::print_known_answers(registry)?;
+        }
+        Command::Prove(args) => {
+            report::print_header(registry, input, None);
+            let measurements = run_proofs(registry, input, &args, true)?;
+            report::print_proof_comparison(registry, &measurements);
+        }
+        Command::Merkle(args) => {
+            report::print_header(registry, input, None);
+            let measurements = run_merkle(registry, input, &args, true)?;
+            report::print_merkle_summary(registry, &measurements);
+        }
+        Command::Chain(args) => {
+            report::print_header(registry, input, None);
+            let measurements = run_chain(registry, input, &args, true)?;
+            report::print_chain_summary(registry, &measurements);
+        }
+        Command::Constraints => {
+            let costs = measure_circuits(registry, input)?;
+            report::print_constraints("SNARK Constraint Estimates", registry, input, &costs)?;
+            report::print_air_costs(registry, input, &costs)?;
+            report::print_plonk_costs(registry, input)?;
+            report::print_lookup_costs(registry, input)?;
+            report::print_plonkish_costs(registry, input)?;
+        }
+    }
+    Ok(())
+}
+
+fn run_structured(
+    command: Command,
+    registry: &[Box<dyn HashFunction>],
+    input: &[u8],
+    format: OutputFormat,
+) -> Result<(), String> {
+    let measurements = match command {
+        Command::Report(args) | Command::Bench(args) | Command::Sweep(args) => {
+            run_benchmarks(registry, input, &args.config()?, false)?
+        }
+        Command::Prove(args) => run_proofs(registry, input, &args, false)?,
+        Command::Merkle(args) => run_merkle(registry, input, &args, false)?,
+        Command::Chain(args) => run_chain(registry, input, &args, false)?,
+        Command::Verify => run_known_answers(registry),
+        Command::Hash | Command::Constraints => Vec::new(),
+    };
+
+    let report = output::Report::new(registry, input, &measurements)?;
+    let stdout = std::io::stdout().lock();
+    let written = match format {
+        OutputFormat::Json => report.write_json(stdout),
+        OutputFormat::Csv => report.write_csv(stdout),
+        OutputFormat::Text => unreachable!("text output is handled by run_text"),
+    };
+    written.map_err(|e| format!("cannot write output: {}", e))?;
+
+    // Failures are in the output too, but must not exit as a pass
+    let failed = measurements
+        .iter()
+        .flat_map(|m| &m.known_answers)
+        .filter(|answer| !answer.passed())
+        .count();
+    match failed {
+        0 => Ok(()),
+        n => Err(format!("{} known-answer check(s) failed", n)),
+    }
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    let input = cli.common.input.read()?;
+    let command = cli.command.unwrap_or(Command::Report(BenchArgs::default()));
+    let registry = match command {
+        Command::Sweep(_) => hashes::poseidon_sweep(&cli.common.poseidon_field),
+        _ => hashes::registry(&cli.common.registry_config()),
+    };
+    let registry = select(registry.map_err(|e| e.to_string())?, &cli.common.hashes)?;
+
+    match cli.common.format {
+        OutputFormat::Text => run_text(command, &registry, &input),
+        format => run_structured(command, &registry, &input, format),
+    }
 }
 
 fn main() {
-    print_header();
-    
-    // 1. Performance Benchmarks
-    print_section("1. Performance Benchmarks");
-    println!();
-    let sha_time = benchmark_hash("SHA-256   ", hash_sha256);
-    let keccak_time = benchmark_hash("Keccak-256", hash_keccak256);
-    let poseidon_time = benchmark_hash("Poseidon  ", hash_poseidon);
-    
-    // 2. SNARK Constraint Analysis
-    print_section("2. SNARK Constraint Estimates");
-    println!("\n  (Lower is better for zero-knowledge proofs)\n");
-    println!("  SHA-256    => ~{:>6} constraints", get_snark_constraints("SHA-256"));
-    println!("  Keccak-256 => ~{:>6} constraints", get_snark_constraints("Keccak-256"));
-    println!("  Poseidon   => ~{:>6} constraints (250x better!)", get_snark_constraints("Poseidon"));
-    
-    // 3. Use Cases
-    print_use_cases();
-    
-    // 4. Summary Table
-    print_summary_table(sha_time, keccak_time, poseidon_time);
-    
-}
\ No newline at end of file
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,137 @@
+use std::hint::black_box;
+
+use crate::bench::{self, BenchConfig, BenchResult};
+use crate::circuits::{self, MerkleCircuit, MerkleLevel, NodeGadget, R1csCost};
+use crate::fields::{self, NamedField};
+use crate::groth16::Groth16Cost;
+use crate::hashes::HashError;
+
+// Tree shapes to measure, and how much proving to do for each
+#[derive(Clone, Debug)]
+pub struct MerkleSettings {
+    pub depths: Vec<usize>,
+    // Children per node, at least 2
+    pub arity: usize,
+    // Native path verification
+    pub native: BenchConfig,
+    // Larger circuits are synthesized but not proved; 0 proves none
+    pub max_proof_constraints: usize,
+    pub proof_samples: usize,
+}
+
+// Cost of proving membership in a tree of one depth
+#[derive(Clone, Debug)]
+pub struct MerkleCost {
+    pub depth: usize,
+    pub arity: usize,
+    pub r1cs: R1csCost,
+    // Recomputing the root from the leaf and its path natively
+    pub native: BenchResult,
+    // None above `max_proof_constraints` or over a field without Groth16
+    pub groth16: Option<Groth16Cost>,
+}
+
+// A node's children's digests as field elements, for field hashes whose
+// nodes hash one element per child
+pub fn elements<F: NamedField>(children: &[Vec<u8>]) -> Result<Vec<F>, HashError> {
+    children
+        .iter()
+        .map(|child| {
+            fields::from_le_bytes(child).ok_or_else(|| HashError::NonCanonicalEncoding {
+                field: F::NAME,
+                bytes: child.clone(),
+            })
+        })
+        .collect()
+}
+
+// A leaf, its path up the tree and the tree's root, in native digests
+struct Path {
+    leaf: Vec<u8>,
+    // Leaf level first
+    levels: Vec<MerkleLevel>,
+    root: Vec<u8>,
+}
+
+// The root recomputed from `leaf` up its path
+fn root(node: &impl Fn(&[Vec<u8>]) -> Result<Vec<u8>, HashError>, leaf: &[u8], levels: &[MerkleLevel]) -> Result<Vec<u8>, HashError> {
+    levels.iter().try_fold(leaf.to_vec(), |current, level| {
+        let mut children = level.children.clone();
+        children[level.position] = current;
+        node(&children)
+    })
+}
+
+// A path of `depth` levels from the leaf `digest(input)`. The path's node
+// takes slot `level mod arity`, so every slot is exercised, and its siblings
+// are digests of fixed labels.
+fn path(
+    digest: &impl Fn(&[u8]) -> Result<Vec<u8>, HashError>,
+    node: &impl Fn(&[Vec<u8>]) -> Result<Vec<u8>, HashError>,
+    input: &[u8],
+    depth: usize,
+    arity: usize,
+) -> Result<Path, HashError> {
+    let leaf = digest(input)?;
+    let mut current = leaf.clone();
+    let mut levels = Vec::with_capacity(depth);
+    for l in 0..depth {
+        let position = l % arity;
+        let children = (0..arity)
+            .map(|slot| match slot == position {
+                true => Ok(current.clone()),
+                false => digest(format!("level {} slot {}", l, slot).as_bytes()),
+            })
+            .collect::<Result<Vec<_>, HashError>>()?;
+        current = node(&children)?;
+        levels.push(MerkleLevel { position, children });
+    }
+    Ok(Path {
+        leaf,
+        levels,
+        root: current,
+    })
+}
+
+// Membership of `input`'s digest in a tree at each of `settings.depths`:
+// the circuit's size, the native path verification time, and Groth16 where
+// the field has a curve here and the circuit is small enough. `digest` is
+// the hash itself and `node` hashes a node's children as `gadget` does.
+pub fn measure<F, G>(
+    gadget: &G,
+    digest: impl Fn(&[u8]) -> Result<Vec<u8>, HashError>,
+    node: impl Fn(&[Vec<u8>]) -> Result<Vec<u8>, HashError>,
+    input: &[u8],
+    settings: &MerkleSettings,
+) -> Result<Vec<MerkleCost>, HashError>
+where
+    F: NamedField,
+    G: NodeGadget<F>,
+{
+    settings
+        .depths
+        .iter()
+        .map(|&depth| {
+            let path = path(&digest, &node, input, depth, settings.arity)?;
+            let circuit = || MerkleCircuit {
+                gadget,
+                leaf: path.leaf.clone(),
+                levels: path.levels.clone(),
+                root: path.root.clone(),
+            };
+            let r1cs = circuits::measure::<F, _>(circuit()).map_err(|e| HashError::Synthesis(e.to_string()))?;
+            let native = bench::run("merkle path", &settings.native, || root(&node, black_box(&path.leaf), black_box(&path.levels)));
+            let groth16 = match r1cs.constraints <= settings.max_proof_constraints {
+                true => F::groth16(circuit, &gadget.public_inputs(&path.root), settings.proof_samples)?,
+                false => None,
+            };
+            Ok(MerkleCost {
+                depth,
+                arity: settings.arity,
+                r1cs,
+                native,
+                groth16,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,200 @@
+use std::time::{Duration, Instant};
+
+use bellpepper_core::num::AllocatedNum;
+use bellpepper_core::{Circuit, ConstraintSystem, SynthesisError};
+use ff::Field;
+use nova_snark::errors::NovaError;
+use nova_snark::provider::{hyperkzg, ipa_pc, Bn256EngineKZG, GrumpkinEngine};
+use nova_snark::spartan::snark::RelaxedR1CSSNARK;
+use nova_snark::traits::circuit::{StepCircuit, TrivialCircuit};
+use nova_snark::traits::snark::RelaxedR1CSSNARKTrait;
+use nova_snark::traits::Engine;
+use nova_snark::{CompressedSNARK, PublicParams, RecursiveSNARK};
+
+use crate::bench::{self, BenchConfig, BenchResult, Iterations, Stats};
+use crate::circuits::{self, NodeGadget, R1csCost};
+use crate::hashes::HashError;
+
+// Nova over the BN254/Grumpkin cycle, as in nova-snark's examples: the step
+// runs in the primary circuit over BN254's scalar field, the secondary
+// circuit only verifies, and the compressed proof is Spartan over HyperKZG
+// (primary) and IPA (secondary)
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+type S1 = RelaxedR1CSSNARK<E1, hyperkzg::EvaluationEngine<E1>>;
+type S2 = RelaxedR1CSSNARK<E2, ipa_pc::EvaluationEngine<E2>>;
+type Secondary = TrivialCircuit<<E2 as Engine>::Scalar>;
+
+// BN254's scalar field in nova-snark's representation, the same field as
+// `fields::Bn254Fr`
+pub type Scalar = <E1 as Engine>::Scalar;
+
+// Cost of folding a hash chain with Nova, one chain step per folding step
+#[derive(Clone, Debug)]
+pub struct NovaCost {
+    pub steps: usize,
+    // The step alone, its state in and out as witnesses
+    pub step: R1csCost,
+    // The primary circuit folded per step: the step plus Nova's verifier
+    pub augmented_constraints: usize,
+    // The recursive verifier's share of the primary circuit
+    pub verifier_constraints: usize,
+    // The secondary circuit over Grumpkin, a verifier around an empty step
+    pub secondary_constraints: usize,
+    // A single run: the public parameters, commitment keys included
+    pub setup: BenchResult,
+    // One sample per folded step; the first step is the base case, which
+    // folds nothing
+    pub fold: BenchResult,
+    // A single run of the recursive SNARK's verifier
+    pub verify: BenchResult,
+    // A single run: the compressed SNARK's keys
+    pub compress_setup: BenchResult,
+    pub compress: BenchResult,
+    pub compressed_verify: BenchResult,
+    pub compressed_proof_bytes: usize,
+}
+
+// `hashes_per_step` hashes of the state, carried between steps as
+// `pack_state`'s elements
+struct Step<'a, G> {
+    gadget: &'a G,
+    hashes_per_step: usize,
+    arity: usize,
+}
+
+impl<G> Clone for Step<'_, G> {
+    fn clone(&self) -> Self {
+        Step { ..*self }
+    }
+}
+
+impl<G: NodeGadget<Scalar> + Sync> StepCircuit<Scalar> for Step<'_, G> {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn synthesize<CS: ConstraintSystem<Scalar>>(&self, cs: &mut CS, z: &[AllocatedNum<Scalar>]) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        let mut state = self.gadget.unpack_state(cs.namespace(|| "state in"), z)?;
+        for i in 0..self.hashes_per_step {
+            state = self.gadget.hash(cs.namespace(|| format!("hash {}", i)), std::slice::from_ref(&state))?;
+        }
+        self.gadget.pack_state(cs.namespace(|| "state out"), &state)
+    }
+}
+
+// The step on its own, with its state in allocated as Nova's augmented
+// circuit allocates it, to separate the step's size from the verifier's
+struct StandaloneStep<'a, G> {
+    step: Step<'a, G>,
+    start: &'a [Scalar],
+}
+
+impl<G: NodeGadget<Scalar> + Sync> Circuit<Scalar> for StandaloneStep<'_, G> {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let z = self
+            .start
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| AllocatedNum::alloc(cs.namespace(|| format!("z {}", i)), || Ok(value)))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.step.synthesize(cs, &z).map(|_| ())
+    }
+}
+
+fn nova_error(e: NovaError) -> HashError {
+    HashError::Proof(e.to_string())
+}
+
+// The final state a proof verified to must be the native chain's
+fn check_end(zn: &[Scalar], end: &[Scalar]) -> Result<(), HashError> {
+    match zn == end {
+        true => Ok(()),
+        false => Err(HashError::Proof("Nova's final state differs from the native chain's".to_string())),
+    }
+}
+
+// Folds `steps` steps of `hashes_per_step` hashes each from the state
+// `start` (`public_inputs` of the first digest), timing every fold, then
+// compresses the result and times `samples` compressed proofs and as many
+// verifications after a warmup run of each. Both proofs must verify to
+// `end`, the native chain's last state. Needs at least two steps, the first
+// being the base case.
+pub fn prove<G: NodeGadget<Scalar> + Sync>(
+    gadget: &G,
+    hashes_per_step: usize,
+    steps: usize,
+    start: &[Scalar],
+    end: &[Scalar],
+    samples: usize,
+) -> Result<NovaCost, HashError> {
+    let step = Step { gadget, hashes_per_step, arity: start.len() };
+    let secondary = Secondary::default();
+    let step_r1cs = circuits::measure(StandaloneStep { step: step.clone(), start })
+        .map_err(|e| HashError::Synthesis(e.to_string()))?;
+
+    let timer = Instant::now();
+    let pp = PublicParams::<E1, E2, Step<'_, G>, Secondary>::setup(&step, &secondary, &*S1::ck_floor(), &*S2::ck_floor())
+        .map_err(nova_error)?;
+    let setup = bench::single_run("nova setup", timer.elapsed());
+    let (augmented_constraints, secondary_constraints) = pp.num_constraints();
+
+    let secondary_start = [<E2 as Engine>::Scalar::ZERO];
+    let mut recursive = RecursiveSNARK::new(&pp, &step, &secondary, start, &secondary_start).map_err(nova_error)?;
+    // `new` proves the base case; the first `prove_step` only records it
+    recursive.prove_step(&pp, &step, &secondary).map_err(nova_error)?;
+    let folds = (1..steps)
+        .map(|_| {
+            let timer = Instant::now();
+            recursive.prove_step(&pp, &step, &secondary).map(|()| timer.elapsed().as_nanos() as f64)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(nova_error)?;
+    let fold = BenchResult {
+        name: "nova fold".to_string(),
+        samples: folds.len(),
+        iterations_per_sample: 1,
+        stats: Stats::from_samples(folds),
+    };
+
+    let timer = Instant::now();
+    let (zn, _) = recursive.verify(&pp, steps, start, &secondary_start).map_err(nova_error)?;
+    let verify = bench::single_run("nova verify", timer.elapsed());
+    check_end(&zn, end)?;
+
+    let timer = Instant::now();
+    let (pk, vk) = CompressedSNARK::<_, _, _, _, S1, S2>::setup(&pp).map_err(nova_error)?;
+    let compress_setup = bench::single_run("nova compress setup", timer.elapsed());
+
+    let config = BenchConfig {
+        warmup: Duration::ZERO,
+        samples,
+        iterations: Iterations::Fixed(1),
+    };
+    let mut compressed = None;
+    let compress = bench::run("nova compress", &config, || {
+        compressed = Some(CompressedSNARK::<_, _, _, _, S1, S2>::prove(&pp, &pk, &recursive));
+    });
+    let compressed = compressed.expect("bench::run calls the closure").map_err(nova_error)?;
+
+    let mut verified = Err(NovaError::ProofVerifyError);
+    let compressed_verify = bench::run("nova compressed verify", &config, || {
+        verified = compressed.verify(&vk, steps, start, &secondary_start);
+    });
+    check_end(&verified.map_err(nova_error)?.0, end)?;
+
+    Ok(NovaCost {
+        steps,
+        step: step_r1cs,
+        augmented_constraints,
+        verifier_constraints: augmented_constraints.saturating_sub(step_r1cs.constraints),
+        secondary_constraints,
+        setup,
+        fold,
+        verify,
+        compress_setup,
+        compress,
+        compressed_verify,
+        compressed_proof_bytes: bincode::serialize(&compressed).map_err(|e| HashError::Proof(e.to_string()))?.len(),
+    })
+}
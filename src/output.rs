@@ -0,0 +1,605 @@
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::bench::{BenchResult, Measurement, Stats};
+use crate::chain::ChainCost;
+use crate::circuits::R1csCost;
+use crate::groth16::Groth16Cost;
+use crate::hashes::{AirCost, HashFunction, KnownAnswer, LookupCost, PlonkishCost};
+use crate::merkle::MerkleCost;
+use crate::nova::NovaCost;
+
+// Bump on any breaking change to the records below (renamed or removed
+// fields, changed units, a field becoming nullable, CSV columns moving).
+// Adding JSON fields, or CSV columns at the end of the row, does not.
+//
+// 2: `snark_constraints` is nullable, and every CSV column added since 1
+// follows the timing columns
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Serialize)]
+pub struct Report {
+    pub schema_version: u32,
+    pub tool_version: &'static str,
+    pub generated_at_unix: u64,
+    pub environment: Environment,
+    pub input_bytes: usize,
+    pub hashes: Vec<HashRecord>,
+}
+
+#[derive(Serialize)]
+pub struct Environment {
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub logical_cpus: usize,
+    pub debug_build: bool,
+}
+
+#[derive(Serialize)]
+pub struct HashRecord {
+    pub name: &'static str,
+    pub parameters: Option<String>,
+    pub domain: String,
+    pub output_size: usize,
+    pub security_bits: u32,
+    pub digest: String,
+    // Field elements the input is encoded into; null for byte-oriented hashes
+    pub field_elements: Option<usize>,
+    // Null for hashes not meant for a SNARK field
+    pub snark_constraints: Option<usize>,
+    // Whether `snark_constraints` was counted by this tool rather than
+    // published
+    pub snark_constraints_computed: bool,
+    // Estimated STARK trace size; null for hashes without an AIR layout
+    pub air_trace_cells: Option<usize>,
+    // The trace's shape, and its cells per absorbed byte
+    pub air_cost: Option<AirCost>,
+    pub air_cells_per_byte: Option<f64>,
+    // Estimated vanilla PLONK gates; null for hashes without an estimate
+    pub plonk_gates: Option<usize>,
+    // Gates plus lookups with a lookup argument; null for hashes without tables
+    pub lookup_cost: Option<LookupCost>,
+    // halo2-style layout; null for hashes without one
+    pub plonkish_cost: Option<PlonkishCost>,
+    // Measured by synthesizing the circuit over the input; null without a gadget
+    pub r1cs: Option<R1csCost>,
+    pub timing: Option<TimingRecord>,
+    // Input bytes per second at the mean time, in MB/s; null when untimed
+    // or for an empty input
+    pub throughput_mb_s: Option<f64>,
+    pub setup: Vec<SetupRecord>,
+    // Only from `prove`; null for hashes without a BLS12-381 circuit
+    pub groth16: Option<Groth16Record>,
+    // Only from `merkle`, one per depth; empty for hashes without a node gadget
+    pub merkle: Vec<MerkleRecord>,
+    // Only from `chain`; null for hashes without a chain benchmark
+    pub chain: Option<ChainRecord>,
+    // Only from `verify`; empty for hashes without reference vectors
+    pub known_answers: Vec<KnownAnswerRecord>,
+}
+
+// All times in nanoseconds per iteration
+#[derive(Serialize)]
+pub struct TimingRecord {
+    pub samples: usize,
+    pub iterations_per_sample: usize,
+    pub mean_ns: f64,
+    pub median_ns: f64,
+    pub stddev_ns: f64,
+    pub min_ns: f64,
+    pub max_ns: f64,
+    pub p95_ns: f64,
+    pub p99_ns: f64,
+    pub ci95_low_ns: f64,
+    pub ci95_high_ns: f64,
+}
+
+// Groth16 over BLS12-381 with the digest public; sizes in bytes as bellman
+// serializes them
+#[derive(Serialize)]
+pub struct Groth16Record {
+    pub r1cs: R1csCost,
+    pub setup: TimingRecord,
+    pub prove: TimingRecord,
+    pub verify: TimingRecord,
+    pub proof_bytes: usize,
+    pub proving_key_bytes: usize,
+    pub verifying_key_bytes: usize,
+}
+
+// Membership in a tree of one depth; `native` times recomputing the root
+#[derive(Serialize)]
+pub struct MerkleRecord {
+    pub depth: usize,
+    pub arity: usize,
+    pub r1cs: R1csCost,
+    pub native: TimingRecord,
+    // Null when not proved
+    pub groth16: Option<Groth16Record>,
+}
+
+// A hash chain: `step` is one step's circuit, `chain` every step in one
+// circuit, and `native_step` times one step
+#[derive(Serialize)]
+pub struct ChainRecord {
+    pub steps: usize,
+    pub hashes_per_step: usize,
+    pub step: R1csCost,
+    pub chain: R1csCost,
+    pub native_step: TimingRecord,
+    // The whole chain; null when not proved
+    pub groth16: Option<Groth16Record>,
+    // Null when not folded
+    pub nova: Option<NovaRecord>,
+}
+
+// The chain folded with Nova, one chain step per folding step: `step` is the
+// step alone, the verifier Nova adds to it is `verifier_constraints`, and
+// `fold` has one sample per folded step
+#[derive(Serialize)]
+pub struct NovaRecord {
+    pub steps: usize,
+    pub step: R1csCost,
+    pub augmented_constraints: usize,
+    pub verifier_constraints: usize,
+    pub secondary_constraints: usize,
+    pub setup: TimingRecord,
+    pub fold: TimingRecord,
+    pub verify: TimingRecord,
+    pub compress_setup: TimingRecord,
+    pub compress: TimingRecord,
+    pub compressed_verify: TimingRecord,
+    pub compressed_proof_bytes: usize,
+}
+
+// A reference vector and what the hash produced for it
+#[derive(Serialize)]
+pub struct KnownAnswerRecord {
+    pub name: &'static str,
+    pub expected: &'static str,
+    pub actual: String,
+    pub passed: bool,
+}
+
+#[derive(Serialize)]
+pub struct SetupRecord {
+    pub phase: String,
+    pub timing: TimingRecord,
+}
+
+// Flat CSV row: one per hash timing, setup phase and Groth16 stage
+#[derive(Serialize)]
+struct CsvRow<'a> {
+    schema_version: u32,
+    tool_version: &'a str,
+    generated_at_unix: u64,
+    os: &'a str,
+    arch: &'a str,
+    logical_cpus: usize,
+    input_bytes: usize,
+    name: &'a str,
+    parameters: Option<&'a str>,
+    domain: &'a str,
+    output_size: usize,
+    security_bits: u32,
+    snark_constraints: Option<usize>,
+    // "hash" for the per-hash timing, "groth16 setup", "groth16 prove" or
+    // "groth16 verify", "merkle path" and, once proved, "merkle setup",
+    // "merkle prove" or "merkle verify" per depth, "chain step" and, once
+    // proved, "chain setup", "chain prove" or "chain verify" and, once
+    // folded, "nova setup", "nova fold", "nova verify", "nova compress
+    // setup", "nova compress" or "nova compressed verify", "known answer"
+    // per reference vector, otherwise the setup phase name
+    measurement: &'a str,
+    samples: Option<usize>,
+    iterations_per_sample: Option<usize>,
+    mean_ns: Option<f64>,
+    median_ns: Option<f64>,
+    stddev_ns: Option<f64>,
+    min_ns: Option<f64>,
+    max_ns: Option<f64>,
+    p95_ns: Option<f64>,
+    p99_ns: Option<f64>,
+    ci95_low_ns: Option<f64>,
+    ci95_high_ns: Option<f64>,
+    // Columns from here on were added after schema 1, in the order they were
+    // added; new ones go last
+    r1cs_constraints: Option<usize>,
+    r1cs_variables: Option<usize>,
+    r1cs_nonzero_entries: Option<usize>,
+    field_elements: Option<usize>,
+    air_trace_cells: Option<usize>,
+    plonk_gates: Option<usize>,
+    lookup_gates: Option<usize>,
+    lookups: Option<usize>,
+    lookup_table_rows: Option<usize>,
+    // Only on the "hash" row
+    throughput_mb_s: Option<f64>,
+    // Only on the "groth16 *" rows and the proved "merkle *" and "chain *" rows
+    proof_bytes: Option<usize>,
+    proving_key_bytes: Option<usize>,
+    verifying_key_bytes: Option<usize>,
+    // Only on the "merkle *" rows
+    merkle_depth: Option<usize>,
+    merkle_arity: Option<usize>,
+    merkle_constraints: Option<usize>,
+    // Only on the "chain *" rows
+    chain_steps: Option<usize>,
+    chain_hashes_per_step: Option<usize>,
+    chain_step_constraints: Option<usize>,
+    // Per hash again, on every row
+    plonkish_advice_columns: Option<usize>,
+    plonkish_rows: Option<usize>,
+    plonkish_lookup_tables: Option<usize>,
+    plonkish_table_rows: Option<usize>,
+    plonkish_degree: Option<usize>,
+    air_trace_width: Option<usize>,
+    air_rows: Option<usize>,
+    air_degree: Option<usize>,
+    air_cells_per_byte: Option<f64>,
+    // Only on the "known answer" rows
+    known_answer: Option<&'a str>,
+    known_answer_passed: Option<bool>,
+    // Per hash again, on every row
+    snark_constraints_computed: bool,
+    // Only on the "nova *" rows
+    nova_step_constraints: Option<usize>,
+    nova_augmented_constraints: Option<usize>,
+    nova_verifier_constraints: Option<usize>,
+    nova_secondary_constraints: Option<usize>,
+    nova_compressed_proof_bytes: Option<usize>,
+}
+
+// What a CSV row measured, before the per-hash columns are added
+struct Row<'a> {
+    measurement: &'a str,
+    timing: Option<&'a TimingRecord>,
+    groth16: Option<&'a Groth16Record>,
+    merkle: Option<&'a MerkleRecord>,
+    chain: Option<&'a ChainRecord>,
+    nova: Option<&'a NovaRecord>,
+    known_answer: Option<&'a KnownAnswerRecord>,
+}
+
+impl<'a> Row<'a> {
+    fn timed(measurement: &'a str, timing: &'a TimingRecord) -> Row<'a> {
+        Row {
+            measurement,
+            timing: Some(timing),
+            groth16: None,
+            merkle: None,
+            chain: None,
+            nova: None,
+            known_answer: None,
+        }
+    }
+
+    // Setup, prove and verify rows of a proof, under `names`
+    fn proof(names: [&'a str; 3], proof: &'a Groth16Record, merkle: Option<&'a MerkleRecord>) -> [Row<'a>; 3] {
+        let timings = [&proof.setup, &proof.prove, &proof.verify];
+        std::array::from_fn(|i| Row {
+            measurement: names[i],
+            timing: Some(timings[i]),
+            groth16: Some(proof),
+            merkle,
+            chain: None,
+            nova: None,
+            known_answer: None,
+        })
+    }
+}
+
+impl Environment {
+    fn current() -> Environment {
+        Environment {
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            logical_cpus: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            debug_build: cfg!(debug_assertions),
+        }
+    }
+}
+
+impl TimingRecord {
+    fn from_result(result: &BenchResult) -> TimingRecord {
+        let Stats { mean, median, stddev, min, max, p95, p99, ci95_low, ci95_high } = result.stats;
+        TimingRecord {
+            samples: result.samples,
+            iterations_per_sample: result.iterations_per_sample,
+            mean_ns: mean,
+            median_ns: median,
+            stddev_ns: stddev,
+            min_ns: min,
+            max_ns: max,
+            p95_ns: p95,
+            p99_ns: p99,
+            ci95_low_ns: ci95_low,
+            ci95_high_ns: ci95_high,
+        }
+    }
+}
+
+impl Groth16Record {
+    fn from_cost(cost: &Groth16Cost) -> Groth16Record {
+        Groth16Record {
+            r1cs: cost.r1cs,
+            setup: TimingRecord::from_result(&cost.setup),
+            prove: TimingRecord::from_result(&cost.prove),
+            verify: TimingRecord::from_result(&cost.verify),
+            proof_bytes: cost.proof_bytes,
+            proving_key_bytes: cost.proving_key_bytes,
+            verifying_key_bytes: cost.verifying_key_bytes,
+        }
+    }
+}
+
+impl MerkleRecord {
+    fn from_cost(cost: &MerkleCost) -> MerkleRecord {
+        MerkleRecord {
+            depth: cost.depth,
+            arity: cost.arity,
+            r1cs: cost.r1cs,
+            native: TimingRecord::from_result(&cost.native),
+            groth16: cost.groth16.as_ref().map(Groth16Record::from_cost),
+        }
+    }
+}
+
+impl ChainRecord {
+    fn from_cost(cost: &ChainCost) -> ChainRecord {
+        ChainRecord {
+            steps: cost.steps,
+            hashes_per_step: cost.hashes_per_step,
+            step: cost.step,
+            chain: cost.chain,
+            native_step: TimingRecord::from_result(&cost.native_step),
+            groth16: cost.groth16.as_ref().map(Groth16Record::from_cost),
+            nova: cost.nova.as_ref().map(NovaRecord::from_cost),
+        }
+    }
+}
+
+impl NovaRecord {
+    fn from_cost(cost: &NovaCost) -> NovaRecord {
+        NovaRecord {
+            steps: cost.steps,
+            step: cost.step,
+            augmented_constraints: cost.augmented_constraints,
+            verifier_constraints: cost.verifier_constraints,
+            secondary_constraints: cost.secondary_constraints,
+            setup: TimingRecord::from_result(&cost.setup),
+            fold: TimingRecord::from_result(&cost.fold),
+            verify: TimingRecord::from_result(&cost.verify),
+            compress_setup: TimingRecord::from_result(&cost.compress_setup),
+            compress: TimingRecord::from_result(&cost.compress),
+            compressed_verify: TimingRecord::from_result(&cost.compressed_verify),
+            compressed_proof_bytes: cost.compressed_proof_bytes,
+        }
+    }
+}
+
+impl KnownAnswerRecord {
+    fn from_answer(answer: &KnownAnswer) -> KnownAnswerRecord {
+        KnownAnswerRecord {
+            name: answer.name,
+            expected: answer.expected,
+            actual: answer.actual.clone(),
+            passed: answer.passed(),
+        }
+    }
+}
+
+impl Report {
+    // `measurements` is either empty (nothing timed) or parallel to `registry`
+    pub fn new(registry: &[Box<dyn HashFunction>], input: &[u8], measurements: &[Measurement]) -> Result<Report, String> {
+        let hashes = registry
+            .iter()
+            .enumerate()
+            .map(|(i, hash)| {
+                let measurement = measurements.get(i);
+                let digest = hash.hash(input).map_err(|e| format!("{}: {}", hash.name(), e))?;
+                let air_cost = hash.air_cost(input).map_err(|e| format!("{}: {}", hash.name(), e))?;
+                Ok(HashRecord {
+                    name: hash.name(),
+                    parameters: hash.parameters(),
+                    domain: hash.domain().to_string(),
+                    output_size: hash.output_size(),
+                    security_bits: hash.security_bits(),
+                    digest: hex::encode(digest),
+                    field_elements: hash.field_elements(input).map_err(|e| format!("{}: {}", hash.name(), e))?,
+                    snark_constraints: hash.snark_constraints(),
+                    snark_constraints_computed: hash.snark_constraints_computed(),
+                    air_trace_cells: air_cost.map(|c| c.cells()),
+                    air_cost,
+                    air_cells_per_byte: air_cost.map(|c| c.cells_per_byte()),
+                    plonk_gates: hash.plonk_gates(input).map_err(|e| format!("{}: {}", hash.name(), e))?,
+                    lookup_cost: hash.lookup_cost(input).map_err(|e| format!("{}: {}", hash.name(), e))?,
+                    plonkish_cost: hash.plonkish_cost(input).map_err(|e| format!("{}: {}", hash.name(), e))?,
+                    r1cs: hash.r1cs_cost(input).map_err(|e| format!("{}: {}", hash.name(), e))?,
+                    timing: measurement
+                        .and_then(|m| m.timing.as_ref())
+                        .map(TimingRecord::from_result),
+                    throughput_mb_s: measurement
+                        .and_then(|m| m.timing.as_ref())
+                        .and_then(|t| t.stats.throughput_mb_s(input.len())),
+                    setup: measurement
+                        .map(|m| {
+                            m.setup
+                                .iter()
+                                .map(|r| SetupRecord {
+                                    phase: r.name.clone(),
+                                    timing: TimingRecord::from_result(r),
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    groth16: measurement.and_then(|m| m.groth16.as_ref()).map(Groth16Record::from_cost),
+                    merkle: measurement
+                        .map(|m| m.merkle.iter().map(MerkleRecord::from_cost).collect())
+                        .unwrap_or_default(),
+                    chain: measurement.and_then(|m| m.chain.as_ref()).map(ChainRecord::from_cost),
+                    known_answers: measurement
+                        .map(|m| m.known_answers.iter().map(KnownAnswerRecord::from_answer).collect())
+                        .unwrap_or_default(),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Report {
+            schema_version: SCHEMA_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION"),
+            generated_at_unix: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            environment: Environment::current(),
+            input_bytes: input.len(),
+            hashes,
+        })
+    }
+
+    pub fn write_json(&self, out: impl io::Write) -> io::Result<()> {
+        let mut out = out;
+        serde_json::to_writer_pretty(&mut out, self)?;
+        writeln!(out)
+    }
+
+    pub fn write_csv(&self, out: impl io::Write) -> io::Result<()> {
+        let mut writer = csv::Writer::from_writer(out);
+        for hash in &self.hashes {
+            let mut rows: Vec<Row> = Vec::new();
+            if let Some(timing) = &hash.timing {
+                rows.push(Row::timed("hash", timing));
+            }
+            rows.extend(hash.setup.iter().map(|s| Row::timed(&s.phase, &s.timing)));
+            if let Some(proof) = &hash.groth16 {
+                rows.extend(Row::proof(["groth16 setup", "groth16 prove", "groth16 verify"], proof, None));
+            }
+            for merkle in &hash.merkle {
+                rows.push(Row {
+                    merkle: Some(merkle),
+                    ..Row::timed("merkle path", &merkle.native)
+                });
+                if let Some(proof) = &merkle.groth16 {
+                    rows.extend(Row::proof(["merkle setup", "merkle prove", "merkle verify"], proof, Some(merkle)));
+                }
+            }
+            if let Some(chain) = &hash.chain {
+                rows.push(Row {
+                    chain: Some(chain),
+                    ..Row::timed("chain step", &chain.native_step)
+                });
+                if let Some(proof) = &chain.groth16 {
+                    rows.extend(
+                        Row::proof(["chain setup", "chain prove", "chain verify"], proof, None)
+                            .map(|row| Row { chain: Some(chain), ..row }),
+                    );
+                }
+                if let Some(nova) = &chain.nova {
+                    let stages = [
+                        ("nova setup", &nova.setup),
+                        ("nova fold", &nova.fold),
+                        ("nova verify", &nova.verify),
+                        ("nova compress setup", &nova.compress_setup),
+                        ("nova compress", &nova.compress),
+                        ("nova compressed verify", &nova.compressed_verify),
+                    ];
+                    rows.extend(stages.map(|(measurement, timing)| Row {
+                        chain: Some(chain),
+                        nova: Some(nova),
+                        ..Row::timed(measurement, timing)
+                    }));
+                }
+            }
+            rows.extend(hash.known_answers.iter().map(|answer| Row {
+                measurement: "known answer",
+                timing: None,
+                groth16: None,
+                merkle: None,
+                chain: None,
+                nova: None,
+                known_answer: Some(answer),
+            }));
+            if rows.is_empty() {
+                rows.push(Row {
+                    measurement: "none",
+                    timing: None,
+                    groth16: None,
+                    merkle: None,
+                    chain: None,
+                    nova: None,
+                    known_answer: None,
+                });
+            }
+
+            for Row { measurement, timing, groth16, merkle, chain, nova, known_answer } in rows {
+                writer.serialize(CsvRow {
+                    schema_version: self.schema_version,
+                    tool_version: self.tool_version,
+                    generated_at_unix: self.generated_at_unix,
+                    os: self.environment.os,
+                    arch: self.environment.arch,
+                    logical_cpus: self.environment.logical_cpus,
+                    input_bytes: self.input_bytes,
+                    name: hash.name,
+                    parameters: hash.parameters.as_deref(),
+                    domain: &hash.domain,
+                    output_size: hash.output_size,
+                    security_bits: hash.security_bits,
+                    snark_constraints: hash.snark_constraints,
+                    measurement,
+                    samples: timing.map(|t| t.samples),
+                    iterations_per_sample: timing.map(|t| t.iterations_per_sample),
+                    mean_ns: timing.map(|t| t.mean_ns),
+                    median_ns: timing.map(|t| t.median_ns),
+                    stddev_ns: timing.map(|t| t.stddev_ns),
+                    min_ns: timing.map(|t| t.min_ns),
+                    max_ns: timing.map(|t| t.max_ns),
+                    p95_ns: timing.map(|t| t.p95_ns),
+                    p99_ns: timing.map(|t| t.p99_ns),
+                    ci95_low_ns: timing.map(|t| t.ci95_low_ns),
+                    ci95_high_ns: timing.map(|t| t.ci95_high_ns),
+                    r1cs_constraints: hash.r1cs.map(|c| c.constraints),
+                    r1cs_variables: hash.r1cs.map(|c| c.variables()),
+                    r1cs_nonzero_entries: hash.r1cs.map(|c| c.nonzero_entries()),
+                    field_elements: hash.field_elements,
+                    air_trace_cells: hash.air_trace_cells,
+                    plonk_gates: hash.plonk_gates,
+                    lookup_gates: hash.lookup_cost.map(|c| c.gates),
+                    lookups: hash.lookup_cost.map(|c| c.lookups),
+                    lookup_table_rows: hash.lookup_cost.map(|c| c.table_rows),
+                    throughput_mb_s: if measurement == "hash" { hash.throughput_mb_s } else { None },
+                    proof_bytes: groth16.map(|g| g.proof_bytes),
+                    proving_key_bytes: groth16.map(|g| g.proving_key_bytes),
+                    verifying_key_bytes: groth16.map(|g| g.verifying_key_bytes),
+                    merkle_depth: merkle.map(|m| m.depth),
+                    merkle_arity: merkle.map(|m| m.arity),
+                    merkle_constraints: merkle.map(|m| m.r1cs.constraints),
+                    chain_steps: chain.map(|c| c.steps),
+                    chain_hashes_per_step: chain.map(|c| c.hashes_per_step),
+                    chain_step_constraints: chain.map(|c| c.step.constraints),
+                    plonkish_advice_columns: hash.plonkish_cost.map(|c| c.advice_columns),
+                    plonkish_rows: hash.plonkish_cost.map(|c| c.rows),
+                    plonkish_lookup_tables: hash.plonkish_cost.map(|c| c.lookup_tables),
+                    plonkish_table_rows: hash.plonkish_cost.map(|c| c.table_rows),
+                    plonkish_degree: hash.plonkish_cost.map(|c| c.degree),
+                    air_trace_width: hash.air_cost.map(|c| c.width),
+                    air_rows: hash.air_cost.map(|c| c.rows),
+                    air_degree: hash.air_cost.map(|c| c.degree),
+                    air_cells_per_byte: hash.air_cells_per_byte,
+                    known_answer: known_answer.map(|a| a.name),
+                    known_answer_passed: known_answer.map(|a| a.passed),
+                    snark_constraints_computed: hash.snark_constraints_computed,
+                    nova_step_constraints: nova.map(|n| n.step.constraints),
+                    nova_augmented_constraints: nova.map(|n| n.augmented_constraints),
+                    nova_verifier_constraints: nova.map(|n| n.verifier_constraints),
+                    nova_secondary_constraints: nova.map(|n| n.secondary_constraints),
+                    nova_compressed_proof_bytes: nova.map(|n| n.compressed_proof_bytes),
+                })?;
+            }
+        }
+        writer.flush()
+    }
+}
@@ -0,0 +1,675 @@
+use crate::bench::{format_duration, format_throughput, BenchConfig, BenchResult, Iterations, Measurement};
+use crate::chain::ChainCost;
+use crate::circuits::R1csCost;
+use crate::groth16::Groth16Cost;
+use crate::hashes::{Domain, HashError, HashFunction, LookupCost, PlonkishCost, NON_STANDARD};
+use crate::merkle::MerkleCost;
+
+// Formats a count with thousands separators, e.g. 25000 -> "25,000"
+fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+// Auto-scales a byte count to B/KiB/MiB
+fn format_bytes(bytes: usize) -> String {
+    if bytes < 1 << 10 {
+        format!("{} B", bytes)
+    } else if bytes < 1 << 20 {
+        format!("{:.1} KiB", bytes as f64 / (1 << 10) as f64)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1 << 20) as f64)
+    }
+}
+
+pub fn print_header(registry: &[Box<dyn HashFunction>], input: &[u8], config: Option<&BenchConfig>) {
+    println!("\n{}", "=".repeat(70));
+    println!("    Ethereum Hash Function Comparison Framework");
+    println!("{}", "=".repeat(70));
+    println!("\nComparing traditional vs SNARK-friendly hash functions");
+    println!("Input: {} bytes", input.len());
+    if let Some(config) = config {
+        let iterations = match config.iterations {
+            Iterations::Fixed(n) => format!("{} iterations", n),
+            Iterations::TimeBudget(budget) => format!("{:.2} s budget", budget.as_secs_f64()),
+        };
+        println!("Samples: {} x {} (after {} ms warmup)",
+                 config.samples,
+                 iterations,
+                 config.warmup.as_millis());
+    }
+    println!();
+    for hash in registry {
+        let parameters = hash.parameters().map(|p| format!(" ({})", p)).unwrap_or_default();
+        println!("  {:<22} {}-byte output, {} domain, {}-bit security{}",
+                 hash.name(),
+                 hash.output_size(),
+                 hash.domain(),
+                 hash.security_bits(),
+                 parameters);
+    }
+    if registry.iter().any(|hash| hash.name().ends_with(NON_STANDARD)) {
+        println!("\n  {} non-standard or unchecked constants: digests may differ from the reference implementation", NON_STANDARD);
+    }
+    println!("{}\n", "=".repeat(70));
+}
+
+pub fn print_section(title: &str) {
+    println!("\n>>> {}", title);
+    println!("{}", "-".repeat(70));
+}
+
+// `costs` holds the measured R1CS size per hash, parallel to `registry`
+pub fn print_constraints(
+    title: &str,
+    registry: &[Box<dyn HashFunction>],
+    input: &[u8],
+    costs: &[Option<R1csCost>],
+) -> Result<(), String> {
+    print_section(title);
+    println!("\n  (Lower is better for zero-knowledge proofs)\n");
+    println!("  Literature figures, or computed where marked:\n");
+
+    for hash in registry {
+        let Some(constraints) = hash.snark_constraints() else {
+            let lookups = hash.lookup_cost(input).map_err(|e| format!("{}: {}", hash.name(), e))?;
+            match (hash.domain(), lookups) {
+                (Domain::Field(_), Some(_)) => println!("  {:<22} => n/a (lookup-based, see the lookup cost below)", hash.name()),
+                _ => println!("  {:<22} => n/a (not a SNARK-field hash)", hash.name()),
+            }
+            continue;
+        };
+        let source = if hash.snark_constraints_computed() { " (computed)" } else { "" };
+        println!("  {:<22} => ~{:>6} constraints{}", hash.name(), constraints, source);
+    }
+
+    println!("\n  Measured by circuit synthesis ({}-byte input):\n", input.len());
+    for (hash, cost) in registry.iter().zip(costs) {
+        match cost {
+            Some(cost) => println!("  {:<22} => {:>9} constraints, {:>9} variables, {:>9} non-zero entries (A/B/C {}/{}/{})",
+                                   hash.name(),
+                                   format_count(cost.constraints),
+                                   format_count(cost.variables()),
+                                   format_count(cost.nonzero_entries()),
+                                   format_count(cost.nonzero_a),
+                                   format_count(cost.nonzero_b),
+                                   format_count(cost.nonzero_c)),
+            None => println!("  {:<22} => no circuit available", hash.name()),
+        }
+    }
+    Ok(())
+}
+
+// AIR trace of each hash with a layout over `input`, next to its measured
+// R1CS size where it has a circuit (`costs`, parallel to `registry`)
+pub fn print_air_costs(registry: &[Box<dyn HashFunction>], input: &[u8], costs: &[Option<R1csCost>]) -> Result<(), String> {
+    let mut header = false;
+    for (hash, r1cs) in registry.iter().zip(costs) {
+        let cost = hash.air_cost(input).map_err(|e| format!("{}: {}", hash.name(), e))?;
+        if let Some(cost) = cost {
+            if !header {
+                println!("\n  STARK cost, AIR trace at constraint degree {} ({}-byte input):\n", cost.degree, input.len());
+                header = true;
+            }
+            let r1cs = r1cs.map(|c| format!(", R1CS {} constraints", format_count(c.constraints))).unwrap_or_default();
+            println!("  {:<22} => {:>5} columns x {:>3} rows = {:>9} cells, {:>6.1} per absorbed byte{}",
+                     hash.name(),
+                     format_count(cost.width),
+                     format_count(cost.rows),
+                     format_count(cost.cells()),
+                     cost.cells_per_byte(),
+                     r1cs);
+        }
+    }
+    Ok(())
+}
+
+// Vanilla PLONK gate estimates for each hash that has one
+pub fn print_plonk_costs(registry: &[Box<dyn HashFunction>], input: &[u8]) -> Result<(), String> {
+    let mut header = false;
+    for hash in registry {
+        let gates = hash.plonk_gates(input).map_err(|e| format!("{}: {}", hash.name(), e))?;
+        if let Some(gates) = gates {
+            if !header {
+                println!("\n  PLONK cost, fan-in-2 arithmetic gates ({}-byte input):\n", input.len());
+                header = true;
+            }
+            println!("  {:<22} => {:>9} gates", hash.name(), format_count(gates));
+        }
+    }
+    Ok(())
+}
+
+// Gates, lookups and table size of each lookup-based hash
+pub fn print_lookup_costs(registry: &[Box<dyn HashFunction>], input: &[u8]) -> Result<(), String> {
+    let mut header = false;
+    for hash in registry {
+        let cost = hash.lookup_cost(input).map_err(|e| format!("{}: {}", hash.name(), e))?;
+        if let Some(cost) = cost {
+            if !header {
+                println!("\n  Lookup-argument cost, gates and lookups counted separately ({}-byte input):\n", input.len());
+                header = true;
+            }
+            println!("  {:<22} => {:>9} gates + {:>5} lookups (table of {} rows, paid once per circuit)",
+                     hash.name(),
+                     format_count(cost.gates),
+                     format_count(cost.lookups),
+                     format_count(cost.table_rows));
+        }
+    }
+    Ok(())
+}
+
+// Advice columns, rows, lookup tables and degree of each hash with a
+// Plonkish layout
+pub fn print_plonkish_costs(registry: &[Box<dyn HashFunction>], input: &[u8]) -> Result<(), String> {
+    let mut header = false;
+    for hash in registry {
+        let cost = hash.plonkish_cost(input).map_err(|e| format!("{}: {}", hash.name(), e))?;
+        if let Some(cost) = cost {
+            if !header {
+                println!("\n  Plonkish cost, halo2-style layout ({}-byte input):\n", input.len());
+                header = true;
+            }
+            let tables = match cost.lookup_tables {
+                0 => "no lookups".to_string(),
+                1 => format!("1 lookup table of {} rows", format_count(cost.table_rows)),
+                n => format!("{} lookup tables of {} rows in all", n, format_count(cost.table_rows)),
+            };
+            println!("  {:<22} => {:>7} rows x {:>3} advice columns, degree {}, {} (k = {})",
+                     hash.name(),
+                     format_count(cost.rows),
+                     cost.advice_columns,
+                     cost.degree,
+                     tables,
+                     domain_bits(&cost));
+        }
+    }
+    Ok(())
+}
+
+// log2 of the smallest power-of-two domain holding the rows and the tables,
+// the k a halo2 circuit would be configured with
+fn domain_bits(cost: &PlonkishCost) -> u32 {
+    cost.rows.max(cost.table_rows).next_power_of_two().trailing_zeros()
+}
+
+// (baseline, candidate, heading): each "<candidate>-X" is compared with
+// "<baseline>-X", X being a field or, for byte hashes, an output size, and
+// the candidate possibly marked non-standard
+const FAMILY_COMPARISONS: &[(&str, &str, &str)] = &[
+    ("Poseidon", "Poseidon2", "Poseidon2 vs Poseidon over the same field"),
+    ("MiMCSponge", "Poseidon", "Migrating from MiMCSponge to Poseidon"),
+    ("Poseidon", "Anemoi", "Anemoi vs Poseidon over the same field"),
+    ("Poseidon", "Griffin", "Griffin vs Poseidon over the same field"),
+    ("Poseidon", "RC", "Reinforced Concrete vs Poseidon over the same field"),
+    ("Poseidon", "Tip5", "Tip5 vs Poseidon over the same field"),
+    ("Poseidon", "Monolith", "Monolith vs Poseidon over the same field"),
+    ("Poseidon", "Pedersen", "Pedersen vs Poseidon over the same field"),
+    ("SHA", "BLAKE2s", "BLAKE2s vs SHA at the same output size"),
+    ("Keccak", "SHA3", "SHA3 vs Keccak: the same permutation with FIPS 202 padding"),
+];
+
+// "<label> a -> b <unit> (+x.x%)", or "<label> n/a" unless both sides have a figure
+fn format_change(label: &str, unit: &str, old: Option<usize>, new: Option<usize>) -> String {
+    match (old, new) {
+        (Some(old), Some(new)) => format!("{} {} -> {} {} ({:+.1}%)",
+                                          label,
+                                          format_count(old),
+                                          format_count(new),
+                                          unit,
+                                          (new as f64 / old as f64 - 1.0) * 100.0),
+        _ => format!("{} n/a", label),
+    }
+}
+
+// One line per field for each of `FAMILY_COMPARISONS`, matched by name; pairs
+// missing either side are skipped. A lookup-based hash's gates stand in for
+// its PLONK cost, with its lookups named in the unit, and AIR cells are
+// compared only where both sides have them.
+pub fn print_family_comparisons(
+    registry: &[Box<dyn HashFunction>],
+    input: &[u8],
+    measurements: &[Measurement],
+    costs: &[Option<R1csCost>],
+) -> Result<(), String> {
+    let index = |name: &str| registry.iter().position(|h| h.name().trim_end_matches(NON_STANDARD) == name);
+    let error = |i: usize| move |e: HashError| format!("{}: {}", registry[i].name(), e);
+    let gates = |i: usize| -> Result<(Option<usize>, Option<LookupCost>), String> {
+        let lookups = registry[i].lookup_cost(input).map_err(error(i))?;
+        match lookups {
+            Some(cost) => Ok((Some(cost.gates), Some(cost))),
+            None => Ok((registry[i].plonk_gates(input).map_err(error(i))?, None)),
+        }
+    };
+    for (baseline, candidate, heading) in FAMILY_COMPARISONS {
+        let mut header = false;
+        for (i, hash) in registry.iter().enumerate() {
+            let Some(field) = hash.name().strip_prefix(&format!("{}-", candidate)) else {
+                continue;
+            };
+            let Some(j) = index(&format!("{}-{}", baseline, field.trim_end_matches(NON_STANDARD))) else {
+                continue;
+            };
+            if !header {
+                println!("\n  {}:\n", heading);
+                header = true;
+            }
+            let speed = match (&measurements[j].timing, &measurements[i].timing) {
+                (Some(old), Some(new)) => format!("native speedup {:.2}x", old.stats.mean / new.stats.mean),
+                _ => "native speedup n/a".to_string(),
+            };
+            let r1cs = format_change("R1CS",
+                                     "constraints",
+                                     costs[j].map(|c| c.constraints),
+                                     costs[i].map(|c| c.constraints));
+            let ((old_gates, _), (new_gates, lookups)) = (gates(j)?, gates(i)?);
+            let unit = match lookups {
+                Some(cost) => format!("gates + {} lookups", format_count(cost.lookups)),
+                None => "gates".to_string(),
+            };
+            let mut parts = vec![speed, r1cs, format_change("PLONK", &unit, old_gates, new_gates)];
+            let air = (registry[j].air_cost(input).map_err(error(j))?.map(|c| c.cells()),
+                       registry[i].air_cost(input).map_err(error(i))?.map(|c| c.cells()));
+            if let (Some(_), Some(_)) = air {
+                parts.push(format_change("AIR", "cells", air.0, air.1));
+            }
+            println!("  {:<22} => {}", field, parts.join(", "));
+        }
+    }
+    Ok(())
+}
+
+// Checks every hash's reference vectors; fails if any mismatch
+pub fn print_known_answers(registry: &[Box<dyn HashFunction>]) -> Result<(), String> {
+    let mut failed = 0;
+    for hash in registry {
+        let answers = hash.known_answers();
+        if answers.is_empty() {
+            println!("  {:<22} => no reference vectors", hash.name());
+        }
+        for answer in answers {
+            let status = if answer.passed() { "ok" } else { "FAILED" };
+            println!("  {:<22} => {:<6} {}", hash.name(), status, answer.name);
+            if !answer.passed() {
+                failed += 1;
+                println!("  {:<22}    expected {}", "", answer.expected);
+                println!("  {:<22}    actual   {}", "", answer.actual);
+            }
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        n => Err(format!("{} known-answer check(s) failed", n)),
+    }
+}
+
+pub fn print_use_cases(registry: &[Box<dyn HashFunction>]) {
+    print_section("Use Case Recommendations");
+
+    for hash in registry {
+        let use_cases = hash.use_cases();
+        println!("\n  {}:", hash.name());
+        for line in use_cases.good_for {
+            println!("    ✓ {}", line);
+        }
+        for line in use_cases.bad_for {
+            println!("    ✗ {}", line);
+        }
+    }
+}
+
+fn print_row(property: &str, cells: impl Iterator<Item = String>) {
+    let cells: String = cells.map(|cell| format!(" {:<22}", cell)).collect();
+    println!("  {:<15}{}", property, cells);
+}
+
+pub fn print_summary_table(
+    registry: &[Box<dyn HashFunction>],
+    input: &[u8],
+    measurements: &[Measurement],
+    costs: &[Option<R1csCost>],
+) -> Result<(), String> {
+    print_section("Summary Comparison Table");
+
+    println!();
+    print_row("Property", registry.iter().map(|h| h.name().to_string()));
+    println!("  {}", "-".repeat(15 + 23 * registry.len()));
+    print_row("Speed", measurements.iter().map(|m| match &m.timing {
+        Some(result) => format!("{}/hash", format_duration(result.stats.mean)),
+        None => "-".to_string(),
+    }));
+    print_row("SNARK Cost", registry.iter().map(|h| match h.snark_constraints() {
+        Some(constraints) if h.snark_constraints_computed() => format!("~{} constr. (comp.)", format_count(constraints)),
+        Some(constraints) => format!("~{} constr.", format_count(constraints)),
+        None => "-".to_string(),
+    }));
+    print_row("R1CS (meas.)", costs.iter().map(|c| match c {
+        Some(cost) => format!("{} constr.", format_count(cost.constraints)),
+        None => "-".to_string(),
+    }));
+    let air = registry
+        .iter()
+        .map(|h| h.air_cost(input).map_err(|e| format!("{}: {}", h.name(), e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    print_row("STARK Cost", air.iter().map(|c| match c {
+        Some(cost) => format!("{} AIR cells", format_count(cost.cells())),
+        None => "-".to_string(),
+    }));
+    let gates = registry
+        .iter()
+        .map(|h| h.plonk_gates(input).map_err(|e| format!("{}: {}", h.name(), e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    print_row("PLONK Cost", gates.iter().map(|g| match g {
+        Some(gates) => format!("{} gates", format_count(*gates)),
+        None => "-".to_string(),
+    }));
+    let lookups = registry
+        .iter()
+        .map(|h| h.lookup_cost(input).map_err(|e| format!("{}: {}", h.name(), e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    print_row("Lookup Cost", lookups.iter().map(|c| match c {
+        Some(cost) => format!("{} + {} lookups", format_count(cost.gates), format_count(cost.lookups)),
+        None => "-".to_string(),
+    }));
+    let plonkish = registry
+        .iter()
+        .map(|h| h.plonkish_cost(input).map_err(|e| format!("{}: {}", h.name(), e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    print_row("Plonkish Cost", plonkish.iter().map(|c| match c {
+        Some(cost) if cost.lookup_tables > 0 => format!("{}r x {}c d{} +{} tbl",
+                                                        format_count(cost.rows),
+                                                        cost.advice_columns,
+                                                        cost.degree,
+                                                        cost.lookup_tables),
+        Some(cost) => format!("{}r x {}c d{}", format_count(cost.rows), cost.advice_columns, cost.degree),
+        None => "-".to_string(),
+    }));
+    print_row("Ethereum Use", registry.iter().map(|h| h.use_cases().ethereum_use.to_string()));
+    print_row("Best For", registry.iter().map(|h| h.use_cases().best_for.to_string()));
+    println!();
+    Ok(())
+}
+
+// One row per variant: time and R1CS cost per hash, per input byte and per
+// field element, plus the first (total) one-off setup phase
+pub fn print_sweep(
+    registry: &[Box<dyn HashFunction>],
+    input: &[u8],
+    measurements: &[Measurement],
+    costs: &[Option<R1csCost>],
+) -> Result<(), String> {
+    print_section("Arity Sweep");
+    println!();
+    println!("  {:<30} {:>9} {:>9} {:>12} {:>12} {:>13} {:>16} {:>12}",
+             "Variant", "Elements", "Absorbed", "Time/hash", "Time/byte", "Constr./hash", "Constr./element", "Setup");
+    println!("  {}", "-".repeat(120));
+
+    for ((hash, measurement), cost) in registry.iter().zip(measurements).zip(costs) {
+        let elements = hash.field_elements(input).map_err(|e| format!("{}: {}", hash.name(), e))?;
+        // Every permutation absorbs a full rate, so a short input at a wide
+        // arity pays for the padding; costs are per absorbed element and
+        // byte, as `AirCost::absorbed_bytes` counts them
+        let absorbed = elements.zip(hash.rate()).map(|(n, rate)| n.div_ceil(rate).max(1) * rate);
+        let absorbed_bytes = hash
+            .air_cost(input)
+            .map_err(|e| format!("{}: {}", hash.name(), e))?
+            .map(|air| air.absorbed_bytes);
+        let mean = measurement.timing.as_ref().map(|t| t.stats.mean);
+        let per_byte = match (mean, absorbed_bytes) {
+            (Some(mean), Some(bytes)) => format_duration(mean / bytes as f64),
+            _ => "-".to_string(),
+        };
+        let per_element = match (cost, absorbed) {
+            (Some(cost), Some(n)) => format_count((cost.constraints as f64 / n as f64).round() as usize),
+            _ => "-".to_string(),
+        };
+        let setup = measurement.setup.first().map(|r| format_duration(r.stats.mean));
+        let variant = match hash.parameters() {
+            Some(parameters) => format!("{} ({})", hash.name(), parameters),
+            None => hash.name().to_string(),
+        };
+        println!("  {:<30} {:>9} {:>9} {:>12} {:>12} {:>13} {:>16} {:>12}",
+                 variant,
+                 elements.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string()),
+                 absorbed.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string()),
+                 mean.map(format_duration).unwrap_or_else(|| "-".to_string()),
+                 per_byte,
+                 cost.map(|c| format_count(c.constraints)).unwrap_or_else(|| "-".to_string()),
+                 per_element,
+                 setup.unwrap_or_else(|| "-".to_string()));
+    }
+    println!();
+    Ok(())
+}
+
+pub fn print_result(label: &str, result: &BenchResult, input_bytes: usize) {
+    let stats = &result.stats;
+    let throughput = stats
+        .throughput_mb_s(input_bytes)
+        .map(|mb_s| format!(", {}", format_throughput(mb_s)))
+        .unwrap_or_default();
+    println!("  {:<22} => {} per hash (95% CI {} .. {}){}",
+             label,
+             format_duration(stats.mean),
+             format_duration(stats.ci95_low),
+             format_duration(stats.ci95_high),
+             throughput);
+    println!("  {:<22}    median {}, stddev {}, min {}, max {}, p95 {}, p99 {} ({} x {})",
+             "",
+             format_duration(stats.median),
+             format_duration(stats.stddev),
+             format_duration(stats.min),
+             format_duration(stats.max),
+             format_duration(stats.p95),
+             format_duration(stats.p99),
+             result.samples,
+             result.iterations_per_sample);
+}
+
+pub fn print_setup(hash: &dyn HashFunction, result: &BenchResult) {
+    println!("  {:<22} => {} ± {} ({}, {} runs)",
+             hash.name(),
+             format_duration(result.stats.mean),
+             format_duration(result.stats.stddev),
+             result.name,
+             result.samples);
+}
+
+pub fn print_proof(label: &str, cost: &Groth16Cost) {
+    println!("  {:<22} => {} constraints: setup {}, prove {} (95% CI {} .. {}), verify {}",
+             label,
+             format_count(cost.r1cs.constraints),
+             format_duration(cost.setup.stats.mean),
+             format_duration(cost.prove.stats.mean),
+             format_duration(cost.prove.stats.ci95_low),
+             format_duration(cost.prove.stats.ci95_high),
+             format_duration(cost.verify.stats.mean));
+    println!("  {:<22}    proof {}, proving key {}, verifying key {} (public inputs: {}, samples: {})",
+             "",
+             format_bytes(cost.proof_bytes),
+             format_bytes(cost.proving_key_bytes),
+             format_bytes(cost.verifying_key_bytes),
+             cost.r1cs.public_inputs,
+             cost.prove.samples);
+}
+
+// Each proved hash against the one that proves fastest
+pub fn print_proof_comparison(registry: &[Box<dyn HashFunction>], measurements: &[Measurement]) {
+    let proved: Vec<(&str, &Groth16Cost)> = registry
+        .iter()
+        .zip(measurements)
+        .filter_map(|(hash, m)| m.groth16.as_ref().map(|cost| (hash.name(), cost)))
+        .collect();
+    let Some(&(fastest, best)) = proved.iter().min_by(|a, b| a.1.prove.stats.mean.total_cmp(&b.1.prove.stats.mean)) else {
+        println!("  No hash with a BLS12-381 circuit selected");
+        return;
+    };
+    if proved.len() < 2 {
+        return;
+    }
+    println!("\n  Relative to {}:\n", fastest);
+    for (name, cost) in proved.iter().filter(|(name, _)| *name != fastest) {
+        println!("  {:<22} => {:.1}x the constraints, {:.1}x the setup, {:.1}x the proving time, {:.1}x the proving key",
+                 name,
+                 cost.r1cs.constraints as f64 / best.r1cs.constraints as f64,
+                 cost.setup.stats.mean / best.setup.stats.mean,
+                 cost.prove.stats.mean / best.prove.stats.mean,
+                 cost.proving_key_bytes as f64 / best.proving_key_bytes as f64);
+    }
+    println!("\n  Proof size and verification time barely depend on the circuit: a Groth16 proof is");
+    println!("  two G1 points and one G2 point, checked with three pairings plus one G1 term per");
+    println!("  public input.");
+}
+
+// One line per depth: circuit size, native path verification and, where
+// proved, Groth16
+pub fn print_merkle(label: &str, costs: &[MerkleCost]) {
+    for (i, cost) in costs.iter().enumerate() {
+        let proof = match &cost.groth16 {
+            Some(proof) => format!(", Groth16 prove {} verify {}",
+                                   format_duration(proof.prove.stats.mean),
+                                   format_duration(proof.verify.stats.mean)),
+            None => String::new(),
+        };
+        println!("  {:<22} depth {:>2} => {} constraints ({} per level), native path {}{}",
+                 if i == 0 { label } else { "" },
+                 cost.depth,
+                 format_count(cost.r1cs.constraints),
+                 format_count(cost.r1cs.constraints / cost.depth),
+                 format_duration(cost.native.stats.mean),
+                 proof);
+    }
+}
+
+// Every measured hash at its deepest tree, fewest constraints first
+pub fn print_merkle_summary(registry: &[Box<dyn HashFunction>], measurements: &[Measurement]) {
+    let mut deepest: Vec<(&str, &MerkleCost)> = registry
+        .iter()
+        .zip(measurements)
+        .filter_map(|(hash, m)| m.merkle.iter().max_by_key(|c| c.depth).map(|cost| (hash.name(), cost)))
+        .collect();
+    deepest.sort_by_key(|(_, cost)| cost.r1cs.constraints);
+    let Some(&(_, best)) = deepest.first() else {
+        println!("  No hash with a Merkle node gadget selected");
+        return;
+    };
+
+    println!("\n  At depth {} (arity {}):\n", best.depth, best.arity);
+    println!("  {:<22} {:>13} {:>9} {:>12} {:>14}",
+             "Hash", "Constraints", "vs best", "Native path", "Groth16 prove");
+    println!("  {}", "-".repeat(74));
+    for (name, cost) in &deepest {
+        println!("  {:<22} {:>13} {:>8.1}x {:>12} {:>14}",
+                 name,
+                 format_count(cost.r1cs.constraints),
+                 cost.r1cs.constraints as f64 / best.r1cs.constraints as f64,
+                 format_duration(cost.native.stats.mean),
+                 cost.groth16.as_ref().map(|p| format_duration(p.prove.stats.mean)).unwrap_or_else(|| "-".to_string()));
+    }
+
+    let without: Vec<&str> = registry
+        .iter()
+        .zip(measurements)
+        .filter(|(_, m)| m.merkle.is_empty())
+        .map(|(hash, _)| hash.name())
+        .collect();
+    if !without.is_empty() {
+        println!("\n  No node gadget: {}", without.join(", "));
+    }
+    println!("\n  Groth16 \"-\": not proved, either over BN254 (no pairing implementation here) or");
+    println!("  above --max-proof-constraints. Native path time recomputes the root from the leaf.");
+}
+
+// The step circuit, native step time and, where proved, Groth16 over the
+// whole chain
+pub fn print_chain(label: &str, cost: &ChainCost) {
+    let proof = match &cost.groth16 {
+        Some(proof) => format!(", Groth16 prove {} verify {}",
+                               format_duration(proof.prove.stats.mean),
+                               format_duration(proof.verify.stats.mean)),
+        None => String::new(),
+    };
+    println!("  {:<22} step => {} constraints, native {}; {} steps => {} constraints{}",
+             label,
+             format_count(cost.step.constraints),
+             format_duration(cost.native_step.stats.mean),
+             cost.steps,
+             format_count(cost.chain.constraints),
+             proof);
+    if let Some(nova) = &cost.nova {
+        println!("  {:<22} Nova => verifier {} constraints, fold {} per step; compressed prove {} verify {}, {}",
+                 "",
+                 format_count(nova.verifier_constraints),
+                 format_duration(nova.fold.stats.mean),
+                 format_duration(nova.compress.stats.mean),
+                 format_duration(nova.compressed_verify.stats.mean),
+                 format_bytes(nova.compressed_proof_bytes));
+    }
+}
+
+// Every measured hash by step circuit size
+pub fn print_chain_summary(registry: &[Box<dyn HashFunction>], measurements: &[Measurement]) {
+    let mut chains: Vec<(&str, &ChainCost)> = registry
+        .iter()
+        .zip(measurements)
+        .filter_map(|(hash, m)| m.chain.as_ref().map(|cost| (hash.name(), cost)))
+        .collect();
+    chains.sort_by_key(|(_, cost)| cost.step.constraints);
+    let Some(&(_, best)) = chains.first() else {
+        println!("  No hash with a chain benchmark selected (Poseidon and SHA-256 have one)");
+        return;
+    };
+
+    println!("\n  Over {} steps:\n", best.steps);
+    println!("  {:<22} {:>13} {:>9} {:>12} {:>13} {:>14}",
+             "Hash", "Per step", "vs best", "Native step", "Whole chain", "Groth16 prove");
+    println!("  {}", "-".repeat(88));
+    for (name, cost) in &chains {
+        println!("  {:<22} {:>13} {:>8.1}x {:>12} {:>13} {:>14}",
+                 name,
+                 format_count(cost.step.constraints),
+                 cost.step.constraints as f64 / best.step.constraints as f64,
+                 format_duration(cost.native_step.stats.mean),
+                 format_count(cost.chain.constraints),
+                 cost.groth16.as_ref().map(|p| format_duration(p.prove.stats.mean)).unwrap_or_else(|| "-".to_string()));
+    }
+    println!("\n  Per step is one step's circuit with both ends public. Groth16 \"-\": not proved,");
+    println!("  either over BN254 or above --max-proof-constraints.");
+
+    let folded: Vec<_> = chains.iter().filter_map(|(name, cost)| cost.nova.as_ref().map(|nova| (name, nova))).collect();
+    if folded.is_empty() {
+        return;
+    }
+    println!("\n  Folded with Nova over BN254/Grumpkin, one chain step per folding step:\n");
+    println!("  {:<22} {:>11} {:>11} {:>12} {:>14} {:>13} {:>10}",
+             "Hash", "Step", "Verifier", "Fold / step", "Compressed", "Verify", "Proof");
+    println!("  {}", "-".repeat(99));
+    for (name, nova) in folded {
+        println!("  {:<22} {:>11} {:>11} {:>12} {:>14} {:>13} {:>10}",
+                 name,
+                 format_count(nova.step.constraints),
+                 format_count(nova.verifier_constraints),
+                 format_duration(nova.fold.stats.mean),
+                 format_duration(nova.compress.stats.mean),
+                 format_duration(nova.compressed_verify.stats.mean),
+                 format_bytes(nova.compressed_proof_bytes));
+    }
+    println!("\n  Step is the step circuit Nova folds, Verifier the recursive verifier it adds");
+    println!("  to every step. Compressed is the Spartan proof of the final folded instance.");
+}
+
+pub fn print_digests(registry: &[Box<dyn HashFunction>], input: &[u8]) -> Result<(), String> {
+    for hash in registry {
+        let digest = hash.hash(input).map_err(|e| format!("{}: {}", hash.name(), e))?;
+        println!("  {:<22} => {}", hash.name(), hex::encode(digest));
+    }
+    Ok(())
+}